@@ -0,0 +1,271 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// 데이터그램마다 붙이는 HMAC-SHA256 태그 길이 (앞 16바이트만 사용)
+pub const MAC_SIZE: usize = 16;
+pub const NONCE_SIZE: usize = 16;
+// 챌린지 응답은 잘라내지 않은 HMAC 전체
+pub const RESPONSE_SIZE: usize = 32;
+
+const TCP_AUTH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// 송신측과 수신측이 미리 나눠 가진 페어링 비밀값
+#[derive(Clone, PartialEq, Eq)]
+pub struct PairingKey(Vec<u8>);
+
+impl PairingKey {
+    pub fn new(secret: impl AsRef<[u8]>) -> Self {
+        Self(secret.as_ref().to_vec())
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+// 로그에 비밀값이 찍히지 않도록
+impl fmt::Debug for PairingKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PairingKey(..)")
+    }
+}
+
+pub fn random_nonce() -> [u8; NONCE_SIZE] {
+    let mut nonce = [0u8; NONCE_SIZE];
+    getrandom::getrandom(&mut nonce).expect("OS 난수 생성 실패");
+    nonce
+}
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC은 모든 키 길이를 지원함");
+    for part in parts {
+        mac.update(part);
+    }
+    mac
+}
+
+// 송신측이 PSK를 알고 있다는 증명 (서버 nonce가 매번 달라서 재사용 불가)
+pub fn challenge_response(key: &PairingKey, session_id: u64, client_nonce: &[u8; NONCE_SIZE], server_nonce: &[u8; NONCE_SIZE]) -> [u8; RESPONSE_SIZE] {
+    hmac(key.as_bytes(), &[b"janggab-response", &session_id.to_be_bytes(), client_nonce, server_nonce])
+        .finalize()
+        .into_bytes()
+        .into()
+}
+
+pub fn verify_response(key: &PairingKey, session_id: u64, client_nonce: &[u8; NONCE_SIZE], server_nonce: &[u8; NONCE_SIZE], response: &[u8]) -> bool {
+    hmac(key.as_bytes(), &[b"janggab-response", &session_id.to_be_bytes(), client_nonce, server_nonce])
+        .verify_slice(response)
+        .is_ok()
+}
+
+// 패킷 끝에 잘린 MAC을 붙이고 검사
+#[derive(Clone)]
+pub struct PacketAuth {
+    key: Vec<u8>,
+}
+
+impl fmt::Debug for PacketAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PacketAuth(..)")
+    }
+}
+
+impl PacketAuth {
+    // handshake 메시지용 (PSK 그대로 사용)
+    pub fn new(key: &PairingKey) -> Self {
+        Self { key: key.as_bytes().to_vec() }
+    }
+
+    // 세션마다 다른 키를 써서 이전 세션의 패킷을 다시 보내도 통과하지 못하게 함
+    pub fn for_session(key: &PairingKey, session_id: u64, client_nonce: &[u8; NONCE_SIZE], server_nonce: &[u8; NONCE_SIZE]) -> Self {
+        let session_key = hmac(key.as_bytes(), &[b"janggab-session", &session_id.to_be_bytes(), client_nonce, server_nonce])
+            .finalize()
+            .into_bytes();
+        Self { key: session_key.to_vec() }
+    }
+
+    pub fn seal(&self, packet: &mut Vec<u8>) {
+        let tag = hmac(&self.key, &[packet]).finalize().into_bytes();
+        packet.extend_from_slice(&tag[..MAC_SIZE]);
+    }
+
+    // MAC이 맞으면 태그를 뗀 본문을 반환
+    pub fn open<'a>(&self, packet: &'a [u8]) -> Option<&'a [u8]> {
+        if packet.len() < MAC_SIZE {
+            return None;
+        }
+        let (body, tag) = packet.split_at(packet.len() - MAC_SIZE);
+        hmac(&self.key, &[body]).verify_truncated_left(tag).ok()?;
+        Some(body)
+    }
+}
+
+// TCP 서버측 챌린지/응답
+// 서버 → nonce_s, 클라이언트 → nonce_c | HMAC(client), 서버 → HMAC(server)
+// 상대가 PSK를 모르면 Ok(false)
+pub fn tcp_accept(stream: &mut TcpStream, key: &PairingKey) -> io::Result<bool> {
+    with_timeout(stream, |stream| {
+        let server_nonce = random_nonce();
+        stream.write_all(&server_nonce)?;
+
+        let mut client_nonce = [0u8; NONCE_SIZE];
+        let mut response = [0u8; RESPONSE_SIZE];
+        stream.read_exact(&mut client_nonce)?;
+        stream.read_exact(&mut response)?;
+        if hmac(key.as_bytes(), &[b"janggab-tcp-client", &server_nonce, &client_nonce]).verify_slice(&response).is_err() {
+            return Ok(false);
+        }
+
+        let proof = hmac(key.as_bytes(), &[b"janggab-tcp-server", &client_nonce, &server_nonce]).finalize().into_bytes();
+        stream.write_all(&proof)?;
+        Ok(true)
+    })
+}
+
+// TCP 클라이언트측: 서버도 같은 PSK를 아는지 확인
+pub fn tcp_connect(stream: &mut TcpStream, key: &PairingKey) -> io::Result<bool> {
+    with_timeout(stream, |stream| {
+        let mut server_nonce = [0u8; NONCE_SIZE];
+        stream.read_exact(&mut server_nonce)?;
+
+        let client_nonce = random_nonce();
+        let response = hmac(key.as_bytes(), &[b"janggab-tcp-client", &server_nonce, &client_nonce]).finalize().into_bytes();
+        stream.write_all(&client_nonce)?;
+        stream.write_all(&response)?;
+
+        let mut proof = [0u8; RESPONSE_SIZE];
+        stream.read_exact(&mut proof)?;
+        Ok(hmac(key.as_bytes(), &[b"janggab-tcp-server", &client_nonce, &server_nonce]).verify_slice(&proof).is_ok())
+    })
+}
+
+fn with_timeout(stream: &mut TcpStream, f: impl FnOnce(&mut TcpStream) -> io::Result<bool>) -> io::Result<bool> {
+    let previous = stream.read_timeout()?;
+    stream.set_read_timeout(Some(TCP_AUTH_TIMEOUT))?;
+    let result = f(stream);
+    stream.set_read_timeout(previous)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn key(secret: &str) -> PairingKey {
+        PairingKey::new(secret)
+    }
+
+    #[test]
+    fn debug_hides_secret() {
+        assert_eq!(format!("{:?}", key("hunter2")), "PairingKey(..)");
+        assert_eq!(format!("{:?}", PacketAuth::new(&key("hunter2"))), "PacketAuth(..)");
+    }
+
+    #[test]
+    fn challenge_response_needs_same_key_and_nonces() {
+        let client_nonce = [1; NONCE_SIZE];
+        let server_nonce = [2; NONCE_SIZE];
+        let response = challenge_response(&key("a"), 7, &client_nonce, &server_nonce);
+        assert!(verify_response(&key("a"), 7, &client_nonce, &server_nonce, &response));
+
+        assert!(!verify_response(&key("b"), 7, &client_nonce, &server_nonce, &response));
+        assert!(!verify_response(&key("a"), 8, &client_nonce, &server_nonce, &response));
+        assert!(!verify_response(&key("a"), 7, &[9; NONCE_SIZE], &server_nonce, &response));
+        assert!(!verify_response(&key("a"), 7, &client_nonce, &[9; NONCE_SIZE], &response));
+        // 잘린 응답은 앞부분이 맞아도 거절
+        assert!(!verify_response(&key("a"), 7, &client_nonce, &server_nonce, &response[..MAC_SIZE]));
+    }
+
+    #[test]
+    fn packet_auth_round_trip() {
+        let auth = PacketAuth::new(&key("a"));
+        let mut packet = b"frame chunk".to_vec();
+        auth.seal(&mut packet);
+        assert_eq!(packet.len(), b"frame chunk".len() + MAC_SIZE);
+        assert_eq!(auth.open(&packet), Some(&b"frame chunk"[..]));
+    }
+
+    #[test]
+    fn packet_auth_rejects_tampering() {
+        let auth = PacketAuth::new(&key("a"));
+        let mut packet = b"frame chunk".to_vec();
+        auth.seal(&mut packet);
+
+        for index in [0, packet.len() - 1] {
+            let mut tampered = packet.clone();
+            tampered[index] ^= 1;
+            assert_eq!(auth.open(&tampered), None, "{}번째 바이트 변조", index);
+        }
+        assert_eq!(PacketAuth::new(&key("b")).open(&packet), None);
+        assert_eq!(auth.open(&packet[..packet.len() - 1]), None);
+        assert_eq!(auth.open(&packet[..MAC_SIZE - 1]), None);
+    }
+
+    #[test]
+    fn session_auth_differs_per_session() {
+        let (client_nonce, server_nonce) = ([1; NONCE_SIZE], [2; NONCE_SIZE]);
+        let session = PacketAuth::for_session(&key("a"), 7, &client_nonce, &server_nonce);
+        let mut packet = b"frame chunk".to_vec();
+        session.seal(&mut packet);
+
+        assert!(PacketAuth::for_session(&key("a"), 7, &client_nonce, &server_nonce).open(&packet).is_some());
+        // 이전 세션이나 handshake 키로 봉인한 패킷은 통과하지 못함
+        assert!(PacketAuth::for_session(&key("a"), 8, &client_nonce, &server_nonce).open(&packet).is_none());
+        assert!(PacketAuth::for_session(&key("a"), 7, &client_nonce, &[3; NONCE_SIZE]).open(&packet).is_none());
+        assert!(PacketAuth::new(&key("a")).open(&packet).is_none());
+    }
+
+    // 서버 스레드에서 tcp_accept를 실행하고 양쪽 결과를 돌려줌
+    fn tcp_pair(server_key: PairingKey, client_key: PairingKey) -> (io::Result<bool>, io::Result<bool>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            tcp_accept(&mut stream, &server_key)
+        });
+        let mut stream = TcpStream::connect(addr).unwrap();
+        let client = tcp_connect(&mut stream, &client_key);
+        (server.join().unwrap(), client)
+    }
+
+    #[test]
+    fn tcp_same_key() {
+        let (server, client) = tcp_pair(key("a"), key("a"));
+        assert!(server.unwrap());
+        assert!(client.unwrap());
+    }
+
+    #[test]
+    fn tcp_key_mismatch() {
+        let (server, client) = tcp_pair(key("a"), key("b"));
+        assert!(!server.unwrap());
+        // 서버는 증명을 보내지 않고 연결을 닫음
+        assert_eq!(client.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn tcp_connect_rejects_fake_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        // 키를 모르는 서버가 응답을 읽고 아무 증명이나 보냄
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(&random_nonce()).unwrap();
+            let mut request = [0u8; NONCE_SIZE + RESPONSE_SIZE];
+            stream.read_exact(&mut request).unwrap();
+            stream.write_all(&[0u8; RESPONSE_SIZE]).unwrap();
+        });
+        let mut stream = TcpStream::connect(addr).unwrap();
+        assert!(!tcp_connect(&mut stream, &key("a")).unwrap());
+        server.join().unwrap();
+    }
+}
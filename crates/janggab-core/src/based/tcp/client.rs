@@ -3,16 +3,22 @@ use std::net::TcpStream;
 use std::thread;
 use std::sync::mpsc;
 
+use crate::endpoint::EndpointConfig;
+
 #[allow(dead_code)]
 pub fn client_main(ip: &str) {
     // WSL의 default IP를 여기에 입력하세요
     // ip route | grep default 명령어로 얻은 IP 주소 사용
-    // 예: "172.20.240.1:8080"
-    let server_addrs = vec![
-        "127.0.0.1:52525",     // localhost
-        "localhost:52525",     // localhost 호스트명
-        ip,
+    // 예: "172.20.240.1" 또는 "172.20.240.1:8080"
+    client_main_with(&EndpointConfig::with_peer(ip))
+}
+
+#[allow(dead_code)]
+pub fn client_main_with(endpoint: &EndpointConfig) {
+    let mut server_addrs = vec![
+        endpoint.loopback_addr().to_string(), // localhost
     ];
+    server_addrs.extend(endpoint.peer.clone());
     
     println!("🔧 연결 시도할 주소들:");
     for addr in &server_addrs {
@@ -25,7 +31,7 @@ pub fn client_main(ip: &str) {
     for server_addr in &server_addrs {
         println!("🔄 서버 {}에 연결 중...", server_addr);
         
-        match TcpStream::connect(server_addr) {
+        match endpoint.resolve(server_addr).and_then(TcpStream::connect) {
             Ok(s) => {
                 println!("✅ {}에 연결 성공!", server_addr);
                 stream = Some(s);
@@ -105,11 +111,11 @@ pub fn client_main(ip: &str) {
                     break;
                 }
                 
-                if !message.is_empty() {
-                    if let Err(e) = writeln!(stream, "{}", message) {
-                        eprintln!("❌ 메시지 전송 오류: {}", e);
-                        break;
-                    }
+                if !message.is_empty()
+                    && let Err(e) = writeln!(stream, "{}", message)
+                {
+                    eprintln!("❌ 메시지 전송 오류: {}", e);
+                    break;
                 }
             }
             Err(e) => {
@@ -5,14 +5,27 @@ use std::sync::{Arc, Mutex};
 use std::thread;
 use chrono::Local;
 
+use crate::endpoint::EndpointConfig;
+
 #[allow(dead_code)]
 type ClientMap = Arc<Mutex<HashMap<String, TcpStream>>>;
 
 #[allow(dead_code)]
 pub fn server_main() {
-    // WSL의 default IP 주소를 여기에 입력하세요
-    // 예: "172.20.240.1:8080" 또는 ip route | grep default 결과의 IP 사용
-    let server_addr = "0.0.0.0:52525"; // 모든 인터페이스에서 연결 허용
+    // 기본값은 모든 인터페이스의 52525 포트에서 연결 허용
+    server_main_with(&EndpointConfig::default())
+}
+
+#[allow(dead_code)]
+pub fn server_main_with(endpoint: &EndpointConfig) {
+    let listener = endpoint.bind_tcp().expect("서버 바인딩 실패");
+    server_main_on(listener);
+}
+
+// 포트 0으로 바인딩한 리스너도 받을 수 있도록 (실제 포트는 local_addr로 확인)
+#[allow(dead_code)]
+pub fn server_main_on(listener: TcpListener) {
+    let server_addr = listener.local_addr().expect("바인딩 주소 확인 실패");
     
     println!("🔧 네트워크 디버깅 정보:");
     println!("   - 서버 바인딩 주소: {}", server_addr);
@@ -26,7 +39,6 @@ pub fn server_main() {
         Err(_) => println!("   - IP 주소 확인 실패"),
     }
     
-    let listener = TcpListener::bind(server_addr).expect("서버 바인딩 실패");
     println!("🚀 채팅 서버가 {}에서 시작되었습니다", server_addr);
     println!("📡 클라이언트 연결을 기다리는 중...\n");
     
@@ -94,7 +106,7 @@ fn broadcast_message(clients: &ClientMap, message: &str) {
     let mut disconnected_clients = Vec::new();
     
     for (client_name, stream) in clients_lock.iter_mut() {
-        if let Err(_) = writeln!(stream, "{}", message) {
+        if writeln!(stream, "{}", message).is_err() {
             disconnected_clients.push(client_name.clone());
         }
     }
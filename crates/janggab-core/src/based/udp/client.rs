@@ -1,46 +1,41 @@
 // client.rs
-use std::net::UdpSocket;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use std::thread;
 
-pub fn client_main() {
-    // 서버 IP 입력 (예: "172.20.240.1" for WSL)
-    // Enter server IP (e.g., "172.20.240.1" for WSL)
-    let server_ip = "your_ip";
+use crate::crypto::SecurityConfig;
+use crate::endpoint::EndpointConfig;
+use crate::handshake::{self, Hello, StreamKind};
 
-    let socket = UdpSocket::bind("0.0.0.0:0").expect("소켓 바인딩 실패"); // Failed to bind socket
-    socket.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+// 서버 IP (예: "172.20.240.1" for WSL, 포트를 붙이지 않으면 기본 포트)
+// Server IP (e.g., "172.20.240.1" for WSL, default port unless one is given)
+pub fn client_main(server_ip: &str) {
+    client_main_with(&EndpointConfig::with_peer(server_ip))
+}
 
-    println!("🔄 서버 {}에 연결 시도 중...", server_ip); // Attempting to connect to server...
+pub fn client_main_with(endpoint: &EndpointConfig) {
+    let (socket, server_addr) = match endpoint.connect_udp() {
+        Ok(connected) => connected,
+        Err(e) => {
+            println!("❌ 소켓 준비 실패: {}. 종료합니다.", e); // Failed to prepare socket. Exiting.
+            return;
+        }
+    };
 
-    // 연결 시도 (HELLO 메시지 반복 전송)
-    // Connection attempt (repeatedly send HELLO message)
-    let mut connected = false;
-    let start = Instant::now();
-    while !connected && start.elapsed().as_secs() < 10 {
-        socket.send_to(b"HELLO", server_ip).unwrap();
-        let mut buf = [0u8; 1024];
+    println!("🔄 서버 {}에 연결 시도 중...", server_addr); // Attempting to connect to server...
 
-        match socket.recv_from(&mut buf) {
-            Ok((size, addr)) => {
-                let msg = String::from_utf8_lossy(&buf[..size]);
-                if msg.trim() == "CONNECTED" {
-                    println!("✅ 서버에 연결됨: {}", addr); // Connected to server:
-                    connected = true;
-                    break;
-                }
-            }
-            Err(_) => {
-                println!("⏳ 연결 대기중..."); // Waiting for connection...
-                thread::sleep(Duration::from_secs(1));
-            }
+    // 연결 시도 (Hello 메시지 반복 전송, 버전이 다르면 바로 실패)
+    // Connection attempt (repeatedly send Hello, fails immediately on version mismatch)
+    let hello = Hello::new(StreamKind::Data);
+    let server_addr = match handshake::connect(&socket, server_addr, &hello, &SecurityConfig::default(), Duration::from_secs(10)) {
+        Ok(session) => {
+            println!("✅ 서버에 연결됨: {}", session.peer); // Connected to server:
+            session.peer
         }
-    }
-
-    if !connected {
-        println!("❌ 서버 연결 실패. 종료합니다."); // Server connection failed. Exiting.
-        return;
-    }
+        Err(e) => {
+            println!("❌ 서버 연결 실패: {}. 종료합니다.", e); // Server connection failed. Exiting.
+            return;
+        }
+    };
 
     println!("💬 데이터를 쉬지 않고 전송합니다. 종료하려면 Ctrl+C"); // Continuously sending data. Press Ctrl+C to exit.
 
@@ -59,7 +54,7 @@ pub fn client_main() {
     // 데이터 전송 루프 (쉬지 않고 반복)
     // Data transmission loop (repeats continuously)
     loop {
-        match socket.send_to(&data_to_send, server_ip) {
+        match socket.send_to(&data_to_send, server_addr) {
             Ok(bytes_sent) => {
                 println!("➡️ {} 바이트 전송됨.", bytes_sent); // bytes sent.
             }
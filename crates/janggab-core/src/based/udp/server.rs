@@ -2,43 +2,59 @@
 use std::net::UdpSocket;
 use std::time::Duration;
 
-#[allow(warnings)]
+use crate::crypto::SecurityConfig;
+use crate::endpoint::EndpointConfig;
+use crate::handshake::{self, AcceptPolicy, Responder, StreamKind};
+
 pub fn server_main() {
-    let server_addr = "0.0.0.0:52525";
-    let socket = UdpSocket::bind(server_addr).expect("서버 바인딩 실패");
+    server_main_with(&EndpointConfig::default())
+}
+
+pub fn server_main_with(endpoint: &EndpointConfig) {
+    let socket = endpoint.bind_udp().expect("서버 바인딩 실패");
+    server_main_on(socket);
+}
+
+// 포트 0으로 바인딩한 소켓도 받을 수 있도록 (실제 포트는 local_addr로 확인)
+#[allow(warnings)]
+pub fn server_main_on(socket: UdpSocket) {
+    let server_addr = socket.local_addr().expect("바인딩 주소 확인 실패");
     socket.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
 
     println!("🚀 UDP 서버가 {}에서 시작되었습니다", server_addr);
     println!("📡 클라이언트 연결을 기다리는 중...\n");
 
-    let mut buf = [0u8; 1024];
-    let mut client_addr = None;
-
-    // 초기 연결 대기
-    loop {
-        match socket.recv_from(&mut buf) {
-            Ok((size, addr)) => {
-                let msg = String::from_utf8_lossy(&buf[..size]);
-                println!("📨 {}로부터 메시지 수신: {}", addr, msg);
-
-                if msg.trim() == "HELLO" {
-                    println!("✅ 클라이언트 연결됨: {}", addr);
-                    client_addr = Some(addr);
-                    // 연결 확인 응답
-                    socket.send_to(b"CONNECTED", addr).unwrap();
-                    break;
-                }
-            }
-            Err(_) => {
-                println!("⏳ 클라이언트 연결 대기중...");
-            }
+    // 초기 연결 대기 (Data 스트림 Hello만 받아들임)
+    let policy = AcceptPolicy {
+        stream_kind: StreamKind::Data,
+        codecs: Vec::new(),
+        max_mtu: 1400,
+    };
+    let mut responder = Responder::new(policy, SecurityConfig::default());
+    let client_addr = match responder.wait(&socket) {
+        Ok(session) => {
+            println!("✅ 클라이언트 연결됨: {}", session.peer);
+            session.peer
         }
-    }
+        Err(e) => {
+            println!("❌ 연결 실패: {}", e);
+            return;
+        }
+    };
+
+    let mut buf = [0u8; 1024];
 
     // 연결 후 데이터 수신
     loop {
         match socket.recv_from(&mut buf) {
             Ok((size, addr)) => {
+                // Accept가 유실되어 다시 온 Hello에는 다시 응답
+                if handshake::is_handshake(&buf[..size]) {
+                    if addr == client_addr {
+                        let _ = responder.handle(&socket, addr, &buf[..size]);
+                    }
+                    continue;
+                }
                 let msg = String::from_utf8_lossy(&buf[..size]);
                 println!("💬 [{}] {}", addr, msg.trim());
             }
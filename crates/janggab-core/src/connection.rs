@@ -0,0 +1,65 @@
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+// 호출자에게 알리는 연결 상태 변화
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    // 처음 연결 시도 중
+    Connecting,
+    Connected,
+    // 상대가 liveness timeout 동안 아무것도 보내지 않음
+    Lost,
+    // 끊긴 뒤 다시 연결됨
+    Reconnected,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LivenessConfig {
+    // 이 간격마다 상대에게 heartbeat 전송
+    pub heartbeat_interval: Duration,
+    // 이 시간 동안 상대에게서 아무것도 받지 못하면 끊긴 것으로 봄
+    pub timeout: Duration,
+    // 끊긴 뒤 다시 연결을 시도하는 간격
+    pub reconnect_interval: Duration,
+}
+
+impl Default for LivenessConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_millis(500),
+            timeout: Duration::from_secs(3),
+            reconnect_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+// 상태가 바뀔 때만 이벤트를 보냄 (받는 쪽이 없거나 닫혔으면 무시)
+#[derive(Debug, Clone, Default)]
+pub struct StateNotifier {
+    events: Option<Sender<ConnectionState>>,
+    state: Option<ConnectionState>,
+}
+
+impl StateNotifier {
+    pub fn new(events: Option<Sender<ConnectionState>>) -> Self {
+        Self { events, state: None }
+    }
+
+    pub fn state(&self) -> Option<ConnectionState> {
+        self.state
+    }
+
+    pub fn set(&mut self, state: ConnectionState) {
+        if self.state == Some(state) {
+            return;
+        }
+        self.state = Some(state);
+        if let Some(events) = &self.events {
+            let _ = events.send(state);
+        }
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.state == Some(ConnectionState::Lost)
+    }
+}
@@ -0,0 +1,473 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::auth::{PacketAuth, PairingKey};
+
+// X25519 키 교환 + (선택) PSK로 HKDF 키 유도, ChaCha20-Poly1305로 암호화
+// PSK가 없으면 도청은 막지만 중간자 공격은 막지 못함
+
+pub const PUBLIC_KEY_SIZE: usize = 32;
+// 암호화된 데이터그램: magic(2) | counter(8) | ciphertext | tag(16)
+pub const ENCRYPTED_MAGIC: [u8; 2] = *b"JE";
+const COUNTER_SIZE: usize = 8;
+const TAG_SIZE: usize = 16;
+pub const ENCRYPTION_OVERHEAD: usize = 2 + COUNTER_SIZE + TAG_SIZE;
+// 스트림 레코드 하나의 최대 평문 크기
+const MAX_RECORD_SIZE: usize = 16 * 1024;
+// 이보다 오래된 카운터는 재전송 공격으로 보고 버림
+const REPLAY_WINDOW: u64 = 128;
+
+// 연결 보안 설정 (양쪽이 같아야 함)
+#[derive(Debug, Clone, Default)]
+pub struct SecurityConfig {
+    // 같은 키를 가진 상대만 받아들임
+    pub psk: Option<PairingKey>,
+    // 송신측: 암호화 요청, 수신측: 암호화하지 않는 연결은 거절
+    pub encrypt: bool,
+}
+
+// 키를 유도할 때 양쪽의 방향을 구분
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    // 연결을 요청한 쪽 (송신측, TCP 클라이언트)
+    Initiator,
+    // 연결을 받은 쪽 (수신측, TCP 서버)
+    Responder,
+}
+
+// 연결마다 새로 만드는 X25519 키 쌍
+pub struct KeyPair {
+    secret: StaticSecret,
+    public: [u8; PUBLIC_KEY_SIZE],
+}
+
+impl KeyPair {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes).expect("OS 난수 생성 실패");
+        let secret = StaticSecret::from(bytes);
+        let public = PublicKey::from(&secret).to_bytes();
+        Self { secret, public }
+    }
+
+    pub fn public_key(&self) -> [u8; PUBLIC_KEY_SIZE] {
+        self.public
+    }
+
+    pub fn shared_secret(&self, peer_public: &[u8; PUBLIC_KEY_SIZE]) -> [u8; 32] {
+        self.secret.diffie_hellman(&PublicKey::from(*peer_public)).to_bytes()
+    }
+}
+
+// 방향별 대칭키 두 개
+#[derive(Clone)]
+pub struct SessionKeys {
+    initiator_to_responder: [u8; 32],
+    responder_to_initiator: [u8; 32],
+}
+
+impl fmt::Debug for SessionKeys {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SessionKeys(..)")
+    }
+}
+
+impl SessionKeys {
+    // transcript에는 세션 id, 양쪽 공개키 등 양쪽이 똑같이 알고 있는 값을 넣음
+    pub fn derive(psk: Option<&PairingKey>, shared_secret: &[u8; 32], transcript: &[&[u8]]) -> Self {
+        let salt = psk.map_or(&b"janggab-no-psk"[..], |k| k.as_bytes());
+        let hkdf = Hkdf::<Sha256>::new(Some(salt), shared_secret);
+        let mut info = b"janggab-keys".to_vec();
+        for part in transcript {
+            info.extend_from_slice(part);
+        }
+
+        let mut okm = [0u8; 64];
+        hkdf.expand(&info, &mut okm).expect("64바이트는 HKDF 출력 한도 안");
+        Self {
+            initiator_to_responder: okm[..32].try_into().unwrap(),
+            responder_to_initiator: okm[32..].try_into().unwrap(),
+        }
+    }
+
+    // (보낼 때 키, 받을 때 키)
+    fn for_role(&self, role: Role) -> (ChaCha20Poly1305, ChaCha20Poly1305) {
+        let outgoing = ChaCha20Poly1305::new(&self.initiator_to_responder.into());
+        let incoming = ChaCha20Poly1305::new(&self.responder_to_initiator.into());
+        match role {
+            Role::Initiator => (outgoing, incoming),
+            Role::Responder => (incoming, outgoing),
+        }
+    }
+}
+
+fn nonce(counter: u64) -> Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&counter.to_be_bytes());
+    nonce.into()
+}
+
+// 최근 REPLAY_WINDOW개 카운터 중 이미 받은 것을 기억
+#[derive(Clone, Default)]
+struct ReplayWindow {
+    highest: Option<u64>,
+    // bit i = highest - i 를 받았음
+    seen: u128,
+}
+
+impl ReplayWindow {
+    fn is_fresh(&self, counter: u64) -> bool {
+        let Some(highest) = self.highest else {
+            return true;
+        };
+        if counter > highest {
+            return true;
+        }
+        let age = highest - counter;
+        age < REPLAY_WINDOW && self.seen & (1 << age) == 0
+    }
+
+    fn mark(&mut self, counter: u64) {
+        match self.highest {
+            Some(highest) if counter <= highest => self.seen |= 1 << (highest - counter),
+            Some(highest) => {
+                let shift = counter - highest;
+                self.seen = if shift >= REPLAY_WINDOW { 0 } else { self.seen << shift };
+                self.seen |= 1;
+                self.highest = Some(counter);
+            }
+            None => {
+                self.seen = 1;
+                self.highest = Some(counter);
+            }
+        }
+    }
+}
+
+// 데이터그램 단위 암호화 (순서가 바뀌어도 되도록 카운터를 패킷에 실음)
+#[derive(Clone)]
+pub struct PacketCipher {
+    outgoing: ChaCha20Poly1305,
+    incoming: ChaCha20Poly1305,
+    next_counter: u64,
+    replay: ReplayWindow,
+}
+
+impl PacketCipher {
+    pub fn new(keys: &SessionKeys, role: Role) -> Self {
+        let (outgoing, incoming) = keys.for_role(role);
+        Self {
+            outgoing,
+            incoming,
+            next_counter: 0,
+            replay: ReplayWindow::default(),
+        }
+    }
+
+    // 평문 패킷을 암호화된 패킷으로 바꿈
+    pub fn seal(&mut self, packet: &mut Vec<u8>) {
+        let counter = self.next_counter;
+        self.next_counter += 1;
+
+        let mut prefix = [0u8; 2 + COUNTER_SIZE];
+        prefix[..2].copy_from_slice(&ENCRYPTED_MAGIC);
+        prefix[2..].copy_from_slice(&counter.to_be_bytes());
+        let ciphertext = self.outgoing
+            .encrypt(&nonce(counter), Payload { msg: packet, aad: &prefix })
+            .expect("ChaCha20-Poly1305 암호화는 실패하지 않음");
+
+        packet.clear();
+        packet.extend_from_slice(&prefix);
+        packet.extend_from_slice(&ciphertext);
+    }
+
+    // 복호화에 실패하거나 이미 받은 카운터면 None
+    pub fn open(&mut self, packet: &[u8]) -> Option<Vec<u8>> {
+        if packet.len() < ENCRYPTION_OVERHEAD || packet[0..2] != ENCRYPTED_MAGIC {
+            return None;
+        }
+        let (prefix, ciphertext) = packet.split_at(2 + COUNTER_SIZE);
+        let counter = u64::from_be_bytes(prefix[2..].try_into().unwrap());
+        if !self.replay.is_fresh(counter) {
+            return None;
+        }
+
+        let plaintext = self.incoming
+            .decrypt(&nonce(counter), Payload { msg: ciphertext, aad: prefix })
+            .ok()?;
+        self.replay.mark(counter);
+        Some(plaintext)
+    }
+}
+
+// 세션 데이터그램 보호 방식
+#[derive(Clone)]
+pub enum Protection {
+    // PSK만 쓰는 경우: 잘린 MAC만 붙임
+    Mac(PacketAuth),
+    Aead(PacketCipher),
+}
+
+impl fmt::Debug for Protection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Protection::Mac(_) => write!(f, "Protection::Mac"),
+            Protection::Aead(_) => write!(f, "Protection::Aead"),
+        }
+    }
+}
+
+impl Protection {
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, Protection::Aead(_))
+    }
+
+    pub fn overhead(&self) -> usize {
+        match self {
+            Protection::Mac(_) => crate::auth::MAC_SIZE,
+            Protection::Aead(_) => ENCRYPTION_OVERHEAD,
+        }
+    }
+
+    pub fn seal(&mut self, packet: &mut Vec<u8>) {
+        match self {
+            Protection::Mac(auth) => auth.seal(packet),
+            Protection::Aead(cipher) => cipher.seal(packet),
+        }
+    }
+
+    pub fn open<'a>(&mut self, packet: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+        match self {
+            Protection::Mac(auth) => auth.open(packet).map(Cow::Borrowed),
+            Protection::Aead(cipher) => cipher.open(packet).map(Cow::Owned),
+        }
+    }
+}
+
+// 보호를 쓰지 않으면 그대로 통과
+pub fn seal(protection: Option<&mut Protection>, packet: &mut Vec<u8>) {
+    if let Some(protection) = protection {
+        protection.seal(packet);
+    }
+}
+
+pub fn open<'a>(protection: Option<&mut Protection>, packet: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+    match protection {
+        Some(protection) => protection.open(packet),
+        None => Some(Cow::Borrowed(packet)),
+    }
+}
+
+pub fn overhead(protection: Option<&Protection>) -> usize {
+    protection.map_or(0, Protection::overhead)
+}
+
+// 스트림 키 교환: 양쪽이 공개키를 보내고 상대 공개키를 받음
+pub fn stream_handshake<S: Read + Write>(stream: &mut S, psk: Option<&PairingKey>, role: Role) -> io::Result<SessionKeys> {
+    let key_pair = KeyPair::generate();
+    stream.write_all(&key_pair.public_key())?;
+    stream.flush()?;
+
+    let mut peer_public = [0u8; PUBLIC_KEY_SIZE];
+    stream.read_exact(&mut peer_public)?;
+
+    let shared_secret = key_pair.shared_secret(&peer_public);
+    let (initiator, responder) = match role {
+        Role::Initiator => (key_pair.public_key(), peer_public),
+        Role::Responder => (peer_public, key_pair.public_key()),
+    };
+    Ok(SessionKeys::derive(psk, &shared_secret, &[b"stream", &initiator, &responder]))
+}
+
+// TCP 연결을 읽기/쓰기 반쪽으로 나눠 각각 암호화
+pub fn secure_stream(mut stream: TcpStream, psk: Option<&PairingKey>, role: Role) -> io::Result<(EncryptedReader<TcpStream>, EncryptedWriter<TcpStream>)> {
+    let keys = stream_handshake(&mut stream, psk, role)?;
+    let (outgoing, incoming) = keys.for_role(role);
+    let reader = EncryptedReader::new(stream.try_clone()?, incoming);
+    let writer = EncryptedWriter::new(stream, outgoing);
+    Ok((reader, writer))
+}
+
+// 레코드: len(4) | ciphertext | tag(16), nonce는 양쪽이 세는 레코드 번호
+pub struct EncryptedReader<R> {
+    inner: R,
+    cipher: ChaCha20Poly1305,
+    counter: u64,
+    plaintext: Vec<u8>,
+    position: usize,
+}
+
+impl<R: Read> EncryptedReader<R> {
+    fn new(inner: R, cipher: ChaCha20Poly1305) -> Self {
+        Self { inner, cipher, counter: 0, plaintext: Vec::new(), position: 0 }
+    }
+
+    // 레코드 경계에서 연결이 끊기면 Ok(false)
+    fn read_record(&mut self) -> io::Result<bool> {
+        let mut len = [0u8; 4];
+        match self.inner.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(false),
+            Err(e) => return Err(e),
+        }
+        let len = u32::from_be_bytes(len) as usize;
+        if !(TAG_SIZE..=MAX_RECORD_SIZE + TAG_SIZE).contains(&len) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "암호화 레코드 길이가 잘못됨"));
+        }
+
+        let mut ciphertext = vec![0u8; len];
+        self.inner.read_exact(&mut ciphertext)?;
+        self.plaintext = self.cipher
+            .decrypt(&nonce(self.counter), &ciphertext[..])
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "복호화 실패 (키가 다르거나 변조됨)"))?;
+        self.counter += 1;
+        self.position = 0;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for EncryptedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.plaintext.len() {
+            if !self.read_record()? {
+                return Ok(0);
+            }
+        }
+        let n = buf.len().min(self.plaintext.len() - self.position);
+        buf[..n].copy_from_slice(&self.plaintext[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
+
+// flush할 때(또는 버퍼가 차면) 모아 둔 데이터를 레코드 하나로 보냄
+pub struct EncryptedWriter<W: Write> {
+    inner: W,
+    cipher: ChaCha20Poly1305,
+    counter: u64,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> EncryptedWriter<W> {
+    fn new(inner: W, cipher: ChaCha20Poly1305) -> Self {
+        Self { inner, cipher, counter: 0, pending: Vec::new() }
+    }
+
+    fn write_record(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let ciphertext = self.cipher
+            .encrypt(&nonce(self.counter), &self.pending[..])
+            .expect("ChaCha20-Poly1305 암호화는 실패하지 않음");
+        self.counter += 1;
+        self.pending.clear();
+
+        self.inner.write_all(&(ciphertext.len() as u32).to_be_bytes())?;
+        self.inner.write_all(&ciphertext)
+    }
+}
+
+impl<W: Write> Write for EncryptedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(MAX_RECORD_SIZE - self.pending.len());
+        self.pending.extend_from_slice(&buf[..n]);
+        if self.pending.len() == MAX_RECORD_SIZE {
+            self.write_record()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_record()?;
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for EncryptedWriter<W> {
+    fn drop(&mut self) {
+        let _ = self.write_record();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> SessionKeys {
+        SessionKeys::derive(None, &[7; 32], &[b"test"])
+    }
+
+    // 송신측이 counter 0..count를 봉인한 패킷
+    fn sealed(count: usize) -> Vec<Vec<u8>> {
+        let mut sender = PacketCipher::new(&keys(), Role::Initiator);
+        (0..count).map(|i| {
+            let mut packet = vec![i as u8; 4];
+            sender.seal(&mut packet);
+            packet
+        }).collect()
+    }
+
+    #[test]
+    fn packet_round_trip() {
+        let packets = sealed(2);
+        let mut receiver = PacketCipher::new(&keys(), Role::Responder);
+        assert_eq!(receiver.open(&packets[1]), Some(vec![1; 4]));
+        // 순서가 바뀌어 온 패킷도 받음
+        assert_eq!(receiver.open(&packets[0]), Some(vec![0; 4]));
+
+        // 같은 방향 키로는 열 수 없음
+        let mut wrong_role = PacketCipher::new(&keys(), Role::Initiator);
+        assert!(wrong_role.open(&packets[0]).is_none());
+
+        let mut tampered = packets[0].clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(PacketCipher::new(&keys(), Role::Responder).open(&tampered).is_none());
+    }
+
+    #[test]
+    fn rejects_duplicates() {
+        let packets = sealed(3);
+        let mut receiver = PacketCipher::new(&keys(), Role::Responder);
+        assert!(receiver.open(&packets[2]).is_some());
+        assert!(receiver.open(&packets[2]).is_none());
+        assert!(receiver.open(&packets[0]).is_some());
+        assert!(receiver.open(&packets[0]).is_none());
+    }
+
+    #[test]
+    fn rejects_packets_older_than_window() {
+        let window = REPLAY_WINDOW as usize;
+        let packets = sealed(window + 2);
+        let mut receiver = PacketCipher::new(&keys(), Role::Responder);
+        assert!(receiver.open(&packets[window + 1]).is_some());
+        // highest - REPLAY_WINDOW 이하는 받은 적이 없어도 버림
+        assert!(receiver.open(&packets[1]).is_none());
+        assert!(receiver.open(&packets[2]).is_some());
+    }
+
+    #[test]
+    fn stream_round_trip() {
+        let (outgoing, _) = keys().for_role(Role::Initiator);
+        let mut writer = EncryptedWriter::new(Vec::new(), outgoing);
+        let message: Vec<u8> = (0..MAX_RECORD_SIZE + 100).map(|i| i as u8).collect();
+        writer.write_all(&message).unwrap();
+        writer.flush().unwrap();
+        let wire = std::mem::take(&mut writer.inner);
+
+        // 수신측은 반대 방향 키로 읽음
+        let (_, incoming) = keys().for_role(Role::Responder);
+        let mut reader = EncryptedReader::new(&wire[..], incoming);
+        let mut received = Vec::new();
+        reader.read_to_end(&mut received).unwrap();
+        assert_eq!(received, message);
+    }
+}
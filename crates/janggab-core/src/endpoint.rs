@@ -0,0 +1,213 @@
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener, ToSocketAddrs, UdpSocket};
+
+pub const DEFAULT_PORT: u16 = 52525;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpFamily {
+    #[default]
+    V4,
+    V6,
+}
+
+impl IpFamily {
+    fn unspecified(self) -> IpAddr {
+        match self {
+            IpFamily::V4 => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpFamily::V6 => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        }
+    }
+
+    fn loopback(self) -> IpAddr {
+        match self {
+            IpFamily::V4 => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpFamily::V6 => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        }
+    }
+
+    fn matches(self, addr: &SocketAddr) -> bool {
+        match self {
+            IpFamily::V4 => addr.is_ipv4(),
+            IpFamily::V6 => addr.is_ipv6(),
+        }
+    }
+}
+
+// 모든 클라이언트/서버가 받는 주소 설정
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointConfig {
+    // 서버와 UDP 클라이언트가 바인딩할 로컬 주소 (None = family의 모든 인터페이스)
+    pub bind_addr: Option<IpAddr>,
+    // 서버는 수신 포트, 클라이언트는 서버 포트
+    // 서버에서 0이면 OS가 빈 포트를 고르고 local_addr()로 확인
+    pub port: u16,
+    // 클라이언트가 연결할 서버 ("192.168.0.10", "host", "host:port", "[::1]:port")
+    // 포트를 적지 않으면 port를 사용
+    pub peer: Option<String>,
+    // bind_addr가 없을 때의 기본 주소와 peer 이름을 풀 때 우선할 주소 종류
+    pub family: IpFamily,
+}
+
+impl Default for EndpointConfig {
+    fn default() -> Self {
+        Self {
+            bind_addr: None,
+            port: DEFAULT_PORT,
+            peer: None,
+            family: IpFamily::V4,
+        }
+    }
+}
+
+impl EndpointConfig {
+    pub fn with_peer(peer: impl Into<String>) -> Self {
+        Self {
+            peer: Some(peer.into()),
+            ..Self::default()
+        }
+    }
+
+    // 서버가 바인딩할 주소
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_addr.unwrap_or(self.family.unspecified()), self.port)
+    }
+
+    // 같은 머신의 서버 주소
+    pub fn loopback_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.family.loopback(), self.port)
+    }
+
+    // 클라이언트 소켓이 바인딩할 주소 (포트는 OS가 고르고, 주소 종류는 서버를 따름)
+    pub fn local_addr_for(&self, peer: &SocketAddr) -> SocketAddr {
+        let ip = match self.bind_addr {
+            Some(ip) => ip,
+            None if peer.is_ipv6() => IpFamily::V6.unspecified(),
+            None => IpFamily::V4.unspecified(),
+        };
+        SocketAddr::new(ip, 0)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        let peer = self.peer.as_deref()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "연결할 서버 주소(peer)가 없음"))?;
+        self.resolve(peer)
+    }
+
+    // 포트가 없으면 port를 붙이고, 여러 주소가 나오면 family가 맞는 것을 우선
+    pub fn resolve(&self, host: &str) -> io::Result<SocketAddr> {
+        if let Ok(addr) = host.parse::<SocketAddr>() {
+            return Ok(addr);
+        }
+        if let Ok(ip) = host.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+            return Ok(SocketAddr::new(ip, self.port));
+        }
+
+        let has_port = host.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+        let addrs: Vec<SocketAddr> = if has_port {
+            host.to_socket_addrs()?.collect()
+        } else {
+            (host, self.port).to_socket_addrs()?.collect()
+        };
+        addrs.iter()
+            .find(|addr| self.family.matches(addr))
+            .or(addrs.first())
+            .copied()
+            .ok_or_else(|| io::Error::new(ErrorKind::NotFound, format!("{}의 주소를 찾을 수 없음", host)))
+    }
+
+    pub fn bind_udp(&self) -> io::Result<UdpSocket> {
+        UdpSocket::bind(self.listen_addr())
+    }
+
+    pub fn bind_tcp(&self) -> io::Result<TcpListener> {
+        TcpListener::bind(self.listen_addr())
+    }
+
+    // 서버에 보낼 클라이언트 UDP 소켓과 서버 주소
+    pub fn connect_udp(&self) -> io::Result<(UdpSocket, SocketAddr)> {
+        let peer = self.peer_addr()?;
+        let socket = UdpSocket::bind(self.local_addr_for(&peer))?;
+        Ok((socket, peer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(port: u16, family: IpFamily) -> EndpointConfig {
+        EndpointConfig { port, family, ..EndpointConfig::default() }
+    }
+
+    #[test]
+    fn resolve_bare_ip_uses_default_port() {
+        let config = config(4000, IpFamily::V4);
+        assert_eq!(config.resolve("192.168.0.10").unwrap(), "192.168.0.10:4000".parse().unwrap());
+        assert_eq!(config.resolve("::1").unwrap(), "[::1]:4000".parse().unwrap());
+    }
+
+    #[test]
+    fn resolve_keeps_explicit_port() {
+        let config = config(4000, IpFamily::V4);
+        assert_eq!(config.resolve("10.0.0.1:5000").unwrap(), "10.0.0.1:5000".parse().unwrap());
+        assert_eq!(config.resolve("[::1]:5000").unwrap(), "[::1]:5000".parse().unwrap());
+        assert_eq!(config.resolve("localhost:5000").unwrap().port(), 5000);
+    }
+
+    #[test]
+    fn resolve_bracketed_v6_without_port() {
+        let config = config(4000, IpFamily::V6);
+        assert_eq!(config.resolve("[fe80::1]").unwrap(), "[fe80::1]:4000".parse().unwrap());
+    }
+
+    #[test]
+    fn resolve_host_name_fills_port() {
+        let addr = config(4000, IpFamily::V4).resolve("localhost").unwrap();
+        assert_eq!(addr.port(), 4000);
+    }
+
+    #[test]
+    fn resolve_prefers_family() {
+        let all: Vec<SocketAddr> = ("localhost", 4000).to_socket_addrs().unwrap().collect();
+
+        assert!(config(4000, IpFamily::V4).resolve("localhost").unwrap().is_ipv4());
+        // ::1이 없는 환경에서는 있는 주소로 대신함
+        let v6 = config(4000, IpFamily::V6).resolve("localhost").unwrap();
+        assert_eq!(v6.is_ipv6(), all.iter().any(SocketAddr::is_ipv6));
+    }
+
+    #[test]
+    fn peer_addr_requires_peer() {
+        let err = EndpointConfig::default().peer_addr().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+        assert_eq!(EndpointConfig::with_peer("127.0.0.1").peer_addr().unwrap(), SocketAddr::new(IpFamily::V4.loopback(), DEFAULT_PORT));
+    }
+
+    #[test]
+    fn listen_addr_defaults_to_family_unspecified() {
+        assert_eq!(config(4000, IpFamily::V4).listen_addr(), "0.0.0.0:4000".parse().unwrap());
+        assert_eq!(config(4000, IpFamily::V6).listen_addr(), "[::]:4000".parse().unwrap());
+
+        let bound = EndpointConfig { bind_addr: Some(IpFamily::V4.loopback()), ..config(4000, IpFamily::V6) };
+        assert_eq!(bound.listen_addr(), "127.0.0.1:4000".parse().unwrap());
+    }
+
+    #[test]
+    fn local_addr_follows_peer_family() {
+        let config = EndpointConfig::default();
+        assert_eq!(config.local_addr_for(&"10.0.0.1:5000".parse().unwrap()), "0.0.0.0:0".parse().unwrap());
+        assert_eq!(config.local_addr_for(&"[::1]:5000".parse().unwrap()), "[::]:0".parse().unwrap());
+
+        let bound = EndpointConfig { bind_addr: Some(IpFamily::V4.loopback()), ..config };
+        assert_eq!(bound.local_addr_for(&"[::1]:5000".parse().unwrap()), "127.0.0.1:0".parse().unwrap());
+    }
+
+    #[test]
+    fn port_zero_binds_free_port() {
+        let config = EndpointConfig { bind_addr: Some(IpFamily::V4.loopback()), ..config(0, IpFamily::V4) };
+        let first = config.bind_udp().unwrap().local_addr().unwrap();
+        let second = config.bind_udp().unwrap().local_addr().unwrap();
+        assert_ne!(first.port(), 0);
+        assert_ne!(first.port(), second.port());
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use opencv::{
+    prelude::*,
+    videoio::{self, VideoCapture},
+};
+
+// 찾아볼 장치 번호 범위 (0..MAX_PROBE_INDEX)
+const MAX_PROBE_INDEX: i32 = 10;
+
+// 지원 해상도를 확인할 때 시도하는 크기
+const COMMON_RESOLUTIONS: [(u32, u32); 7] = [
+    (160, 120),
+    (320, 240),
+    (640, 480),
+    (800, 600),
+    (1280, 720),
+    (1920, 1080),
+    (3840, 2160),
+];
+
+// OpenCV 캡처 백엔드 선호
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaptureBackend {
+    // OpenCV가 알아서 고름
+    #[default]
+    Any,
+    DirectShow,
+    MediaFoundation,
+    V4l2,
+    AvFoundation,
+    GStreamer,
+    Ffmpeg,
+}
+
+impl CaptureBackend {
+    fn api_preference(self) -> i32 {
+        match self {
+            CaptureBackend::Any => videoio::CAP_ANY,
+            CaptureBackend::DirectShow => videoio::CAP_DSHOW,
+            CaptureBackend::MediaFoundation => videoio::CAP_MSMF,
+            CaptureBackend::V4l2 => videoio::CAP_V4L2,
+            CaptureBackend::AvFoundation => videoio::CAP_AVFOUNDATION,
+            CaptureBackend::GStreamer => videoio::CAP_GSTREAMER,
+            CaptureBackend::Ffmpeg => videoio::CAP_FFMPEG,
+        }
+    }
+}
+
+// 어떤 카메라를 열지
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DeviceSelector {
+    // 0번 장치
+    #[default]
+    Default,
+    Index(i32),
+    // 장치 이름의 일부 (대소문자 무시)
+    // 이름은 리눅스 V4L2의 sysfs에서만 읽을 수 있어서 리눅스에서 Any/V4l2 백엔드로 열 때만 동작
+    // 그 밖의 환경(Windows, macOS 등)에서는 open이 바로 에러를 돌려줌 (janggab::webcam::Windows는 번호로만 고름)
+    Name(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CaptureConfig {
+    pub device: DeviceSelector,
+    pub backend: CaptureBackend,
+}
+
+// list_devices가 찾은 장치
+#[derive(Debug, Clone)]
+pub struct DeviceInfo {
+    pub index: i32,
+    // 실제로 열린 백엔드 이름 (예: "DSHOW", "V4L2")
+    pub backend: String,
+    // 백엔드가 이름을 알려주지 않으면 None
+    pub name: Option<String>,
+    // COMMON_RESOLUTIONS 중 장치가 그대로 받아들인 크기
+    pub resolutions: Vec<(u32, u32)>,
+}
+
+// 열리는 장치를 모두 찾음 (장치마다 해상도를 바꿔 보므로 몇 초 걸릴 수 있음)
+pub fn list_devices(backend: CaptureBackend) -> Result<Vec<DeviceInfo>> {
+    let mut devices = Vec::new();
+    for index in 0..MAX_PROBE_INDEX {
+        let Ok(mut cap) = VideoCapture::new(index, backend.api_preference()) else {
+            continue;
+        };
+        if !cap.is_opened().unwrap_or(false) {
+            continue;
+        }
+
+        let backend_name = cap.get_backend_name().unwrap_or_default();
+        let resolutions = probe_resolutions(&mut cap);
+        cap.release().context("카메라 해제 실패")?;
+
+        devices.push(DeviceInfo {
+            index,
+            name: device_name(index, &backend_name),
+            backend: backend_name,
+            resolutions,
+        });
+    }
+    Ok(devices)
+}
+
+// 선택한 카메라를 열고 실제로 연 장치 번호와 함께 반환 (출력은 호출자가 함)
+pub fn open(config: &CaptureConfig) -> Result<(VideoCapture, i32)> {
+    let index = match &config.device {
+        DeviceSelector::Default => 0,
+        DeviceSelector::Index(index) => *index,
+        DeviceSelector::Name(name) => find_by_name(name, config.backend)?,
+    };
+
+    let cap = VideoCapture::new(index, config.backend.api_preference())
+        .context("카메라 초기화 실패")?;
+    if !cap.is_opened()? {
+        anyhow::bail!("{}번 카메라를 열 수 없습니다 ({:?}). list_devices로 사용 가능한 장치를 확인하세요.", index, config.backend);
+    }
+    Ok((cap, index))
+}
+
+fn find_by_name(name: &str, backend: CaptureBackend) -> Result<i32> {
+    // 장치를 모두 열어 보기 전에 이름을 얻을 수 없는 환경은 바로 거절
+    if !cfg!(target_os = "linux") || !matches!(backend, CaptureBackend::Any | CaptureBackend::V4l2) {
+        anyhow::bail!("장치 이름으로 카메라를 고르는 기능은 리눅스 V4L2에서만 지원합니다 ({:?}). DeviceSelector::Index로 선택하세요.", backend);
+    }
+    let wanted = name.to_lowercase();
+    let devices = list_devices(backend)?;
+    if devices.iter().all(|device| device.name.is_none()) && !devices.is_empty() {
+        anyhow::bail!("이 백엔드는 장치 이름을 알려주지 않습니다. DeviceSelector::Index로 선택하세요.");
+    }
+    devices.iter()
+        .find(|device| device.name.as_ref().is_some_and(|n| n.to_lowercase().contains(&wanted)))
+        .map(|device| device.index)
+        .with_context(|| format!("'{}' 이름의 카메라를 찾을 수 없음", name))
+}
+
+fn probe_resolutions(cap: &mut VideoCapture) -> Vec<(u32, u32)> {
+    let mut resolutions = Vec::new();
+    for (width, height) in COMMON_RESOLUTIONS {
+        let applied = cap.set(videoio::CAP_PROP_FRAME_WIDTH, width as f64).unwrap_or(false)
+            && cap.set(videoio::CAP_PROP_FRAME_HEIGHT, height as f64).unwrap_or(false);
+        if !applied {
+            continue;
+        }
+        // 지원하지 않는 크기는 가까운 크기로 바뀌므로 다시 읽어서 확인
+        let actual = (
+            cap.get(videoio::CAP_PROP_FRAME_WIDTH).unwrap_or(0.0) as u32,
+            cap.get(videoio::CAP_PROP_FRAME_HEIGHT).unwrap_or(0.0) as u32,
+        );
+        if actual == (width, height) {
+            resolutions.push(actual);
+        }
+    }
+    resolutions
+}
+
+// OpenCV는 장치 이름을 알려주지 않아서 리눅스 V4L2만 sysfs에서 읽음
+#[cfg(target_os = "linux")]
+fn device_name(index: i32, backend_name: &str) -> Option<String> {
+    if !backend_name.eq_ignore_ascii_case("V4L2") {
+        return None;
+    }
+    std::fs::read_to_string(format!("/sys/class/video4linux/video{}/name", index))
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn device_name(_index: i32, _backend_name: &str) -> Option<String> {
+    None
+}
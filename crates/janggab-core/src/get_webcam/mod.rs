@@ -1,2 +1,6 @@
+pub mod device;
+pub mod record;
+pub mod sink;
+pub mod snapshot;
 pub mod tcp;
 pub mod udp;
@@ -0,0 +1,556 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+
+use super::udp::codec::{self, Codec};
+use super::udp::convert::OutputFormat;
+use super::udp::frame::Frame;
+use super::udp::header::PixelFormat;
+
+// 'movi' 리스트 전까지의 헤더 길이 (RIFF, hdrl, movi 리스트 머리)
+const AVI_HEADER_SIZE: usize = 224;
+// 파일 하나가 이보다 커지면 다음 파일로 넘어감 (AVI 1.0 RIFF 크기 제한보다 넉넉히 작게)
+const MAX_AVI_BYTES: u64 = 1 << 30;
+// 프레임 사이가 이보다 길게 비면 빈 칸을 채우지 않고 바로 이어 붙임 (초)
+const MAX_GAP_SECS: u64 = 60;
+const AVIF_HASINDEX: u32 = 0x10;
+const AVIIF_KEYFRAME: u32 = 0x10;
+
+#[derive(Debug, Clone)]
+pub struct RecordConfig {
+    // 녹화 파일을 만들 디렉토리 (없으면 만듦)
+    pub dir: PathBuf,
+    // 파일의 고정 프레임율
+    // 프레임은 캡처 시각에 맞는 칸에 들어가고, 빈 칸은 이전 프레임을 유지하며, 한 칸에 두 장이 오면 뒤의 것은 버림
+    pub fps: u32,
+    pub jpeg_quality: u8,
+}
+
+impl Default for RecordConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("recordings"),
+            fps: 15,
+            jpeg_quality: 80,
+        }
+    }
+}
+
+// 다 쓴 녹화 파일 하나
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub client: String,
+    pub path: PathBuf,
+    // 빈 칸을 포함한 재생 프레임 수 (재생 시간 = frames / fps)
+    pub frames: u32,
+}
+
+// MJPEG AVI 파일 하나 (프레임 크기와 fps는 파일 안에서 고정)
+pub struct AviWriter {
+    file: BufWriter<File>,
+    width: u32,
+    height: u32,
+    fps: u32,
+    // 'movi' fourcc 뒤에 쓴 바이트 수
+    movi_len: u64,
+    // idx1 항목 (항목마다 16바이트)
+    index: Vec<u8>,
+    frames: u32,
+    max_chunk: u32,
+}
+
+impl AviWriter {
+    pub fn create(path: &Path, width: u32, height: u32, fps: u32) -> io::Result<Self> {
+        let mut writer = Self {
+            file: BufWriter::new(File::create(path)?),
+            width,
+            height,
+            fps: fps.max(1),
+            movi_len: 0,
+            index: Vec::new(),
+            frames: 0,
+            max_chunk: 0,
+        };
+        // 크기와 프레임 수는 finish에서 다시 씀
+        let header = writer.header();
+        writer.file.write_all(&header)?;
+        Ok(writer)
+    }
+
+    // JPEG 한 장을 다음 칸에 씀
+    pub fn write_frame(&mut self, jpeg: &[u8]) -> io::Result<()> {
+        self.write_chunk(jpeg, AVIIF_KEYFRAME)
+    }
+
+    // 다음 칸에 길이 0 청크를 써서 이전 프레임을 한 칸 더 보여 줌
+    pub fn repeat_frame(&mut self) -> io::Result<()> {
+        self.write_chunk(&[], 0)
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    // idx1을 붙이기 전까지의 파일 크기
+    pub fn file_size(&self) -> u64 {
+        AVI_HEADER_SIZE as u64 + self.movi_len
+    }
+
+    // idx1을 붙이고 헤더의 크기와 프레임 수를 채움
+    pub fn finish(mut self) -> io::Result<()> {
+        self.file.write_all(b"idx1")?;
+        self.file.write_all(&(self.index.len() as u32).to_le_bytes())?;
+        self.file.write_all(&self.index)?;
+        let header = self.header();
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&header)?;
+        self.file.flush()
+    }
+
+    fn write_chunk(&mut self, data: &[u8], flags: u32) -> io::Result<()> {
+        // idx1 오프셋은 'movi' fourcc 위치 기준
+        let offset = 4 + self.movi_len as u32;
+        self.file.write_all(b"00dc")?;
+        self.file.write_all(&(data.len() as u32).to_le_bytes())?;
+        self.file.write_all(data)?;
+        // 청크는 2바이트 단위로 정렬
+        if data.len() % 2 == 1 {
+            self.file.write_all(&[0])?;
+        }
+        self.movi_len += 8 + data.len().next_multiple_of(2) as u64;
+
+        self.index.extend_from_slice(b"00dc");
+        self.index.extend_from_slice(&flags.to_le_bytes());
+        self.index.extend_from_slice(&offset.to_le_bytes());
+        self.index.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        self.frames += 1;
+        self.max_chunk = self.max_chunk.max(data.len() as u32);
+        Ok(())
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let riff_len = AVI_HEADER_SIZE as u64 - 8 + self.movi_len + 8 + self.index.len() as u64;
+        let buffer_size = self.max_chunk + 8;
+        let mut h = Vec::with_capacity(AVI_HEADER_SIZE);
+        let mut put = |bytes: &[u8]| h.extend_from_slice(bytes);
+
+        put(b"RIFF");
+        put(&(riff_len as u32).to_le_bytes());
+        put(b"AVI ");
+        put(b"LIST");
+        put(&192u32.to_le_bytes());
+        put(b"hdrl");
+
+        // MainAVIHeader
+        put(b"avih");
+        put(&56u32.to_le_bytes());
+        put(&(1_000_000 / self.fps).to_le_bytes());
+        put(&buffer_size.saturating_mul(self.fps).to_le_bytes());
+        put(&0u32.to_le_bytes());
+        put(&AVIF_HASINDEX.to_le_bytes());
+        put(&self.frames.to_le_bytes());
+        put(&0u32.to_le_bytes());
+        put(&1u32.to_le_bytes());
+        put(&buffer_size.to_le_bytes());
+        put(&self.width.to_le_bytes());
+        put(&self.height.to_le_bytes());
+        put(&[0; 16]);
+
+        put(b"LIST");
+        put(&116u32.to_le_bytes());
+        put(b"strl");
+
+        // AVIStreamHeader: 1초에 fps 칸
+        put(b"strh");
+        put(&56u32.to_le_bytes());
+        put(b"vids");
+        put(b"MJPG");
+        put(&0u32.to_le_bytes());
+        put(&0u32.to_le_bytes());
+        put(&0u32.to_le_bytes());
+        put(&1u32.to_le_bytes());
+        put(&self.fps.to_le_bytes());
+        put(&0u32.to_le_bytes());
+        put(&self.frames.to_le_bytes());
+        put(&buffer_size.to_le_bytes());
+        put(&u32::MAX.to_le_bytes());
+        put(&0u32.to_le_bytes());
+        put(&0u16.to_le_bytes());
+        put(&0u16.to_le_bytes());
+        // rcFrame은 16비트라서 그보다 큰 해상도는 잘라서 씀
+        put(&(self.width.min(u16::MAX as u32) as u16).to_le_bytes());
+        put(&(self.height.min(u16::MAX as u32) as u16).to_le_bytes());
+
+        // BITMAPINFOHEADER
+        put(b"strf");
+        put(&40u32.to_le_bytes());
+        put(&40u32.to_le_bytes());
+        put(&self.width.to_le_bytes());
+        put(&self.height.to_le_bytes());
+        put(&1u16.to_le_bytes());
+        put(&24u16.to_le_bytes());
+        put(b"MJPG");
+        put(&self.width.saturating_mul(self.height).saturating_mul(3).to_le_bytes());
+        put(&[0; 16]);
+
+        put(b"LIST");
+        put(&((4 + self.movi_len) as u32).to_le_bytes());
+        put(b"movi");
+        h
+    }
+}
+
+// 녹화할 JPEG 한 장 (압축은 Recorder를 잠그지 않고 미리 해 둘 수 있음)
+pub struct EncodedFrame {
+    width: usize,
+    height: usize,
+    timestamp_us: u64,
+    jpeg: Vec<u8>,
+}
+
+impl EncodedFrame {
+    pub fn encode(frame: &Frame, jpeg_quality: u8) -> Result<Self> {
+        let rgb = frame.convert(OutputFormat::Rgb8);
+        let jpeg = codec::encode(Codec::Jpeg, &rgb.data, rgb.width as u32, rgb.height as u32, PixelFormat::Rgb8, jpeg_quality)
+            .context("녹화 프레임 JPEG 압축 실패")?;
+        Ok(Self { width: frame.width, height: frame.height, timestamp_us: frame.timestamp_us, jpeg })
+    }
+}
+
+// 클라이언트 하나의 진행 중인 파일
+struct ClientRecording {
+    writer: AviWriter,
+    path: PathBuf,
+    width: usize,
+    height: usize,
+    // 0번 칸의 캡처 시각 (µs)
+    origin_us: u64,
+    last_us: u64,
+    next_slot: u64,
+}
+
+impl ClientRecording {
+    // 캡처 시각에 가장 가까운 칸과, 기준을 다시 잡아야 하는지
+    // 시계가 뒤로 가거나 너무 오래 비었으면 지금 프레임을 다음 칸에 맞춰 기준을 다시 잡음
+    fn slot_of(&self, timestamp_us: u64, fps: u64) -> (u64, bool) {
+        let slot = (timestamp_us.saturating_sub(self.origin_us) * fps + 500_000) / 1_000_000;
+        if timestamp_us < self.last_us || slot > self.next_slot + MAX_GAP_SECS * fps {
+            (self.next_slot, true)
+        } else {
+            (slot, false)
+        }
+    }
+
+    // 해상도가 바뀌었거나 파일이 너무 커졌으면 새 파일로
+    fn needs_rollover(&self, width: usize, height: usize) -> bool {
+        self.width != width || self.height != height || self.writer.file_size() >= MAX_AVI_BYTES
+    }
+}
+
+// 클라이언트마다 "{이름}_{시작 시각}.avi" 파일에 녹화
+// 해상도가 바뀌거나 파일이 MAX_AVI_BYTES를 넘으면 새 파일로 넘어감
+pub struct Recorder {
+    config: RecordConfig,
+    recordings: HashMap<String, ClientRecording>,
+    // 쓰기에 실패한 클라이언트 (finish를 부를 때까지 더 녹화하지 않음)
+    failed: HashSet<String>,
+}
+
+impl Recorder {
+    pub fn new(config: RecordConfig) -> Result<Self> {
+        fs::create_dir_all(&config.dir)
+            .with_context(|| format!("녹화 디렉토리 생성 실패: {}", config.dir.display()))?;
+        Ok(Self { config, recordings: HashMap::new(), failed: HashSet::new() })
+    }
+
+    pub fn config(&self) -> &RecordConfig {
+        &self.config
+    }
+
+    // 이 프레임을 파일에 넣을지 (이미 채운 칸이거나 녹화를 멈춘 클라이언트면 false)
+    // false면 압축하지 않고 버려도 됨
+    pub fn wants(&self, client: &str, frame: &Frame) -> bool {
+        if self.failed.contains(client) {
+            return false;
+        }
+        let Some(recording) = self.recordings.get(client) else {
+            return true;
+        };
+        if recording.needs_rollover(frame.width, frame.height) {
+            return true;
+        }
+        let (slot, reanchor) = recording.slot_of(frame.timestamp_us, self.config.fps.max(1) as u64);
+        reanchor || slot >= recording.next_slot
+    }
+
+    // 이 프레임 때문에 닫힌 파일이 있으면 돌려줌
+    // 실패하면 그 클라이언트는 finish 전까지 녹화를 멈춤
+    pub fn record(&mut self, client: &str, frame: &Frame) -> Result<Option<Recording>> {
+        if !self.wants(client, frame) {
+            return Ok(None);
+        }
+        let encoded = EncodedFrame::encode(frame, self.config.jpeg_quality)?;
+        self.record_encoded(client, &encoded)
+    }
+
+    // EncodedFrame::encode로 미리 압축한 프레임을 씀 (그 밖에는 record와 같음)
+    pub fn record_encoded(&mut self, client: &str, frame: &EncodedFrame) -> Result<Option<Recording>> {
+        if self.failed.contains(client) {
+            return Ok(None);
+        }
+        let result = self.try_record(client, frame);
+        if result.is_err() {
+            self.failed.insert(client.to_string());
+            // 헤더라도 채워서 이미 쓴 부분은 재생되게 함
+            let _ = self.finish(client);
+        }
+        result
+    }
+
+    // 클라이언트의 파일을 닫음 (연결이 끊겼을 때)
+    pub fn finish(&mut self, client: &str) -> Result<Option<Recording>> {
+        self.failed.remove(client);
+        match self.recordings.remove(client) {
+            Some(recording) => close(client, recording).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    // 모든 파일을 닫음 (실패한 파일이 있어도 나머지는 모두 닫고 첫 오류를 돌려줌)
+    pub fn finish_all(&mut self) -> Result<Vec<Recording>> {
+        self.failed.clear();
+        let mut finished = Vec::new();
+        let mut first_error = None;
+        for (client, recording) in self.recordings.drain() {
+            match close(&client, recording) {
+                Ok(done) => finished.push(done),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(finished),
+        }
+    }
+
+    fn try_record(&mut self, client: &str, frame: &EncodedFrame) -> Result<Option<Recording>> {
+        let fps = self.config.fps.max(1) as u64;
+        let mut closed = None;
+
+        if let Some(recording) = self.recordings.get(client)
+            && recording.needs_rollover(frame.width, frame.height)
+        {
+            closed = self.finish(client)?;
+        }
+        let recording = match self.recordings.entry(client.to_string()) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let path = unique_path(&self.config.dir, client);
+                let writer = AviWriter::create(&path, frame.width as u32, frame.height as u32, self.config.fps)
+                    .with_context(|| format!("녹화 파일 생성 실패: {}", path.display()))?;
+                entry.insert(ClientRecording {
+                    writer,
+                    path,
+                    width: frame.width,
+                    height: frame.height,
+                    origin_us: frame.timestamp_us,
+                    last_us: frame.timestamp_us,
+                    next_slot: 0,
+                })
+            }
+        };
+
+        let timestamp_us = frame.timestamp_us;
+        let (slot, reanchor) = recording.slot_of(timestamp_us, fps);
+        if reanchor {
+            recording.origin_us = timestamp_us.saturating_sub(recording.next_slot * 1_000_000 / fps);
+        }
+        recording.last_us = timestamp_us;
+        // 이미 채운 칸이면 버림 (녹화 fps보다 빨리 오는 프레임)
+        if slot < recording.next_slot {
+            return Ok(closed);
+        }
+
+        let path = recording.path.display().to_string();
+        for _ in recording.next_slot..slot {
+            recording.writer.repeat_frame().with_context(|| format!("녹화 파일 쓰기 실패: {}", path))?;
+        }
+        recording.writer.write_frame(&frame.jpeg).with_context(|| format!("녹화 파일 쓰기 실패: {}", path))?;
+        recording.next_slot = slot + 1;
+        Ok(closed)
+    }
+}
+
+fn close(client: &str, recording: ClientRecording) -> Result<Recording> {
+    let frames = recording.writer.frames();
+    recording.writer.finish()
+        .with_context(|| format!("녹화 파일 마무리 실패: {}", recording.path.display()))?;
+    Ok(Recording { client: client.to_string(), path: recording.path, frames })
+}
+
+// 같은 초에 파일을 다시 만들면 뒤에 번호를 붙임
+fn unique_path(dir: &Path, client: &str) -> PathBuf {
+    let stem = format!("{}_{}", client, Local::now().format("%Y%m%d_%H%M%S"));
+    let mut path = dir.join(format!("{}.avi", stem));
+    let mut n = 1;
+    while path.exists() {
+        path = dir.join(format!("{}_{}.avi", stem, n));
+        n += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 테스트마다 따로 쓰는 빈 디렉토리
+    fn empty_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("janggab-record-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn frame(width: usize, height: usize, timestamp_us: u64) -> Frame {
+        Frame {
+            stream_id: 0,
+            source: String::new(),
+            frame_id: 0,
+            width,
+            height,
+            channels: 3,
+            pixel_format: PixelFormat::Bgr8,
+            stride: width * 3,
+            timestamp_us,
+            data: vec![128; width * height * 3],
+        }
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+    }
+
+    // idx1 항목 (fourcc, flags, offset, size)
+    fn index_entries(bytes: &[u8]) -> Vec<([u8; 4], u32, u32, u32)> {
+        let movi_size = u32_at(bytes, AVI_HEADER_SIZE - 8) as usize;
+        let idx1 = AVI_HEADER_SIZE - 4 + movi_size;
+        assert_eq!(&bytes[idx1..idx1 + 4], b"idx1");
+        let len = u32_at(bytes, idx1 + 4) as usize;
+        assert_eq!(idx1 + 8 + len, bytes.len());
+        bytes[idx1 + 8..].chunks(16)
+            .map(|e| (e[..4].try_into().unwrap(), u32_at(e, 4), u32_at(e, 8), u32_at(e, 12)))
+            .collect()
+    }
+
+    #[test]
+    fn avi_layout() {
+        let dir = empty_dir("layout");
+        let path = dir.join("layout.avi");
+        let mut writer = AviWriter::create(&path, 320, 240, 15).unwrap();
+        writer.write_frame(b"abc").unwrap();
+        writer.repeat_frame().unwrap();
+        writer.write_frame(b"wxyz").unwrap();
+        writer.finish().unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        // RIFF 'AVI ' 크기는 파일 크기 - 8
+        assert_eq!(&bytes[..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..12], b"AVI ");
+        // LIST hdrl = hdrl + avih(8+56) + LIST strl(8+116)
+        assert_eq!((&bytes[12..16], u32_at(&bytes, 16), &bytes[20..24]), (&b"LIST"[..], 192, &b"hdrl"[..]));
+        assert_eq!((&bytes[24..28], u32_at(&bytes, 28)), (&b"avih"[..], 56));
+        assert_eq!(u32_at(&bytes, 32), 1_000_000 / 15);
+        assert_eq!(u32_at(&bytes, 48), 3);
+        assert_eq!((u32_at(&bytes, 64), u32_at(&bytes, 68)), (320, 240));
+        // LIST strl = strl + strh(8+56) + strf(8+40)
+        assert_eq!((&bytes[88..92], u32_at(&bytes, 92), &bytes[96..100]), (&b"LIST"[..], 116, &b"strl"[..]));
+        assert_eq!((&bytes[100..104], u32_at(&bytes, 104), &bytes[108..116]), (&b"strh"[..], 56, &b"vidsMJPG"[..]));
+        assert_eq!((u32_at(&bytes, 128), u32_at(&bytes, 132), u32_at(&bytes, 140)), (1, 15, 3));
+        assert_eq!((&bytes[164..168], u32_at(&bytes, 168)), (&b"strf"[..], 40));
+        // 청크: 8 + "abc" + 패딩 1, 8 + 0, 8 + "wxyz"
+        let movi_len = 12 + 8 + 12;
+        assert_eq!((&bytes[212..216], u32_at(&bytes, 216), &bytes[220..224]), (&b"LIST"[..], 4 + movi_len, &b"movi"[..]));
+
+        let entries = index_entries(&bytes);
+        assert_eq!(entries, [
+            (*b"00dc", AVIIF_KEYFRAME, 4, 3),
+            (*b"00dc", 0, 16, 0),
+            (*b"00dc", AVIIF_KEYFRAME, 24, 4),
+        ]);
+        // 오프셋은 'movi' fourcc 위치 기준
+        for (_, _, offset, size) in entries {
+            let chunk = AVI_HEADER_SIZE - 4 + offset as usize;
+            assert_eq!(&bytes[chunk..chunk + 4], b"00dc");
+            assert_eq!(u32_at(&bytes, chunk + 4), size);
+        }
+        assert_eq!(&bytes[232..235], b"abc");
+        assert_eq!(&bytes[252..256], b"wxyz");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn recorder(dir: &Path) -> Recorder {
+        Recorder::new(RecordConfig { dir: dir.to_path_buf(), fps: 10, jpeg_quality: 80 }).unwrap()
+    }
+
+    #[test]
+    fn gap_fills_repeat_slots() {
+        let dir = empty_dir("gap");
+        let mut recorder = recorder(&dir);
+        for timestamp_us in [0, 100_000, 400_000] {
+            assert!(recorder.record("Client_0", &frame(4, 2, timestamp_us)).unwrap().is_none());
+        }
+        // 이미 채운 4번 칸
+        assert!(!recorder.wants("Client_0", &frame(4, 2, 420_000)));
+
+        let recording = recorder.finish("Client_0").unwrap().unwrap();
+        assert_eq!(recording.frames, 5);
+        let flags: Vec<(u32, bool)> = index_entries(&fs::read(&recording.path).unwrap()).iter()
+            .map(|&(_, flags, _, size)| (flags, size > 0))
+            .collect();
+        assert_eq!(flags, [(AVIIF_KEYFRAME, true), (AVIIF_KEYFRAME, true), (0, false), (0, false), (AVIIF_KEYFRAME, true)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reanchors_on_clock_jump() {
+        let dir = empty_dir("reanchor");
+        let mut recorder = recorder(&dir);
+        // 10.0초, 10.1초, 시계가 뒤로 간 10.05초, 그 뒤 0.1초, 2분 뒤
+        for timestamp_us in [10_000_000, 10_100_000, 10_050_000, 10_150_000, 130_150_000] {
+            recorder.record("Client_0", &frame(4, 2, timestamp_us)).unwrap();
+        }
+        // 빈 칸 없이 다섯 칸에 차례로 들어감
+        let recording = recorder.finish("Client_0").unwrap().unwrap();
+        assert_eq!(recording.frames, 5);
+        let entries = index_entries(&fs::read(&recording.path).unwrap());
+        assert!(entries.iter().all(|&(_, flags, _, _)| flags == AVIIF_KEYFRAME));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn size_change_rolls_over() {
+        let dir = empty_dir("rollover");
+        let mut recorder = recorder(&dir);
+        assert!(recorder.record("Client_0", &frame(4, 2, 0)).unwrap().is_none());
+
+        let first = recorder.record("Client_0", &frame(8, 4, 100_000)).unwrap().expect("해상도가 바뀌면 파일을 닫음");
+        assert_eq!((first.client.as_str(), first.frames), ("Client_0", 1));
+        let bytes = fs::read(&first.path).unwrap();
+        assert_eq!((u32_at(&bytes, 64), u32_at(&bytes, 68)), (4, 2));
+
+        let second = recorder.finish("Client_0").unwrap().unwrap();
+        assert_ne!(first.path, second.path);
+        assert_eq!(second.frames, 1);
+        let bytes = fs::read(&second.path).unwrap();
+        assert_eq!((u32_at(&bytes, 64), u32_at(&bytes, 68)), (8, 4));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+
+use super::udp::frame::Frame;
+
+// 수신한 프레임을 화면 등으로 내보내는 곳
+// 기본 수신 경로는 sink 없이 프레임을 호출자에게만 돌려줌
+pub trait FrameSink: Send {
+    // name은 송신측마다 다른 창/출력 이름
+    fn show(&mut self, name: &str, frame: &Frame) -> Result<()>;
+
+    // 송신측 연결이 끊겼을 때
+    fn close(&mut self, _name: &str) {}
+}
+
+// 여러 클라이언트 스레드가 함께 쓰는 sink
+pub type SharedSink = Arc<Mutex<dyn FrameSink>>;
+
+#[cfg(feature = "display")]
+pub use self::highgui_sink::{HighguiSink, SinkEvent};
+
+#[cfg(feature = "display")]
+mod highgui_sink {
+    use std::sync::mpsc::Sender;
+
+    use anyhow::{Context, Result};
+    use opencv::{core, highgui, imgproc, prelude::*};
+
+    use super::FrameSink;
+    use crate::get_webcam::udp::frame::Frame;
+    use crate::get_webcam::udp::header::PixelFormat;
+
+    // 창이 열리고 닫힐 때 호출자에게 알림 (sink는 stdout에 출력하지 않음)
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum SinkEvent {
+        WindowOpened(String),
+        // 사용자가 ESC나 'q'로 창을 닫음 (다음 프레임이 오면 다시 열림)
+        WindowClosed(String),
+    }
+
+    // OpenCV highgui 창에 표시 (`display` feature 필요)
+    #[derive(Debug, Default)]
+    pub struct HighguiSink {
+        // 이보다 작은 프레임은 창에서 확대해서 표시 (None = 원본 크기)
+        pub min_size: Option<(usize, usize)>,
+        pub events: Option<Sender<SinkEvent>>,
+    }
+
+    impl HighguiSink {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn shared(self) -> super::SharedSink {
+            std::sync::Arc::new(std::sync::Mutex::new(self))
+        }
+
+        fn notify(&self, event: SinkEvent) {
+            // 받는 쪽이 없으면 무시
+            if let Some(events) = &self.events {
+                let _ = events.send(event);
+            }
+        }
+    }
+
+    impl FrameSink for HighguiSink {
+        fn show(&mut self, name: &str, frame: &Frame) -> Result<()> {
+            let mut mat = core::Mat::zeros(
+                frame.height as i32,
+                frame.width as i32,
+                core::CV_8UC(frame.channels as i32).context("Mat 타입 생성 실패")?,
+            )
+            .context("MatExpr 생성 실패")?
+            .to_mat()
+            .context("Mat 변환 실패")?;
+            mat.data_bytes_mut()
+                .context("Mat 바이트 접근 실패")?
+                .copy_from_slice(&frame.packed());
+
+            // highgui는 BGR 순서로 표시
+            let code = match frame.pixel_format {
+                PixelFormat::Rgb8 => Some(imgproc::COLOR_RGB2BGR),
+                PixelFormat::Rgba8 => Some(imgproc::COLOR_RGBA2BGRA),
+                _ => None,
+            };
+            if let Some(code) = code {
+                let mut converted = core::Mat::default();
+                imgproc::cvt_color_def(&mat, &mut converted, code).context("색 변환 실패")?;
+                mat = converted;
+            }
+
+            if let Some((min_width, min_height)) = self.min_size
+                && (frame.width < min_width || frame.height < min_height)
+            {
+                let scale = (min_width as f64 / frame.width as f64)
+                    .max(min_height as f64 / frame.height as f64)
+                    .max(1.0);
+                let size = core::Size::new((frame.width as f64 * scale) as i32, (frame.height as f64 * scale) as i32);
+                let mut resized = core::Mat::default();
+                imgproc::resize(&mat, &mut resized, size, 0.0, 0.0, imgproc::INTER_NEAREST).context("확대 실패")?;
+                mat = resized;
+            }
+
+            if highgui::get_window_property(name, highgui::WND_PROP_VISIBLE).unwrap_or(-1.0) < 0.0 {
+                highgui::named_window(name, highgui::WINDOW_AUTOSIZE).context("OpenCV 창 생성 실패")?;
+                self.notify(SinkEvent::WindowOpened(name.to_string()));
+            }
+            highgui::imshow(name, &mat).context("이미지 표시 실패")?;
+
+            // ESC 키나 'q' 키가 눌리면 해당 창만 닫기
+            let key = highgui::wait_key(1).context("WaitKey 실패")?;
+            if key == 27 || key == 'q' as i32 {
+                highgui::destroy_window(name).context("창 닫기 실패")?;
+                self.notify(SinkEvent::WindowClosed(name.to_string()));
+            }
+            Ok(())
+        }
+
+        fn close(&mut self, name: &str) {
+            highgui::destroy_window(name).unwrap_or_default();
+        }
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use image::RgbImage;
+
+use super::udp::convert::OutputFormat;
+use super::udp::frame::Frame;
+
+// 파일 이름 템플릿의 {time} 형식
+const TIME_FORMAT: &str = "%Y%m%d_%H%M%S";
+const TIME_LEN: usize = 15;
+
+// 어떤 프레임을 저장할지
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotInterval {
+    // 클라이언트마다 받은 프레임 N개 중 첫 번째 (1이면 모두)
+    Frames(u32),
+    // 클라이언트마다 마지막으로 저장한 뒤 이 시간이 지난 첫 프레임
+    Time(Duration),
+}
+
+// 클라이언트마다 따로 적용하는 보존 규칙 (None인 항목은 검사하지 않음)
+// 방금 저장한 파일은 개수/용량 규칙으로 지우지 않음
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    // 최근 파일을 이 개수까지만 남김
+    pub max_files: Option<usize>,
+    // 이보다 오래된 파일을 지움 (파일 수정 시각 기준)
+    pub max_age: Option<Duration>,
+    // 오래된 파일부터 지워서 합계를 이 바이트 이하로 맞춤
+    pub max_bytes: Option<u64>,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self { max_files: Some(10), max_age: None, max_bytes: None }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SnapshotConfig {
+    // 저장 디렉토리 (없으면 만듦)
+    pub dir: PathBuf,
+    // 파일 이름 템플릿: {client}, {time}(YYYYMMDD_HHMMSS), {frame_id}, {timestamp_us}
+    // {client}는 반드시 있어야 하고 바로 뒤에 숫자가 오면 안 됨 (보존 규칙이 이 클라이언트의 파일만 정확히 골라내는 데 씀)
+    // 확장자로 이미지 형식을 정함 (png, jpg, bmp 등)
+    pub name_template: String,
+    pub interval: SnapshotInterval,
+    pub retention: RetentionPolicy,
+}
+
+impl Default for SnapshotConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("captured_frames"),
+            name_template: "{client}_{time}_frame_{frame_id}.png".to_string(),
+            interval: SnapshotInterval::Frames(10),
+            retention: RetentionPolicy::default(),
+        }
+    }
+}
+
+// 템플릿 한 조각
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Client,
+    Time,
+    FrameId,
+    TimestampUs,
+}
+
+#[derive(Debug, Clone)]
+struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    fn parse(template: &str) -> Result<Self> {
+        if template.contains(['/', '\\']) {
+            bail!("파일 이름 템플릿에 경로 구분자를 쓸 수 없음: {}", template);
+        }
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..].find('}')
+                .with_context(|| format!("닫히지 않은 자리표시자: {}", template))? + start;
+            parts.push(match &rest[start + 1..end] {
+                "client" => Part::Client,
+                "time" => Part::Time,
+                "frame_id" => Part::FrameId,
+                "timestamp_us" => Part::TimestampUs,
+                other => bail!("알 수 없는 자리표시자 {{{}}}", other),
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+        if !parts.contains(&Part::Client) {
+            bail!("파일 이름 템플릿에 {{client}}가 없음: {}", template);
+        }
+        // {client} 바로 뒤가 숫자면 Client_1의 파일과 Client_10의 파일을 구분할 수 없음
+        let digit_after_client = parts.windows(2).any(|pair| pair[0] == Part::Client && match &pair[1] {
+            Part::FrameId | Part::TimestampUs => true,
+            Part::Literal(text) => text.starts_with(|c: char| c.is_ascii_digit()),
+            Part::Client | Part::Time => false,
+        });
+        if digit_after_client {
+            bail!("{{client}} 바로 뒤에 숫자가 올 수 없음 (구분자를 넣으세요): {}", template);
+        }
+        Ok(Self { parts })
+    }
+
+    fn render(&self, client: &str, frame: &Frame) -> String {
+        let time = Local::now().format(TIME_FORMAT).to_string();
+        self.parts.iter().map(|part| match part {
+            Part::Literal(text) => text.clone(),
+            Part::Client => client.to_string(),
+            Part::Time => time.clone(),
+            Part::FrameId => frame.frame_id.to_string(),
+            Part::TimestampUs => frame.timestamp_us.to_string(),
+        }).collect()
+    }
+
+    // 이 클라이언트가 이 템플릿으로 만든 이름인지 (다른 클라이언트 이름이 접두사여도 구분)
+    fn matches(&self, client: &str, name: &str) -> bool {
+        match_parts(&self.parts, client, name)
+    }
+}
+
+fn match_parts(parts: &[Part], client: &str, name: &str) -> bool {
+    let Some((part, rest)) = parts.split_first() else {
+        return name.is_empty();
+    };
+    match part {
+        Part::Literal(text) => name.strip_prefix(text.as_str()).is_some_and(|name| match_parts(rest, client, name)),
+        Part::Client => name.strip_prefix(client).is_some_and(|name| match_parts(rest, client, name)),
+        Part::Time => {
+            let is_time = name.len() >= TIME_LEN
+                && name.bytes().take(TIME_LEN).enumerate()
+                    .all(|(i, b)| if i == 8 { b == b'_' } else { b.is_ascii_digit() });
+            is_time && match_parts(rest, client, &name[TIME_LEN..])
+        }
+        // 숫자 한 개 이상: 길이마다 나머지가 맞는지 확인
+        Part::FrameId | Part::TimestampUs => {
+            let digits = name.bytes().take_while(u8::is_ascii_digit).count();
+            (1..=digits).any(|len| match_parts(rest, client, &name[len..]))
+        }
+    }
+}
+
+// 클라이언트마다 저장 간격을 세는 상태
+#[derive(Debug, Default)]
+struct ClientState {
+    received: u64,
+    last_saved: Option<Instant>,
+}
+
+// 받은 프레임 중 일부를 이미지 파일로 저장하고 보존 규칙에 따라 오래된 파일을 지움
+// 여러 클라이언트 스레드가 함께 쓰므로 간격 카운터만 잠그고 인코딩/파일 쓰기는 잠그지 않음
+#[derive(Debug)]
+pub struct Snapshotter {
+    config: SnapshotConfig,
+    template: Template,
+    clients: Mutex<HashMap<String, ClientState>>,
+}
+
+impl Snapshotter {
+    pub fn new(config: SnapshotConfig) -> Result<Self> {
+        let template = Template::parse(&config.name_template)?;
+        fs::create_dir_all(&config.dir)
+            .with_context(|| format!("스냅샷 디렉토리 생성 실패: {}", config.dir.display()))?;
+        Ok(Self { config, template, clients: Mutex::new(HashMap::new()) })
+    }
+
+    pub fn config(&self) -> &SnapshotConfig {
+        &self.config
+    }
+
+    // 간격에 맞는 프레임이면 저장하고 경로를 돌려줌 (아니면 None)
+    pub fn offer(&self, client: &str, frame: &Frame) -> Result<Option<PathBuf>> {
+        if !self.due(client) {
+            return Ok(None);
+        }
+        self.save(client, frame).map(Some)
+    }
+
+    // 간격 카운터를 한 칸 진행하고 이번 프레임을 저장할 차례인지
+    fn due(&self, client: &str) -> bool {
+        let mut clients = self.clients.lock().unwrap();
+        let state = clients.entry(client.to_string()).or_default();
+        let due = match self.config.interval {
+            SnapshotInterval::Frames(every) => state.received.is_multiple_of(every.max(1) as u64),
+            SnapshotInterval::Time(interval) => state.last_saved.is_none_or(|at| at.elapsed() >= interval),
+        };
+        state.received += 1;
+        if due {
+            state.last_saved = Some(Instant::now());
+        }
+        due
+    }
+
+    // 간격과 관계없이 지금 프레임을 저장
+    pub fn save(&self, client: &str, frame: &Frame) -> Result<PathBuf> {
+        // 받은 픽셀 데이터로 이미지 만들기 (JSON 모드는 RGB, 바이너리 모드는 보통 BGR)
+        let rgb = frame.convert(OutputFormat::Rgb8);
+        let img = RgbImage::from_raw(rgb.width as u32, rgb.height as u32, rgb.data)
+            .context("이미지 버퍼 크기 불일치")?;
+        let path = self.config.dir.join(self.template.render(client, frame));
+        img.save(&path).with_context(|| format!("이미지 저장 실패: {}", path.display()))?;
+
+        self.apply_retention(client, &path)?;
+        Ok(path)
+    }
+
+    // 연결이 끊긴 클라이언트의 간격 상태를 지움 (파일은 남김)
+    pub fn forget(&self, client: &str) {
+        self.clients.lock().unwrap().remove(client);
+    }
+
+    // 이 클라이언트의 파일 목록 (오래된 것부터)
+    pub fn files(&self, client: &str) -> Result<Vec<PathBuf>> {
+        Ok(self.client_files(client)?.into_iter().map(|file| file.path).collect())
+    }
+
+    fn client_files(&self, client: &str) -> Result<Vec<SavedFile>> {
+        let dir = &self.config.dir;
+        let mut files: Vec<SavedFile> = fs::read_dir(dir)
+            .with_context(|| format!("스냅샷 디렉토리 읽기 실패: {}", dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_str().is_some_and(|name| self.template.matches(client, name)))
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+                Some(SavedFile {
+                    path: entry.path(),
+                    modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                    size: metadata.len(),
+                })
+            })
+            .collect();
+        // 수정 시각이 같으면 이름 순서 (같은 초 안에서는 frame_id 순서가 대체로 맞음)
+        files.sort_by(|a, b| a.modified.cmp(&b.modified).then_with(|| a.path.cmp(&b.path)));
+        Ok(files)
+    }
+
+    fn apply_retention(&self, client: &str, newest: &Path) -> Result<()> {
+        let retention = self.config.retention;
+        let files = self.client_files(client)?;
+        let now = SystemTime::now();
+        let mut remaining = files.len();
+        let mut total: u64 = files.iter().map(|file| file.size).sum();
+
+        for file in &files {
+            if file.path == newest {
+                continue;
+            }
+            let too_old = retention.max_age
+                .is_some_and(|max_age| now.duration_since(file.modified).is_ok_and(|age| age > max_age));
+            let too_many = retention.max_files.is_some_and(|max_files| remaining > max_files);
+            let too_big = retention.max_bytes.is_some_and(|max_bytes| total > max_bytes);
+            if !(too_old || too_many || too_big) {
+                continue;
+            }
+            fs::remove_file(&file.path)
+                .with_context(|| format!("오래된 스냅샷 삭제 실패: {}", file.path.display()))?;
+            remaining -= 1;
+            total -= file.size;
+        }
+        Ok(())
+    }
+}
+
+struct SavedFile {
+    path: PathBuf,
+    modified: SystemTime,
+    size: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_webcam::udp::header::PixelFormat;
+
+    fn frame(frame_id: u32) -> Frame {
+        Frame {
+            stream_id: 0,
+            source: String::new(),
+            frame_id,
+            width: 2,
+            height: 2,
+            channels: 3,
+            pixel_format: PixelFormat::Bgr8,
+            stride: 6,
+            timestamp_us: 0,
+            data: vec![0; 12],
+        }
+    }
+
+    // 테스트마다 따로 쓰는 빈 디렉토리
+    fn empty_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("janggab-snapshot-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn snapshotter(dir: &Path, retention: RetentionPolicy) -> Snapshotter {
+        Snapshotter::new(SnapshotConfig {
+            dir: dir.to_path_buf(),
+            interval: SnapshotInterval::Frames(1),
+            retention,
+            ..Default::default()
+        }).unwrap()
+    }
+
+    fn file_names(snapshotter: &Snapshotter, client: &str) -> Vec<String> {
+        snapshotter.files(client).unwrap().iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Template::parse("{client}_{frame_id}.png").is_ok());
+        for template in [
+            "{time}.png",
+            "{client}/{time}.png",
+            "{client}_{time.png",
+            "{client}_{date}.png",
+            // 자리표시자든 글자든 {client} 바로 뒤의 숫자는 거절
+            "{client}{frame_id}.png",
+            "{client}{timestamp_us}.png",
+            "{client}1_{time}.png",
+        ] {
+            assert!(Template::parse(template).is_err(), "{}", template);
+        }
+    }
+
+    #[test]
+    fn matches_exact_client() {
+        let template = Template::parse(&SnapshotConfig::default().name_template).unwrap();
+        let name = "Client_1_20260101_120000_frame_5.png";
+        assert!(template.matches("Client_1", name));
+        assert!(!template.matches("Client_10", name));
+
+        let name = "Client_10_20260101_120000_frame_5.png";
+        assert!(template.matches("Client_10", name));
+        assert!(!template.matches("Client_1", name));
+
+        assert!(!template.matches("Client_1", "Client_1_2026_frame_5.png"));
+        assert!(!template.matches("Client_1", "Client_1_20260101_120000_frame_.png"));
+    }
+
+    #[test]
+    fn retention_keeps_newest_files_per_client() {
+        let dir = empty_dir("count");
+        let snapshotter = snapshotter(&dir, RetentionPolicy { max_files: Some(2), ..Default::default() });
+        snapshotter.save("Client_10", &frame(1)).unwrap();
+        for frame_id in 1..=4 {
+            snapshotter.save("Client_1", &frame(frame_id)).unwrap();
+        }
+
+        let names = file_names(&snapshotter, "Client_1");
+        assert_eq!(names.len(), 2);
+        assert!(names[0].ends_with("_frame_3.png") && names[1].ends_with("_frame_4.png"), "{:?}", names);
+        // 다른 클라이언트의 파일은 세지도 지우지도 않음
+        assert_eq!(file_names(&snapshotter, "Client_10").len(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn retention_removes_old_files() {
+        let dir = empty_dir("age");
+        let snapshotter = snapshotter(&dir, RetentionPolicy { max_files: None, max_age: Some(Duration::from_secs(3600)), max_bytes: None });
+        let two_hours_ago = SystemTime::now() - Duration::from_secs(2 * 3600);
+        for name in ["Client_1_20200101_000000_frame_9.png", "Client_10_20200101_000000_frame_9.png"] {
+            fs::File::create(dir.join(name)).unwrap().set_modified(two_hours_ago).unwrap();
+        }
+
+        let saved = snapshotter.save("Client_1", &frame(1)).unwrap();
+        assert_eq!(snapshotter.files("Client_1").unwrap(), [saved]);
+        // 다른 클라이언트의 오래된 파일은 그 클라이언트가 저장할 때 지움
+        assert_eq!(file_names(&snapshotter, "Client_10"), ["Client_10_20200101_000000_frame_9.png"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn retention_caps_total_bytes() {
+        let dir = empty_dir("bytes");
+        // 저장될 이미지 크기를 먼저 재서 오래된 파일 하나만 남도록 한도를 정함
+        let png = fs::metadata(snapshotter(&dir, RetentionPolicy::default()).save("Client_2", &frame(1)).unwrap()).unwrap().len();
+        let now = SystemTime::now();
+        for hours in 1..=3 {
+            let name = format!("Client_1_20200101_00000{}_frame_9.png", hours);
+            fs::write(dir.join(&name), [0u8; 100]).unwrap();
+            fs::File::options().write(true).open(dir.join(&name)).unwrap()
+                .set_modified(now - Duration::from_secs(hours * 3600)).unwrap();
+        }
+
+        let capped = snapshotter(&dir, RetentionPolicy { max_files: None, max_age: None, max_bytes: Some(png + 150) });
+        let saved = capped.save("Client_1", &frame(1)).unwrap();
+        // 가장 오래된 두 파일만 지움
+        assert_eq!(capped.files("Client_1").unwrap(), [dir.join("Client_1_20200101_000001_frame_9.png"), saved]);
+
+        // 한도가 새 파일보다 작아도 방금 저장한 파일은 남김
+        let tiny = snapshotter(&dir, RetentionPolicy { max_files: None, max_age: None, max_bytes: Some(1) });
+        let saved = tiny.save("Client_1", &frame(2)).unwrap();
+        assert_eq!(tiny.files("Client_1").unwrap(), [saved]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn time_interval_samples_per_client() {
+        let dir = empty_dir("time");
+        let snapshotter = Snapshotter::new(SnapshotConfig {
+            dir: dir.clone(),
+            interval: SnapshotInterval::Time(Duration::from_millis(200)),
+            retention: RetentionPolicy { max_files: None, max_age: None, max_bytes: None },
+            ..Default::default()
+        }).unwrap();
+
+        assert!(snapshotter.offer("Client_1", &frame(1)).unwrap().is_some());
+        assert!(snapshotter.offer("Client_1", &frame(2)).unwrap().is_none());
+        // 간격은 클라이언트마다 따로 셈
+        assert!(snapshotter.offer("Client_2", &frame(1)).unwrap().is_some());
+
+        std::thread::sleep(Duration::from_millis(250));
+        assert!(snapshotter.offer("Client_1", &frame(3)).unwrap().is_some());
+        assert!(snapshotter.offer("Client_1", &frame(4)).unwrap().is_none());
+        assert_eq!(snapshotter.files("Client_1").unwrap().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
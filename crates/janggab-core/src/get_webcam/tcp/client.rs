@@ -1,153 +1,320 @@
-use std::io::{self, BufRead, BufReader, Write};
-use std::net::TcpStream;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
 use std::thread;
-use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Sender};
 use std::time::{Duration, Instant};
 use opencv::{
     prelude::*,
-    videoio::{self, VideoCapture, CAP_ANY},
+    videoio,
 };
-use serde::{Deserialize, Serialize};
-
-#[derive(Serialize, Deserialize, Debug)]
-struct PixelData {
-    x: i32,
-    y: i32,
-    r: u8,
-    g: u8,
-    b: u8,
-    timestamp: u64,
+
+use crate::auth;
+use crate::connection::{ConnectionState, LivenessConfig, StateNotifier};
+use crate::crypto::{self, Role, SecurityConfig};
+use crate::endpoint::EndpointConfig;
+use crate::get_webcam::device::{self, CaptureConfig};
+use crate::get_webcam::udp::frame::Frame;
+use crate::get_webcam::udp::header::PixelFormat;
+
+use super::flow::{self, FlowControl, LatestSlot, SendStats, CREDIT_LINE, READY_LINE};
+use super::wire::{WireFormat, BINARY_LINE};
+
+#[derive(Debug, Clone, Default)]
+pub struct ClientConfig {
+    // 연결할 서버 (peer가 없으면 같은 머신의 서버만 시도)
+    pub endpoint: EndpointConfig,
+    // psk가 Some이면 연결 직후 서버와 챌린지/응답으로 서로 확인
+    // encrypt가 true면 이후 스트림 전체를 암호화 (서버도 같은 설정이어야 함)
+    pub security: SecurityConfig,
+    // 서버가 timeout 동안 응답하지 않으면 끊긴 것으로 보고 reconnect_interval마다 다시 연결
+    pub liveness: LivenessConfig,
+    // 연결 상태가 바뀔 때마다 전달
+    pub events: Option<Sender<ConnectionState>>,
+    // 사용할 카메라와 캡처 백엔드
+    pub capture: CaptureConfig,
+    // 프레임 전송 형식 (Json은 디버깅용, 서버는 두 형식을 모두 받음)
+    pub wire: WireFormat,
+    // 서버가 느릴 때 다음 프레임을 보내는 시점 (밀린 프레임은 항상 최신 하나만 남김)
+    pub flow: FlowControl,
+    // 통계를 출력할 때마다 (약 5초) 연결의 누적 전송 통계를 전달
+    pub stats: Option<Sender<SendStats>>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ScreenFrame {
-    width: i32,
-    height: i32,
-    pixels: Vec<PixelData>,
-    frame_id: u64,
+// 캡처 루프가 끝난 이유
+enum CaptureEnd {
+    // 서버와 연결이 끊김 (다시 연결)
+    Disconnected,
+    // 카메라를 열 수 없음 (종료)
+    CameraUnavailable,
 }
 
+type Streams = (Box<dyn Read + Send>, Box<dyn Write + Send>);
+
+// ip는 "host" 또는 "host:port" (포트가 없으면 기본 포트)
 pub fn client_main(ip: &str) {
-    let server_addrs = vec![
-        "127.0.0.1:52525",     // localhost
-        "localhost:52525",     // localhost 호스트명
-        ip,
+    client_main_with(ClientConfig {
+        endpoint: EndpointConfig::with_peer(ip),
+        ..ClientConfig::default()
+    })
+}
+
+pub fn client_main_with(config: ClientConfig) {
+    let mut server_addrs = vec![
+        config.endpoint.loopback_addr().to_string(), // localhost
     ];
+    server_addrs.extend(config.endpoint.peer.clone());
         
     println!("🔧 연결 시도할 주소들:");
     for addr in &server_addrs {
         println!("   - {}", addr);
     }
-        
-    let mut stream = None;
-    let mut connected_addr = String::new();
-        
-    for server_addr in &server_addrs {
-        println!("🔄 서버 {}에 연결 중...", server_addr);
-                
-        match TcpStream::connect(server_addr) {
-            Ok(s) => {
-                println!("✅ {}에 연결 성공!", server_addr);
-                stream = Some(s);
-                connected_addr = server_addr.to_string();
-                break;
-            }
-            Err(e) => {
-                println!("❌ {} 연결 실패: {}", server_addr, e);
+
+    let mut state = StateNotifier::new(config.events.clone());
+    state.set(ConnectionState::Connecting);
+
+    loop {
+        let connected_before = state.is_lost();
+        let Some(stream) = connect_any(&config.endpoint, &server_addrs) else {
+            if connected_before {
+                // 한 번 연결됐던 서버면 다시 올라올 때까지 계속 시도
+                thread::sleep(config.liveness.reconnect_interval);
+                continue;
             }
-        }
-    }
-        
-    let mut stream = match stream {
-        Some(s) => {
-            println!("🎉 서버 {}에 연결되었습니다!", connected_addr);
-            println!("🚀 최적화된 스트리밍 모드로 화면 캡처를 시작합니다.");
-            println!("📺 영상은 서버에서만 표시되며, 클라이언트는 백그라운드에서 전송만 합니다.");
-            println!("⏹️  Ctrl+C를 눌러 종료하세요.\n");
-            s
-        }
-        None => {
             eprintln!("❌ 모든 서버 주소 연결 실패");
             eprintln!("💡 다음을 확인해주세요:");
             eprintln!("   1. WSL에서 서버가 실행 중인지 확인");
             eprintln!("   2. 방화벽 설정 확인");
             eprintln!("   3. IP 주소가 올바른지 확인");
             return;
+        };
+
+        // 끊을 때 읽기 스레드를 깨우려고 원본 소켓을 보관 (암호화하면 reader/writer는 감싼 타입)
+        let socket = stream.try_clone().ok();
+        let (reader, mut writer) = match open_session(stream, &config) {
+            Some(streams) => streams,
+            None if connected_before => {
+                thread::sleep(config.liveness.reconnect_interval);
+                continue;
+            }
+            None => return,
+        };
+
+        if connected_before {
+            println!("🔁 서버에 다시 연결되었습니다. 스트리밍을 이어갑니다.");
+            state.set(ConnectionState::Reconnected);
+        } else {
+            println!("🚀 최적화된 스트리밍 모드로 화면 캡처를 시작합니다.");
+            println!("📺 영상은 서버에서만 표시되며, 클라이언트는 백그라운드에서 전송만 합니다.");
+            println!("⏹️  Ctrl+C를 눌러 종료하세요.\n");
+            state.set(ConnectionState::Connected);
         }
-    };
-            
-    let stream_clone = stream.try_clone().unwrap();
         
-    // 서버로부터 메시지를 받는 스레드
-    let (tx, rx) = mpsc::channel();
-    thread::spawn(move || {
-        let mut reader = BufReader::new(stream_clone);
-        let mut buffer = String::new();
-                
-        loop {
-            buffer.clear();
-            match reader.read_line(&mut buffer) {
-                Ok(0) => {
-                    println!("🔌 서버 연결이 종료되었습니다.");
-                    tx.send(()).unwrap();
-                    break;
-                }
-                Ok(_) => {
-                    // 서버 메시지는 조용히 처리 (성능 최적화)
-                    if buffer.trim().contains("처리 완료") {
-                        // 간단한 확인 메시지만 출력
-                        print!(".");
-                        io::stdout().flush().unwrap();
+        // 캡처한 프레임을 전송 스레드에 넘기는 칸 (READY_LINE을 받으면 credit 추가)
+        let slot = Arc::new(LatestSlot::new(config.flow));
+        let reader_slot = Arc::clone(&slot);
+
+        // 서버로부터 메시지를 받는 스레드
+        let (tx, rx) = mpsc::channel();
+        let reader_thread = thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            let mut buffer = String::new();
+                    
+            loop {
+                buffer.clear();
+                match reader.read_line(&mut buffer) {
+                    Ok(0) => {
+                        println!("🔌 서버 연결이 종료되었습니다.");
+                        let _ = tx.send(());
+                        break;
+                    }
+                    Ok(_) if buffer.trim() == READY_LINE => reader_slot.grant(),
+                    // heartbeat 응답과 채팅 메시지는 연결이 살아 있다는 뜻으로만 씀
+                    Ok(_) => {}
+                    Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                        eprintln!("⏱️ 서버가 응답하지 않습니다.");
+                        let _ = tx.send(());
+                        break;
+                    }
+                    Err(e) => {
+                        eprintln!("❌ 메시지 받기 오류: {}", e);
+                        let _ = tx.send(());
+                        break;
                     }
-                }
-                Err(e) => {
-                    eprintln!("❌ 메시지 받기 오류: {}", e);
-                    tx.send(()).unwrap();
-                    break;
                 }
             }
+        });
+
+        // 최적화된 화면 캡처 시작 (화면 표시 없음)
+        let end = start_optimized_capture(&mut *writer, &rx, &slot, &config);
+
+        // 이전 연결의 읽기 스레드가 끝난 뒤에 다시 연결 (남은 READY_LINE이 새 연결의 credit에 섞이지 않도록)
+        if let Some(socket) = &socket {
+            let _ = socket.shutdown(Shutdown::Both);
         }
-    });
+        let _ = reader_thread.join();
+
+        match end {
+            Ok(CaptureEnd::Disconnected) => {
+                println!("\n🔄 {:.1}초 후 다시 연결합니다.", config.liveness.reconnect_interval.as_secs_f32());
+                state.set(ConnectionState::Lost);
+                thread::sleep(config.liveness.reconnect_interval);
+            }
+            Ok(CaptureEnd::CameraUnavailable) => {
+                println!("\n👋 스트리밍을 종료합니다.");
+                return;
+            }
+            Err(e) => {
+                eprintln!("❌ 화면 캡처 오류: {}", e);
+                return;
+            }
+        }
+    }
+}
 
-    // 최적화된 화면 캡처 시작 (화면 표시 없음)
-    match start_optimized_capture(&mut stream, &rx) {
-        Ok(_) => println!("\n👋 스트리밍을 종료합니다."),
-        Err(e) => eprintln!("❌ 화면 캡처 오류: {}", e),
+fn connect_any(endpoint: &EndpointConfig, server_addrs: &[String]) -> Option<TcpStream> {
+    for server_addr in server_addrs {
+        println!("🔄 서버 {}에 연결 중...", server_addr);
+                
+        match endpoint.resolve(server_addr).and_then(TcpStream::connect) {
+            Ok(s) => {
+                println!("🎉 서버 {}에 연결되었습니다!", server_addr);
+                return Some(s);
+            }
+            Err(e) => {
+                println!("❌ {} 연결 실패: {}", server_addr, e);
+            }
+        }
     }
+    None
 }
 
-fn start_optimized_capture(stream: &mut TcpStream, rx: &mpsc::Receiver<()>) -> opencv::Result<()> {
-    // 웹캠 초기화
-    let mut cap = VideoCapture::new(0, CAP_ANY)?;
-    
-    if !cap.is_opened()? {
-        eprintln!("❌ 카메라를 열 수 없습니다. 웹캠이 연결되어 있는지 확인하세요.");
-        return Ok(());
+// 인증, 읽기 타임아웃, 암호화를 차례로 설정
+fn open_session(mut stream: TcpStream, config: &ClientConfig) -> Option<Streams> {
+    let security = &config.security;
+    if let Some(psk) = &security.psk {
+        match auth::tcp_connect(&mut stream, psk) {
+            Ok(true) => println!("🔒 페어링 키 인증 완료"),
+            Ok(false) => {
+                eprintln!("❌ 서버가 같은 페어링 키를 갖고 있지 않습니다.");
+                return None;
+            }
+            Err(e) => {
+                eprintln!("❌ 페어링 키 인증 실패 (서버 키가 다르면 연결이 끊김): {}", e);
+                return None;
+            }
+        }
+    }
+
+    // 서버는 heartbeat마다 응답하므로 timeout 동안 아무것도 오지 않으면 끊긴 것
+    if let Err(e) = stream.set_read_timeout(Some(config.liveness.timeout)) {
+        eprintln!("❌ 읽기 타임아웃 설정 실패: {}", e);
+        return None;
+    }
+    // credit 모드에서는 프레임마다 서버 응답을 기다리므로 작은 쓰기를 모아 보내지 않음
+    let _ = stream.set_nodelay(true);
+            
+    if security.encrypt {
+        match crypto::secure_stream(stream, security.psk.as_ref(), Role::Initiator) {
+            Ok((reader, writer)) => {
+                println!("🔒 암호화 연결 설정 완료");
+                Some((Box::new(reader), Box::new(writer)))
+            }
+            Err(e) => {
+                eprintln!("❌ 암호화 키 교환 실패: {}", e);
+                None
+            }
+        }
+    } else {
+        match stream.try_clone() {
+            Ok(reader) => Some((Box::new(reader), Box::new(stream))),
+            Err(e) => {
+                eprintln!("❌ 소켓 복제 실패: {}", e);
+                None
+            }
+        }
     }
+}
+
+fn start_optimized_capture(stream: &mut (dyn Write + Send), rx: &mpsc::Receiver<()>, slot: &LatestSlot<Frame>, config: &ClientConfig) -> opencv::Result<CaptureEnd> {
+    // 웹캠 초기화
+    let mut cap = match device::open(&config.capture) {
+        Ok((cap, index)) => {
+            println!("📷 {}번 카메라 사용 ({})", index, cap.get_backend_name().unwrap_or_default());
+            cap
+        }
+        Err(e) => {
+            eprintln!("❌ {:#}", e);
+            return Ok(CaptureEnd::CameraUnavailable);
+        }
+    };
 
     // 최적화된 캡처 해상도 설정 (성능 우선)
     cap.set(videoio::CAP_PROP_FRAME_WIDTH, 320.0)?;  // 해상도 낮춤
     cap.set(videoio::CAP_PROP_FRAME_HEIGHT, 240.0)?; // 해상도 낮춤
     cap.set(videoio::CAP_PROP_FPS, 15.0)?;           // FPS 제한
 
-    let mut frame = Mat::default();
-    let mut frame_id = 0u64;
-    let mut last_frame_time = Instant::now();
-    let mut last_stats_time = Instant::now();
-    let frame_interval = Duration::from_millis(66); // 약 15 FPS
-    let mut total_pixels_sent = 0u64;
-    let mut frames_sent = 0u64;
+    // 서버가 이후 프레임마다 READY_LINE을 보내고, 메시지를 바이너리로 읽도록 알림
+    let mut mode_lines = Vec::new();
+    if config.flow == FlowControl::Credit {
+        mode_lines.push(CREDIT_LINE);
+    }
+    if config.wire == WireFormat::Binary {
+        mode_lines.push(BINARY_LINE);
+    }
+    for line in mode_lines {
+        if let Err(e) = writeln!(stream, "{}", line).and_then(|_| stream.flush()) {
+            eprintln!("❌ 전송 형식 알림 오류: {}", e);
+            return Ok(CaptureEnd::Disconnected);
+        }
+    }
 
     println!("📹 최적화된 스트리밍 시작! (화면 표시 없음)");
     println!("📊 성능 통계:");
     println!("   - 해상도: 320x240");
     println!("   - 프레임율: ~15 FPS");
+    println!("   - 전송 형식: {:?}", config.wire);
+    println!("   - 흐름 제어: {:?}", config.flow);
     println!("   - UI 오버헤드: 없음");
     println!();
 
+    // 소켓 쓰기는 전송 스레드가 맡아서 서버가 느려도 캡처가 막히지 않음
+    let heartbeat_interval = config.liveness.heartbeat_interval;
+    let (result, sent) = thread::scope(|scope| {
+        let sender = scope.spawn(move || flow::run_sender(stream, slot, config.wire, heartbeat_interval));
+        let result = capture_loop(&mut cap, rx, slot, config);
+        slot.close();
+        (result, sender.join())
+    });
+    match sent {
+        Ok(Err(e)) => eprintln!("❌ 데이터 전송 오류: {}", e),
+        Err(_) => eprintln!("❌ 전송 스레드가 비정상 종료되었습니다"),
+        Ok(Ok(())) => {}
+    }
+
+    let stats = slot.stats();
+    println!("📊 최종 통계:");
+    println!("   - 총 캡처 프레임: {}", stats.captured);
+    println!("   - 총 전송 프레임: {}", stats.sent);
+    println!("   - 밀려서 버린 프레임: {}", stats.dropped);
+    println!("   - 최적화 효과: UI 렌더링 오버헤드 제거로 성능 향상");
+
+    result
+}
+
+// 연결이 끊기거나 전송 스레드가 멈출 때까지 캡처해서 슬롯에 넣음
+fn capture_loop(cap: &mut videoio::VideoCapture, rx: &mpsc::Receiver<()>, slot: &LatestSlot<Frame>, config: &ClientConfig) -> opencv::Result<CaptureEnd> {
+    let mut frame = Mat::default();
+    let mut frame_id = 0u32;
+    let mut last_frame_time = Instant::now();
+    let mut last_stats_time = Instant::now();
+    let frame_interval = Duration::from_millis(66); // 약 15 FPS
+    let mut total_pixels_captured = 0u64;
+    let mut last_stats = SendStats::default();
+
     loop {
         // 연결 상태 확인
-        if rx.try_recv().is_ok() {
+        if rx.try_recv().is_ok() || slot.is_closed() {
             break;
         }
 
@@ -164,112 +331,71 @@ fn start_optimized_capture(stream: &mut TcpStream, rx: &mpsc::Receiver<()>) -> o
         }
 
         last_frame_time = Instant::now();
-        frame_id += 1;
-        frames_sent += 1;
+        frame_id = frame_id.wrapping_add(1);
+        total_pixels_captured += (frame.cols() * frame.rows()) as u64;
 
-        // 전체 픽셀 데이터 전송 (최적화된 버전)
-        let screen_frame = get_optimized_frame(&frame, frame_id)?;
-        total_pixels_sent += screen_frame.pixels.len() as u64;
-        
-        // JSON으로 직렬화하여 서버로 전송
-        match serde_json::to_string(&screen_frame) {
-            Ok(json_data) => {
-                if let Err(e) = writeln!(stream, "{}", json_data) {
-                    eprintln!("❌ 데이터 전송 오류: {}", e);
-                    break;
-                }
-            }
-            Err(e) => {
-                eprintln!("❌ JSON 직렬화 오류: {}", e);
-                continue;
+        // 직렬화는 전송 스레드가 보낼 때 함 (밀려서 교체된 프레임은 직렬화하지 않음)
+        match frame_from_mat(&frame, frame_id) {
+            Ok(outgoing) => {
+                slot.put(outgoing);
             }
+            Err(e) => eprintln!("❌ 프레임 변환 오류: {}", e),
         }
 
         // 성능 통계 출력 (5초마다)
         if last_stats_time.elapsed() >= Duration::from_secs(5) {
-            let avg_fps = frames_sent as f64 / last_stats_time.elapsed().as_secs_f64();
-            let avg_pixels_per_sec = total_pixels_sent as f64 / last_stats_time.elapsed().as_secs_f64();
+            let elapsed = last_stats_time.elapsed().as_secs_f64();
+            let stats = slot.stats();
             
             println!("📊 성능 통계 (최근 5초):");
-            println!("   - 평균 FPS: {:.1}", avg_fps);
-            println!("   - 전송 픽셀/초: {:.0}", avg_pixels_per_sec);
+            println!("   - 평균 전송 FPS: {:.1}", (stats.sent - last_stats.sent) as f64 / elapsed);
+            println!("   - 캡처 픽셀/초: {:.0}", total_pixels_captured as f64 / elapsed);
+            println!("   - 밀려서 버린 프레임: {} (누적 {})", stats.dropped - last_stats.dropped, stats.dropped);
             println!("   - 총 프레임: {}", frame_id);
-            println!("   - 메모리 효율: 화면 표시 없음으로 최적화됨");
             println!();
+            if let Some(events) = &config.stats {
+                let _ = events.send(stats);
+            }
             
             // 통계 리셋
             last_stats_time = Instant::now();
-            frames_sent = 0;
-            total_pixels_sent = 0;
+            last_stats = stats;
+            total_pixels_captured = 0;
         }
     }
 
-    println!("📊 최종 통계:");
-    println!("   - 총 전송 프레임: {}", frame_id);
-    println!("   - 최적화 효과: UI 렌더링 오버헤드 제거로 성능 향상");
-
-    Ok(())
+    Ok(CaptureEnd::Disconnected)
 }
 
-fn get_optimized_frame(frame: &Mat, frame_id: u64) -> opencv::Result<ScreenFrame> {
-    let height = frame.rows();
-    let width = frame.cols();
-    let mut pixels = Vec::with_capacity((width * height) as usize); // 메모리 최적화
-    
-    let timestamp = std::time::SystemTime::now()
+// Mat 버퍼를 복사해서 전송용 프레임으로 (시각은 UNIX µs)
+fn frame_from_mat(frame: &Mat, frame_id: u32) -> Result<Frame, Box<dyn std::error::Error>> {
+    let channels = frame.channels() as usize;
+    let pixel_format = PixelFormat::from_opencv_channels(channels)
+        .ok_or("지원하지 않는 채널 수")?;
+    // ROI 등으로 연속되지 않은 Mat은 복사해서 연속 버퍼로
+    let continuous;
+    let frame = if frame.is_continuous() {
+        frame
+    } else {
+        continuous = frame.try_clone()?;
+        &continuous
+    };
+    let (width, height) = (frame.cols() as usize, frame.rows() as usize);
+    let timestamp_us = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
-        .as_millis() as u64;
-
-    // 모든 픽셀 데이터 읽기 (최적화된 버전)
-    for y in 0..height {
-        for x in 0..width {
-            // 픽셀 값 읽기 (BGR 형식이므로 순서 주의)
-            let pixel = frame.at_2d::<opencv::core::Vec3b>(y, x)?;
-            
-            pixels.push(PixelData {
-                x,
-                y,
-                r: pixel[2], // BGR -> RGB 변환
-                g: pixel[1],
-                b: pixel[0],
-                timestamp,
-            });
-        }
-    }
-
-    Ok(ScreenFrame {
+        .as_micros() as u64;
+    Ok(Frame {
+        stream_id: 0,
+        source: String::new(),
+        frame_id,
         width,
         height,
-        pixels,
-        frame_id,
+        channels,
+        pixel_format,
+        stride: width * channels,
+        timestamp_us,
+        data: frame.data_bytes()?.to_vec(),
     })
 }
 
-// 키보드 입력 처리 (백그라운드에서 종료 감지)
-#[allow(dead_code)]
-fn check_exit_condition() -> bool {
-    // 실제 구현에서는 더 정교한 종료 조건 확인 가능
-    // 현재는 Ctrl+C로만 종료 가능
-    false
-}
-
-// 네트워크 상태 모니터링
-#[allow(dead_code)]
-fn monitor_network_performance(stream: &TcpStream) {
-    match stream.peer_addr() {
-        Ok(addr) => {
-            println!("🌐 연결된 서버: {}", addr);
-        }
-        Err(_) => {
-            println!("⚠️ 네트워크 상태 확인 불가");
-        }
-    }
-}
-
-// 메모리 사용량 최적화를 위한 헬퍼 함수
-#[allow(dead_code)]
-fn optimize_memory_usage() {
-    // 가비지 컬렉션 힌트 (Rust에서는 자동이므로 실제로는 불필요)
-    println!("🧹 메모리 최적화: 자동 메모리 관리 활성화됨");
-}
@@ -0,0 +1,255 @@
+use std::io::{self, Write};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::get_webcam::udp::frame::Frame;
+
+use super::HEARTBEAT_LINE;
+use super::wire::{self, WireFormat};
+
+// 클라이언트가 다음 프레임을 언제 보낼지
+// 두 모드 모두 보내지 못한 프레임은 최대 하나만 들고 있고, 새 프레임이 오면 교체함
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FlowControl {
+    // 소켓에 쓸 수 있으면 바로 보냄
+    #[default]
+    LatestFrame,
+    // 서버가 프레임을 처리하고 READY_LINE을 돌려준 뒤에만 다음 프레임을 보냄
+    Credit,
+}
+
+// 클라이언트 → 서버: 이 연결은 credit 모드 (BINARY_LINE보다 먼저 보냄)
+pub(crate) const CREDIT_LINE: &str = "__janggab_credit__";
+// 서버 → 클라이언트: 프레임 하나를 처리했으니 다음 프레임을 보내도 됨
+pub(crate) const READY_LINE: &str = "__janggab_ready__";
+
+// 연결 하나의 누적 전송 통계
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SendStats {
+    pub captured: u64,
+    pub sent: u64,
+    // 보내기 전에 더 새 프레임으로 교체되어 직렬화하지 않고 버린 수
+    pub dropped: u64,
+}
+
+pub(crate) enum Taken<T> {
+    Frame(T),
+    // timeout 동안 보낼 프레임이 없음 (또는 credit이 없음)
+    Idle,
+    Closed,
+}
+
+struct SlotState<T> {
+    pending: Option<T>,
+    credits: u32,
+    closed: bool,
+    stats: SendStats,
+}
+
+// 캡처 스레드가 넣고 전송 스레드가 꺼내는 한 칸짜리 우편함
+pub(crate) struct LatestSlot<T> {
+    state: Mutex<SlotState<T>>,
+    changed: Condvar,
+    credit: bool,
+}
+
+impl<T> LatestSlot<T> {
+    // credit 모드는 첫 프레임 하나를 보낼 수 있는 상태로 시작
+    pub fn new(flow: FlowControl) -> Self {
+        Self {
+            state: Mutex::new(SlotState { pending: None, credits: 1, closed: false, stats: SendStats::default() }),
+            changed: Condvar::new(),
+            credit: flow == FlowControl::Credit,
+        }
+    }
+
+    // 닫혔으면 false
+    pub fn put(&self, item: T) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.closed {
+            return false;
+        }
+        state.stats.captured += 1;
+        if state.pending.replace(item).is_some() {
+            state.stats.dropped += 1;
+        }
+        self.changed.notify_all();
+        true
+    }
+
+    pub fn take(&self, timeout: Duration) -> Taken<T> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.closed {
+                return Taken::Closed;
+            }
+            if state.pending.is_some() && (!self.credit || state.credits > 0) {
+                if self.credit {
+                    state.credits -= 1;
+                }
+                state.stats.sent += 1;
+                return state.pending.take().map_or(Taken::Idle, Taken::Frame);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Taken::Idle;
+            }
+            state = self.changed.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    // 서버의 READY_LINE 하나마다 호출
+    pub fn grant(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.credits = state.credits.saturating_add(1);
+        self.changed.notify_all();
+    }
+
+    pub fn close(&self) {
+        self.state.lock().unwrap().closed = true;
+        self.changed.notify_all();
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    pub fn stats(&self) -> SendStats {
+        self.state.lock().unwrap().stats
+    }
+}
+
+// 전송 스레드: 슬롯의 프레임과 heartbeat를 stream에 씀 (슬롯이 닫히면 Ok로 끝남)
+// 쓰기 오류가 나면 슬롯을 닫아 캡처 쪽도 멈추게 함
+pub(crate) fn run_sender(stream: &mut dyn Write, slot: &LatestSlot<Frame>, format: WireFormat, heartbeat_interval: Duration) -> io::Result<()> {
+    let result = send_until_closed(stream, slot, format, heartbeat_interval);
+    slot.close();
+    result
+}
+
+fn send_until_closed(stream: &mut dyn Write, slot: &LatestSlot<Frame>, format: WireFormat, heartbeat_interval: Duration) -> io::Result<()> {
+    let mut last_heartbeat = Instant::now();
+    loop {
+        match slot.take(heartbeat_interval.saturating_sub(last_heartbeat.elapsed())) {
+            Taken::Frame(frame) => wire::send_frame(stream, &frame, format)?,
+            Taken::Idle => {}
+            Taken::Closed => return Ok(()),
+        }
+
+        // 프레임과 관계없이 주기적으로 보내서 서버의 응답을 받음
+        if last_heartbeat.elapsed() >= heartbeat_interval {
+            last_heartbeat = Instant::now();
+            match format {
+                WireFormat::Binary => wire::write_heartbeat(stream)?,
+                WireFormat::Json => writeln!(stream, "{}", HEARTBEAT_LINE).and_then(|_| stream.flush())?,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::get_webcam::udp::header::PixelFormat;
+
+    const NO_WAIT: Duration = Duration::ZERO;
+
+    fn taken(taken: Taken<u32>) -> Option<u32> {
+        match taken {
+            Taken::Frame(item) => Some(item),
+            Taken::Idle => None,
+            Taken::Closed => panic!("슬롯이 닫힘"),
+        }
+    }
+
+    #[test]
+    fn latest_frame_replaces_pending() {
+        let slot = LatestSlot::new(FlowControl::LatestFrame);
+        assert!(slot.put(1));
+        assert!(slot.put(2));
+        assert!(slot.put(3));
+        assert_eq!(taken(slot.take(NO_WAIT)), Some(3));
+        assert_eq!(taken(slot.take(NO_WAIT)), None);
+        assert!(slot.put(4));
+        assert_eq!(taken(slot.take(NO_WAIT)), Some(4));
+        assert_eq!(slot.stats(), SendStats { captured: 4, sent: 2, dropped: 2 });
+    }
+
+    #[test]
+    fn credit_waits_for_grant() {
+        let slot = LatestSlot::new(FlowControl::Credit);
+        slot.put(1);
+        // 첫 프레임은 credit 없이 보냄
+        assert_eq!(taken(slot.take(NO_WAIT)), Some(1));
+        slot.put(2);
+        assert_eq!(taken(slot.take(Duration::from_millis(20))), None);
+        slot.put(3);
+        slot.grant();
+        assert_eq!(taken(slot.take(NO_WAIT)), Some(3));
+        assert_eq!(slot.stats(), SendStats { captured: 3, sent: 2, dropped: 1 });
+
+        // 다른 스레드의 grant가 기다리던 take를 깨움
+        slot.put(4);
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(20));
+                slot.grant();
+            });
+            assert_eq!(taken(slot.take(Duration::from_secs(5))), Some(4));
+        });
+    }
+
+    #[test]
+    fn close_stops_both_sides() {
+        let slot = LatestSlot::new(FlowControl::LatestFrame);
+        slot.put(1);
+        slot.close();
+        assert!(slot.is_closed());
+        assert!(!slot.put(2));
+        assert!(matches!(slot.take(NO_WAIT), Taken::Closed));
+    }
+
+    #[test]
+    fn sender_writes_frames_and_heartbeats() {
+        let slot = LatestSlot::new(FlowControl::LatestFrame);
+        let frame = Frame {
+            stream_id: 0,
+            source: String::new(),
+            frame_id: 1,
+            width: 2,
+            height: 1,
+            channels: 1,
+            pixel_format: PixelFormat::Gray8,
+            stride: 2,
+            timestamp_us: 0,
+            data: vec![7, 8],
+        };
+        let mut written = Vec::new();
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                slot.put(frame);
+                thread::sleep(Duration::from_millis(50));
+                slot.close();
+            });
+            run_sender(&mut written, &slot, WireFormat::Binary, Duration::from_millis(10)).unwrap();
+        });
+
+        let mut stream = &written[..];
+        let mut frames = 0;
+        let mut heartbeats = 0;
+        while let Some(message) = wire::read_message(&mut stream, "test").unwrap() {
+            match message {
+                wire::Message::Frame(frame) => {
+                    assert_eq!(frame.data, [7, 8]);
+                    frames += 1;
+                }
+                wire::Message::Heartbeat => heartbeats += 1,
+            }
+        }
+        assert_eq!(frames, 1);
+        assert!(heartbeats >= 1);
+    }
+}
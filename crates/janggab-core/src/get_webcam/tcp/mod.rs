@@ -1,2 +1,7 @@
 pub mod client;
-pub mod server;
\ No newline at end of file
+pub mod flow;
+pub mod server;
+pub mod wire;
+
+// 전송할 프레임이 없어도 양쪽이 살아 있음을 알리는 줄 (서버는 보낸 클라이언트에게만 되돌려 줌)
+pub(crate) const HEARTBEAT_LINE: &str = "__janggab_heartbeat__";
@@ -1,52 +1,365 @@
 use std::collections::HashMap;
-use std::net::{TcpListener, TcpStream};
-use std::io::{BufRead, BufReader, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::path::Path;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use std::path::PathBuf;
+use std::fmt;
+use anyhow::{bail, Result, Context};
 use chrono::Local;
-use serde::{Deserialize, Serialize};
-use image::{ImageBuffer, Rgb, RgbImage};
-use opencv::{
-    prelude::*,
-    highgui,
-    core::Size,
-    imgproc,
-};
-
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct PixelData {
-    x: i32,
-    y: i32,
-    r: u8,
-    g: u8,
-    b: u8,
-    timestamp: u64,
+
+use crate::auth;
+use crate::connection::LivenessConfig;
+use crate::crypto::{self, Role, SecurityConfig};
+use crate::endpoint::EndpointConfig;
+use crate::get_webcam::record::{EncodedFrame, RecordConfig, Recorder, Recording};
+use crate::get_webcam::sink::SharedSink;
+use crate::get_webcam::snapshot::{SnapshotConfig, Snapshotter};
+use crate::get_webcam::udp::frame::Frame;
+
+use super::HEARTBEAT_LINE;
+use super::flow::{CREDIT_LINE, READY_LINE};
+use super::wire::{self, Message, ScreenFrame, BINARY_LINE};
+
+// 새 연결이 없을 때 정지 요청을 확인하는 간격
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// 암호화를 쓰면 쓰기 반쪽이 EncryptedWriter라서 trait object로 보관
+// 쓰기는 클라이언트마다 따로 잠가서 느린 클라이언트 하나가 맵 전체를 막지 않게 함
+type ClientWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+type ClientMap = Arc<Mutex<HashMap<String, ClientWriter>>>;
+
+// 클라이언트가 보낸 메시지 하나 (줄 모드와 바이너리 모드 공통)
+enum Incoming {
+    // 이후 메시지는 바이너리 형식
+    BinaryMode,
+    // 프레임을 처리할 때마다 READY_LINE을 돌려줘야 함
+    CreditMode,
+    Heartbeat,
+    Frame(Frame),
+    Text(String),
+}
+
+#[derive(Clone)]
+pub struct ServerConfig {
+    // 바인딩할 주소와 포트
+    pub endpoint: EndpointConfig,
+    // psk가 Some이면 같은 키로 챌린지/응답을 통과한 클라이언트만 받음
+    // encrypt가 true면 이후 스트림 전체를 암호화 (클라이언트도 같은 설정이어야 함)
+    pub security: SecurityConfig,
+    // timeout 동안 아무 줄도 보내지 않은 클라이언트는 연결을 끊음
+    pub liveness: LivenessConfig,
+    // Some이면 받은 프레임을 클라이언트마다 "Live Stream - {이름}"으로 보냄
+    // None이면 화면 없이 수신만 함 (HighguiSink는 `display` feature 필요)
+    pub sink: Option<SharedSink>,
+    // frames()에 쌓아 둘 최대 프레임 수 (가득 차면 새 프레임은 버리고 dropped_frames에 셈)
+    pub frame_queue: usize,
+    // Some이면 시작하자마자 클라이언트마다 MJPEG AVI로 녹화 (나중에 start_recording으로도 시작 가능)
+    pub record: Option<RecordConfig>,
+    // Some이면 받은 프레임 일부를 이미지 파일로 저장 (server_main은 기본 설정으로 켬)
+    pub snapshots: Option<SnapshotConfig>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: EndpointConfig::default(),
+            security: SecurityConfig::default(),
+            liveness: LivenessConfig::default(),
+            sink: None,
+            frame_queue: 8,
+            record: None,
+            snapshots: None,
+        }
+    }
+}
+
+impl fmt::Debug for ServerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServerConfig")
+            .field("endpoint", &self.endpoint)
+            .field("security", &self.security)
+            .field("liveness", &self.liveness)
+            .field("sink", &self.sink.as_ref().map(|_| "FrameSink"))
+            .field("frame_queue", &self.frame_queue)
+            .field("record", &self.record)
+            .field("snapshots", &self.snapshots)
+            .finish()
+    }
+}
+
+// 클라이언트 연결이 끝난 이유
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisconnectReason {
+    // 클라이언트가 연결을 닫음
+    Closed,
+    // liveness timeout 동안 아무것도 보내지 않음
+    TimedOut,
+    Error(String),
+    // 서버를 멈춤
+    Stopped,
+}
+
+// 호출자에게 알리는 클라이언트 상태 변화 (프레임은 frames()로 따로 전달)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerEvent {
+    // client는 "Client_{번호}", 이 클라이언트의 Frame.stream_id가 그 번호
+    Connected { client: String, addr: SocketAddr, encrypted: bool },
+    // 프레임이 아닌 텍스트 줄 (다른 클라이언트에게도 전달됨)
+    Message { client: String, text: String },
+    Disconnected { client: String, reason: DisconnectReason },
+    // 인증이나 암호화 키 교환에 실패해 받지 않은 연결
+    Rejected { addr: SocketAddr, reason: String },
+    // 녹화 파일 하나를 다 씀 (연결 종료, 해상도 변경, stop_recording)
+    RecordingFinished(Recording),
+    // 이 클라이언트는 녹화를 다시 시작할 때까지 녹화하지 않음
+    RecordingFailed { client: String, error: String },
+    SnapshotSaved { client: String, path: PathBuf },
+    // 저장이나 오래된 파일 삭제에 실패 (다음 간격에 다시 시도)
+    SnapshotFailed { client: String, error: String },
+}
+
+// 스레드끼리 나누는 서버 상태
+struct Shared {
+    config: ServerConfig,
+    stopping: AtomicBool,
+    clients: ClientMap,
+    // 정지할 때 읽기 중인 스레드를 깨우려고 원본 소켓을 보관
+    connections: Mutex<HashMap<String, TcpStream>>,
+    latest: Mutex<HashMap<String, Frame>>,
+    recorder: Mutex<Option<Recorder>>,
+    snapshots: Option<Snapshotter>,
+    events: Sender<ServerEvent>,
+    frames: SyncSender<Frame>,
+    dropped_frames: AtomicU64,
+}
+
+impl Shared {
+    fn notify(&self, event: ServerEvent) {
+        // 받는 쪽이 없으면 무시
+        let _ = self.events.send(event);
+    }
+
+    fn deliver(&self, frame: Frame) {
+        match self.frames.try_send(frame) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::SeqCst)
+    }
+
+    // JPEG 압축은 녹화기를 잠그지 않은 채로 해서 다른 클라이언트를 막지 않음
+    fn record(&self, client_name: &str, frame: &Frame) {
+        let jpeg_quality = match self.recorder.lock().unwrap().as_ref() {
+            Some(recorder) if recorder.wants(client_name, frame) => recorder.config().jpeg_quality,
+            _ => return,
+        };
+        let result = EncodedFrame::encode(frame, jpeg_quality).and_then(|encoded| {
+            // 압축하는 사이에 녹화가 멈췄으면 버림
+            match self.recorder.lock().unwrap().as_mut() {
+                Some(recorder) => recorder.record_encoded(client_name, &encoded),
+                None => Ok(None),
+            }
+        });
+        match result {
+            Ok(Some(recording)) => self.notify(ServerEvent::RecordingFinished(recording)),
+            Ok(None) => {}
+            Err(e) => self.notify(ServerEvent::RecordingFailed { client: client_name.to_string(), error: format!("{:#}", e) }),
+        }
+    }
+
+    fn snapshot(&self, client_name: &str, frame: &Frame) {
+        let Some(snapshots) = &self.snapshots else {
+            return;
+        };
+        // 저장 간격 카운터만 잠그고 PNG 인코딩과 파일 쓰기는 잠그지 않은 채로 해서 다른 클라이언트를 막지 않음
+        match snapshots.offer(client_name, frame) {
+            Ok(Some(path)) => self.notify(ServerEvent::SnapshotSaved { client: client_name.to_string(), path }),
+            Ok(None) => {}
+            Err(e) => self.notify(ServerEvent::SnapshotFailed { client: client_name.to_string(), error: format!("{:#}", e) }),
+        }
+    }
+
+    fn finish_recording(&self, client_name: &str) {
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            match recorder.finish(client_name) {
+                Ok(Some(recording)) => self.notify(ServerEvent::RecordingFinished(recording)),
+                Ok(None) => {}
+                Err(e) => self.notify(ServerEvent::RecordingFailed { client: client_name.to_string(), error: format!("{:#}", e) }),
+            }
+        }
+    }
+}
+
+// 백그라운드에서 클라이언트를 받는 TCP 웹캠 서버 (stdout에는 아무것도 출력하지 않음)
+pub struct TcpWebcamServer {
+    local_addr: SocketAddr,
+    shared: Arc<Shared>,
+    events: Receiver<ServerEvent>,
+    frames: Receiver<Frame>,
+    accept: Option<JoinHandle<()>>,
+}
+
+impl TcpWebcamServer {
+    // config.endpoint에 바인딩하고 바로 연결을 받기 시작
+    pub fn start(config: ServerConfig) -> Result<Self> {
+        let listener = config.endpoint.bind_tcp()
+            .with_context(|| format!("서버 바인딩 실패: {}", config.endpoint.listen_addr()))?;
+        Self::start_on(listener, config)
+    }
+
+    // 이미 바인딩한 리스너로 시작 (포트 0으로 바인딩한 뒤 실제 포트를 먼저 확인할 때 사용)
+    pub fn start_on(listener: TcpListener, config: ServerConfig) -> Result<Self> {
+        let local_addr = listener.local_addr().context("바인딩 주소 확인 실패")?;
+        listener.set_nonblocking(true).context("리스너 논블로킹 설정 실패")?;
+
+        let recorder = config.record.clone().map(Recorder::new).transpose()?;
+        let snapshots = config.snapshots.clone().map(Snapshotter::new).transpose()?;
+        let (event_tx, events) = mpsc::channel();
+        let (frame_tx, frames) = mpsc::sync_channel(config.frame_queue.max(1));
+        let shared = Arc::new(Shared {
+            config,
+            stopping: AtomicBool::new(false),
+            clients: Arc::new(Mutex::new(HashMap::new())),
+            connections: Mutex::new(HashMap::new()),
+            latest: Mutex::new(HashMap::new()),
+            recorder: Mutex::new(recorder),
+            snapshots,
+            events: event_tx,
+            frames: frame_tx,
+            dropped_frames: AtomicU64::new(0),
+        });
+
+        let accept_shared = Arc::clone(&shared);
+        let accept = thread::Builder::new()
+            .name("janggab-tcp-accept".to_string())
+            .spawn(move || accept_loop(listener, accept_shared))
+            .context("accept 스레드 시작 실패")?;
+
+        Ok(Self { local_addr, shared, events, frames, accept: Some(accept) })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    // 연결/종료/텍스트 메시지 이벤트
+    pub fn events(&self) -> &Receiver<ServerEvent> {
+        &self.events
+    }
+
+    // 모든 클라이언트의 프레임 (Frame.source로 클라이언트 구분)
+    pub fn frames(&self) -> &Receiver<Frame> {
+        &self.frames
+    }
+
+    // 지금 연결되어 있는 클라이언트 이름
+    pub fn clients(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.shared.clients.lock().unwrap().keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    // 클라이언트가 마지막으로 보낸 프레임 (frames()를 읽지 않아도 갱신됨)
+    pub fn latest_frame(&self, client: &str) -> Option<Frame> {
+        self.shared.latest.lock().unwrap().get(client).cloned()
+    }
+
+    // frames()가 가득 차서 버린 프레임 수
+    pub fn dropped_frames(&self) -> u64 {
+        self.shared.dropped_frames.load(Ordering::Relaxed)
+    }
+
+    // 지금 연결된 클라이언트와 이후 연결되는 클라이언트를 녹화 (이미 녹화 중이면 오류)
+    pub fn start_recording(&self, config: RecordConfig) -> Result<()> {
+        let mut recorder = self.shared.recorder.lock().unwrap();
+        if recorder.is_some() {
+            bail!("이미 녹화 중입니다");
+        }
+        *recorder = Some(Recorder::new(config)?);
+        Ok(())
+    }
+
+    // 녹화를 멈추고 다 쓴 파일 목록을 돌려줌 (녹화 중이 아니면 빈 목록)
+    pub fn stop_recording(&self) -> Result<Vec<Recording>> {
+        match self.shared.recorder.lock().unwrap().take() {
+            Some(mut recorder) => recorder.finish_all(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.shared.recorder.lock().unwrap().is_some()
+    }
+
+    // 모든 연결을 닫고 스레드가 끝날 때까지 기다림 (drop해도 같음, 녹화 파일도 닫힘)
+    // 아직 읽지 않은 이벤트를 돌려줌 (연결마다 Disconnected { reason: Stopped }, 녹화 중이었으면 RecordingFinished)
+    pub fn stop(mut self) -> Vec<ServerEvent> {
+        self.shutdown();
+        self.events.try_iter().collect()
+    }
+
+    fn shutdown(&mut self) {
+        self.shared.stopping.store(true, Ordering::SeqCst);
+        for stream in self.shared.connections.lock().unwrap().values() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        if let Some(accept) = self.accept.take() {
+            let _ = accept.join();
+        }
+        // 클라이언트 스레드가 끝나면서 각자 파일을 닫으므로 보통은 남은 것이 없음
+        let _ = self.stop_recording();
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct ScreenFrame {
-    width: i32,
-    height: i32,
-    pixels: Vec<PixelData>,
-    frame_id: u64,
+impl Drop for TcpWebcamServer {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
 }
 
-type ClientMap = Arc<Mutex<HashMap<String, TcpStream>>>;
-type FrameBuffer = Arc<Mutex<HashMap<String, ScreenFrame>>>;
+impl fmt::Debug for TcpWebcamServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TcpWebcamServer")
+            .field("local_addr", &self.local_addr)
+            .field("clients", &self.clients())
+            .field("dropped_frames", &self.dropped_frames())
+            .finish()
+    }
+}
 
+// 받은 프레임을 기본 설정(captured_frames, 10프레임마다, 클라이언트마다 최근 10개)으로 저장
 pub fn server_main() {
-    let server_addr = "0.0.0.0:52525";
+    server_main_with(ServerConfig {
+        snapshots: Some(SnapshotConfig::default()),
+        ..ServerConfig::default()
+    })
+}
+
+pub fn server_main_with(config: ServerConfig) {
+    let listener = config.endpoint.bind_tcp().expect("서버 바인딩 실패");
+    server_main_on(listener, config);
+}
+
+// 이미 바인딩한 리스너로 서버 실행 (포트 0으로 바인딩한 뒤 실제 포트를 먼저 확인할 때 사용)
+// TcpWebcamServer의 이벤트와 프레임을 콘솔에 출력 (저장은 config.snapshots 설정대로)
+pub fn server_main_on(listener: TcpListener, config: ServerConfig) {
+    let server_addr = listener.local_addr().expect("바인딩 주소 확인 실패");
     
     println!("🔧 네트워크 디버깅 정보:");
     println!("   - 서버 바인딩 주소: {}", server_addr);
     
-    // 저장 디렉토리 생성
-    let save_dir = "captured_frames";
-    if !Path::new(save_dir).exists() {
-        std::fs::create_dir_all(save_dir).expect("디렉토리 생성 실패");
+    if let Some(snapshots) = &config.snapshots {
+        println!("📁 프레임 저장 디렉토리: {}", snapshots.dir.display());
     }
-    println!("📁 프레임 저장 디렉토리: {}", save_dir);
     
     match std::process::Command::new("hostname").arg("-I").output() {
         Ok(output) => {
@@ -56,253 +369,349 @@ pub fn server_main() {
         Err(_) => println!("   - IP 주소 확인 실패"),
     }
     
-    let listener = TcpListener::bind(server_addr).expect("서버 바인딩 실패");
     println!("🚀 실시간 이미지 스트리밍 서버가 {}에서 시작되었습니다", server_addr);
     println!("📡 클라이언트 연결을 기다리는 중...");
-    println!("🖥️ 클라이언트가 연결되면 실시간 영상이 표시됩니다\n");
+    if config.sink.is_some() {
+        println!("🖥️ 클라이언트가 연결되면 실시간 영상이 표시됩니다\n");
+    } else {
+        println!("🖥️ 화면 표시 없이 수신만 합니다\n");
+    }
     
-    let clients: ClientMap = Arc::new(Mutex::new(HashMap::new()));
-    let frame_buffer: FrameBuffer = Arc::new(Mutex::new(HashMap::new()));
+    let server = TcpWebcamServer::start_on(listener, config).expect("서버 시작 실패");
     
-    for (client_id, stream) in listener.incoming().enumerate() {
-        match stream {
-            Ok(stream) => {
-                let client_addr = stream.peer_addr().unwrap();
-                let client_name = format!("Client_{}", client_id);
-                
-                println!("✅ 새 클라이언트 연결: {} ({})", client_name, client_addr);
-                
-                let clients_clone = Arc::clone(&clients);
-                let frame_buffer_clone = Arc::clone(&frame_buffer);
-                let stream_clone = stream.try_clone().unwrap();
-                
-                clients_clone.lock().unwrap().insert(client_name.clone(), stream_clone);
-                
-                thread::spawn(move || {
-                    handle_client(stream, client_name, clients_clone, frame_buffer_clone);
-                });
-            }
-            Err(e) => {
-                eprintln!("❌ 클라이언트 연결 오류: {}", e);
+    loop {
+        while let Ok(event) = server.events().try_recv() {
+            print_event(event);
+        }
+        match server.frames().recv_timeout(ACCEPT_POLL_INTERVAL) {
+            Ok(frame) => {
+                let timestamp = Local::now().format("%H:%M:%S");
+                println!("📸 [{}] {} 실시간 프레임 수신: {}x{} {:?}, {} 바이트, 프레임 ID: {}", 
+                        timestamp, frame.source, frame.width, frame.height, 
+                        frame.pixel_format, frame.data.len(), frame.frame_id);
             }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
         }
     }
 }
 
-fn handle_client(stream: TcpStream, client_name: String, clients: ClientMap, frame_buffer: FrameBuffer) {
-    let mut reader = BufReader::new(stream.try_clone().unwrap());
-    let mut buffer = String::new();
-    
-    loop {
-        buffer.clear();
-        match reader.read_line(&mut buffer) {
-            Ok(0) => {
-                println!("🔌 {} 연결 종료", client_name);
-                clients.lock().unwrap().remove(&client_name);
-                frame_buffer.lock().unwrap().remove(&client_name);
-                
-                // 클라이언트 연결 종료시 해당 창 닫기
-                let window_name = format!("Live Stream - {}", client_name);
-                highgui::destroy_window(&window_name).unwrap_or_default();
-                break;
-            }
-            Ok(_) => {
-                let message = buffer.trim();
-                let timestamp = Local::now().format("%H:%M:%S");
-                
-                // JSON 데이터인지 확인하고 파싱 시도
-                if let Ok(screen_frame) = serde_json::from_str::<ScreenFrame>(message) {
-                    println!("📸 [{}] {} 실시간 프레임 수신: {}x{}, {} 픽셀, 프레임 ID: {}", 
-                            timestamp, client_name, screen_frame.width, screen_frame.height, 
-                            screen_frame.pixels.len(), screen_frame.frame_id);
-                    
-                    // 프레임 버퍼에 저장
-                    frame_buffer.lock().unwrap().insert(client_name.clone(), screen_frame.clone());
-                    
-                    // 실시간으로 이미지 표시
-                    if let Err(e) = display_frame_realtime(&screen_frame, &client_name) {
-                        eprintln!("❌ 실시간 표시 오류: {}", e);
-                    }
-                    
-                    // 이미지 저장 (선택사항 - 매 10프레임마다만 저장하여 성능 최적화)
-                    if screen_frame.frame_id % 10 == 0 {
-                        if let Err(e) = assemble_and_save_image(&screen_frame, &client_name) {
-                            eprintln!("❌ 이미지 저장 오류: {}", e);
-                        }
-                    }
-                    
-                    // 클라이언트에게 확인 메시지 전송
-                    let response = format!("프레임 {} 실시간 표시 완료", screen_frame.frame_id);
-                    broadcast_message(&clients, &format!("[서버] {}: {}", client_name, response));
-                } else {
-                    // 일반 텍스트 메시지 처리
-                    println!("💬 [{}] {}: {}", timestamp, client_name, message);
-                    broadcast_message(&clients, &format!("[{}] {}: {}", timestamp, client_name, message));
+fn print_event(event: ServerEvent) {
+    match event {
+        ServerEvent::Connected { client, addr, encrypted } => {
+            println!("✅ 새 클라이언트 연결: {} ({}){}", client, addr, if encrypted { " 🔒" } else { "" });
+        }
+        ServerEvent::Message { client, text } => {
+            let timestamp = Local::now().format("%H:%M:%S");
+            println!("💬 [{}] {}: {}", timestamp, client, text);
+        }
+        ServerEvent::Disconnected { client, reason } => match reason {
+            DisconnectReason::Closed | DisconnectReason::Stopped => println!("🔌 {} 연결 종료", client),
+            DisconnectReason::TimedOut => eprintln!("⏱️ {}이(가) 응답하지 않아 연결을 끊습니다", client),
+            DisconnectReason::Error(e) => eprintln!("❌ {} 메시지 읽기 오류: {}", client, e),
+        },
+        ServerEvent::Rejected { addr, reason } => eprintln!("🔒 {} 연결 거절: {}", addr, reason),
+        ServerEvent::RecordingFinished(recording) => {
+            println!("🎞️ {} 녹화 파일 저장됨: {} ({} 프레임)", recording.client, recording.path.display(), recording.frames);
+        }
+        ServerEvent::RecordingFailed { client, error } => eprintln!("❌ {} 녹화 오류: {}", client, error),
+        ServerEvent::SnapshotSaved { path, .. } => println!("💾 이미지 저장됨: {}", path.display()),
+        ServerEvent::SnapshotFailed { client, error } => eprintln!("❌ {} 이미지 저장 오류: {}", client, error),
+    }
+}
+
+fn accept_loop(listener: TcpListener, shared: Arc<Shared>) {
+    let mut workers: Vec<JoinHandle<()>> = Vec::new();
+    let mut client_id: u32 = 0;
+
+    while !shared.is_stopping() {
+        match listener.accept() {
+            Ok((stream, addr)) => {
+                let client_name = format!("Client_{}", client_id);
+                let id = client_id;
+                client_id = client_id.wrapping_add(1);
+
+                // 플랫폼에 따라 논블로킹 설정이 이어지므로 되돌림
+                let registered = stream.set_nonblocking(false)
+                    .and_then(|_| stream.try_clone())
+                    .map(|clone| shared.connections.lock().unwrap().insert(client_name.clone(), clone));
+                if let Err(e) = registered {
+                    shared.notify(ServerEvent::Rejected { addr, reason: e.to_string() });
+                    continue;
                 }
+                // stop()이 connections를 훑은 뒤에 등록됐을 수 있음
+                if shared.is_stopping() {
+                    let _ = stream.shutdown(Shutdown::Both);
+                }
+
+                let worker_shared = Arc::clone(&shared);
+                workers.push(thread::spawn(move || serve_client(stream, addr, id, client_name, worker_shared)));
+                workers.retain(|worker| !worker.is_finished());
             }
-            Err(e) => {
-                eprintln!("❌ {} 메시지 읽기 오류: {}", client_name, e);
-                clients.lock().unwrap().remove(&client_name);
-                frame_buffer.lock().unwrap().remove(&client_name);
-                
-                // 연결 오류시 창 닫기
-                let window_name = format!("Live Stream - {}", client_name);
-                highgui::destroy_window(&window_name).unwrap_or_default();
-                break;
-            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL_INTERVAL),
+            // 연결 하나의 accept 실패는 서버를 멈추지 않음
+            Err(_) => thread::sleep(ACCEPT_POLL_INTERVAL),
         }
     }
+
+    for worker in workers {
+        let _ = worker.join();
+    }
 }
 
-fn display_frame_realtime(screen_frame: &ScreenFrame, client_name: &str) -> opencv::Result<()> {
-    let width = screen_frame.width;
-    let height = screen_frame.height;
-    
-    // OpenCV Mat 생성 (BGR 형식)
-    let mut mat = Mat::zeros(height, width, opencv::core::CV_8UC3)?.to_mat()?;
-    
-    // 픽셀 데이터를 Mat에 복사
-    for pixel_data in &screen_frame.pixels {
-        let x = pixel_data.x;
-        let y = pixel_data.y;
-        
-        // 좌표가 이미지 범위 내에 있는지 확인
-        if x >= 0 && x < width && y >= 0 && y < height {
-            let pixel = mat.at_2d_mut::<opencv::core::Vec3b>(y, x)?;
-            // RGB -> BGR 변환하여 저장
-            pixel[0] = pixel_data.b; // B
-            pixel[1] = pixel_data.g; // G
-            pixel[2] = pixel_data.r; // R
+fn serve_client(mut stream: TcpStream, addr: SocketAddr, id: u32, client_name: String, shared: Arc<Shared>) {
+    let security = &shared.config.security;
+    let reject = |reason: String| {
+        shared.connections.lock().unwrap().remove(&client_name);
+        shared.notify(ServerEvent::Rejected { addr, reason });
+    };
+
+    // 인증에 실패한 연결은 아무 응답 없이 닫음
+    if let Some(psk) = &security.psk {
+        match auth::tcp_accept(&mut stream, psk) {
+            Ok(true) => {}
+            Ok(false) => return reject("페어링 키 인증 실패".to_string()),
+            Err(e) => return reject(format!("인증 중 오류: {}", e)),
         }
     }
-    
-    // 창 이름 설정
-    let window_name = format!("Live Stream - {}", client_name);
-    
-    // 창이 처음 생성되는 경우 창 설정
-    if highgui::get_window_property(&window_name, highgui::WND_PROP_VISIBLE)? < 0.0 {
-        highgui::named_window(&window_name, highgui::WINDOW_AUTOSIZE)?;
-        println!("🖥️ 새 실시간 스트림 창 생성: {}", window_name);
+
+    if let Err(e) = stream.set_read_timeout(Some(shared.config.liveness.timeout)) {
+        return reject(format!("읽기 타임아웃 설정 실패: {}", e));
     }
-    
-    // 이미지가 너무 작은 경우 크기 조정
-    let display_mat = if width < 320 || height < 240 {
-        let mut resized_mat = Mat::default();
-        let scale_x = (640.0 / width as f64).max(2.0);
-        let scale_y = (480.0 / height as f64).max(2.0);
-        let scale = scale_x.min(scale_y);
-        
-        let new_size = Size::new(
-            (width as f64 * scale) as i32,
-            (height as f64 * scale) as i32
-        );
-        
-        imgproc::resize(&mat, &mut resized_mat, new_size, 0.0, 0.0, imgproc::INTER_NEAREST)?;
-        resized_mat
+    // 읽지 않는 클라이언트에게 쓰다가 스레드가 계속 멈춰 있지 않도록
+    if let Err(e) = stream.set_write_timeout(Some(shared.config.liveness.timeout)) {
+        return reject(format!("쓰기 타임아웃 설정 실패: {}", e));
+    }
+    // heartbeat와 READY_LINE 같은 짧은 줄이 Nagle 알고리즘에 묶여 늦게 가지 않도록
+    let _ = stream.set_nodelay(true);
+
+    let (reader, writer): (Box<dyn Read + Send>, Box<dyn Write + Send>) = if security.encrypt {
+        match crypto::secure_stream(stream, security.psk.as_ref(), Role::Responder) {
+            Ok((reader, writer)) => (Box::new(reader), Box::new(writer)),
+            Err(e) => return reject(format!("암호화 키 교환 실패: {}", e)),
+        }
     } else {
-        mat
+        match stream.try_clone() {
+            Ok(reader) => (Box::new(reader), Box::new(stream)),
+            Err(e) => return reject(e.to_string()),
+        }
     };
-    
-    // 실시간으로 이미지 표시
-    highgui::imshow(&window_name, &display_mat)?;
-    
-    // 1ms 대기 (실시간 업데이트를 위해)
-    let key = highgui::wait_key(1)?;
-    
-    // ESC 키나 'q' 키가 눌리면 해당 클라이언트 창만 닫기
-    if key == 27 || key == 'q' as i32 {
-        println!("🔲 사용자 요청으로 {} 스트림 창을 닫습니다", client_name);
-        highgui::destroy_window(&window_name)?;
+
+    shared.clients.lock().unwrap().insert(client_name.clone(), Arc::new(Mutex::new(writer)));
+    shared.notify(ServerEvent::Connected { client: client_name.clone(), addr, encrypted: security.encrypt });
+
+    let reason = handle_client(reader, id, &client_name, &shared);
+
+    shared.clients.lock().unwrap().remove(&client_name);
+    shared.latest.lock().unwrap().remove(&client_name);
+    shared.connections.lock().unwrap().remove(&client_name);
+    shared.finish_recording(&client_name);
+    if let Some(snapshots) = &shared.snapshots {
+        snapshots.forget(&client_name);
     }
-    
-    Ok(())
+    shared.notify(ServerEvent::Disconnected { client: client_name, reason });
 }
 
-fn assemble_and_save_image(screen_frame: &ScreenFrame, client_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    let width = screen_frame.width as u32;
-    let height = screen_frame.height as u32;
-    
-    // 이미지 버퍼 생성
-    let mut img: RgbImage = ImageBuffer::new(width, height);
+// 연결이 끝날 때까지 메시지를 처리하고 끝난 이유를 돌려줌
+fn handle_client(stream: Box<dyn Read + Send>, id: u32, client_name: &str, shared: &Shared) -> DisconnectReason {
+    let window_name = format!("Live Stream - {}", client_name);
+    let sink = &shared.config.sink;
+    let mut reader = BufReader::new(stream);
+    let mut buffer = String::new();
+    let mut binary = false;
+    let mut credit = false;
     
-    // 받은 픽셀 데이터로 이미지 채우기
-    for pixel_data in &screen_frame.pixels {
-        let x = pixel_data.x as u32;
-        let y = pixel_data.y as u32;
-        
-        // 좌표가 이미지 범위 내에 있는지 확인
-        if x < width && y < height {
-            let pixel = img.get_pixel_mut(x, y);
-            *pixel = Rgb([pixel_data.r, pixel_data.g, pixel_data.b]);
+    let reason = loop {
+        let incoming = if binary {
+            read_binary(&mut reader, client_name)
+        } else {
+            read_line(&mut reader, &mut buffer, client_name)
+        };
+        match incoming {
+            Ok(None) => break DisconnectReason::Closed,
+            Ok(Some(Incoming::BinaryMode)) => binary = true,
+            Ok(Some(Incoming::CreditMode)) => credit = true,
+            // heartbeat는 보낸 클라이언트에게만 되돌려 줌
+            Ok(Some(Incoming::Heartbeat)) => reply(&shared.clients, client_name, HEARTBEAT_LINE),
+            Ok(Some(Incoming::Frame(mut frame))) => {
+                frame.stream_id = id;
+                
+                // sink가 있을 때만 실시간으로 이미지 표시 (표시 오류로 연결을 끊지는 않음)
+                if let Some(sink) = sink {
+                    let _ = sink.lock().unwrap().show(&window_name, &frame);
+                }
+                shared.record(client_name, &frame);
+                shared.snapshot(client_name, &frame);
+                
+                shared.latest.lock().unwrap().insert(client_name.to_string(), frame.clone());
+                shared.deliver(frame);
+                // credit 모드 클라이언트는 이 줄을 받아야 다음 프레임을 보냄
+                if credit {
+                    reply(&shared.clients, client_name, READY_LINE);
+                }
+            }
+            Ok(Some(Incoming::Text(message))) => {
+                // 일반 텍스트 메시지 처리
+                let timestamp = Local::now().format("%H:%M:%S");
+                broadcast_message(&shared.clients, &format!("[{}] {}: {}", timestamp, client_name, message));
+                shared.notify(ServerEvent::Message { client: client_name.to_string(), text: message });
+            }
+            Err(_) if shared.is_stopping() => break DisconnectReason::Stopped,
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break DisconnectReason::TimedOut,
+            Err(e) => break DisconnectReason::Error(e.to_string()),
         }
-    }
-    
-    // 파일명 생성 (타임스탬프 포함)
-    let timestamp = Local::now().format("%Y%m%d_%H%M%S");
-    let filename = format!("captured_frames/{}_{}_frame_{}.png", 
-                          client_name, timestamp, screen_frame.frame_id);
-    
-    // 이미지 저장
-    img.save(&filename)?;
-    println!("💾 이미지 저장됨: {}", filename);
-    
-    // 최근 10개 프레임만 유지
-    cleanup_old_frames(client_name, 10)?;
+    };
     
-    Ok(())
+    // 클라이언트 연결 종료시 해당 창 닫기
+    close_sink(sink, &window_name);
+    // 정지로 소켓이 닫혀 EOF를 받은 경우도 Stopped
+    if shared.is_stopping() { DisconnectReason::Stopped } else { reason }
 }
 
-fn cleanup_old_frames(client_name: &str, keep_count: usize) -> Result<(), Box<dyn std::error::Error>> {
-    let dir = Path::new("captured_frames");
-    if !dir.exists() {
-        return Ok(());
+// 줄 모드: heartbeat, 바이너리 전환, JSON 프레임(디버그 모드), 그 외는 텍스트
+fn read_line(reader: &mut impl BufRead, buffer: &mut String, client_name: &str) -> std::io::Result<Option<Incoming>> {
+    buffer.clear();
+    if reader.read_line(buffer)? == 0 {
+        return Ok(None);
     }
-    
-    let mut files: Vec<_> = std::fs::read_dir(dir)?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| {
-            entry.file_name()
-                .to_str()
-                .map(|name| name.starts_with(client_name) && name.ends_with(".png"))
-                .unwrap_or(false)
-        })
+    let message = buffer.trim();
+    Ok(Some(match message {
+        HEARTBEAT_LINE => Incoming::Heartbeat,
+        BINARY_LINE => Incoming::BinaryMode,
+        CREDIT_LINE => Incoming::CreditMode,
+        _ => match serde_json::from_str::<ScreenFrame>(message) {
+            Ok(screen_frame) => Incoming::Frame(screen_frame.to_frame(client_name)?),
+            Err(_) => Incoming::Text(message.to_string()),
+        },
+    }))
+}
+
+fn read_binary(reader: &mut impl Read, client_name: &str) -> std::io::Result<Option<Incoming>> {
+    Ok(wire::read_message(reader, client_name)?.map(|message| match message {
+        Message::Frame(frame) => Incoming::Frame(frame),
+        Message::Heartbeat => Incoming::Heartbeat,
+    }))
+}
+
+fn close_sink(sink: &Option<SharedSink>, window_name: &str) {
+    if let Some(sink) = sink {
+        sink.lock().unwrap().close(window_name);
+    }
+}
+
+// 보낸 클라이언트에게만 제어 줄 하나를 돌려줌
+fn reply(clients: &ClientMap, client_name: &str, line: &str) {
+    // 맵 잠금은 writer를 꺼내는 동안만 잡음
+    let writer = clients.lock().unwrap().get(client_name).cloned();
+    if let Some(writer) = writer
+        && write_line(&writer, line).is_err()
+    {
+        remove_writer(clients, client_name, &writer);
+    }
+}
+
+fn broadcast_message(clients: &ClientMap, message: &str) {
+    let writers: Vec<(String, ClientWriter)> = clients.lock().unwrap()
+        .iter()
+        .map(|(client_name, writer)| (client_name.clone(), Arc::clone(writer)))
         .collect();
     
-    // 파일을 수정 시간으로 정렬
-    files.sort_by_key(|entry| {
-        entry.metadata()
-            .and_then(|m| m.modified())
-            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-    });
-    
-    // 오래된 파일들 삭제
-    if files.len() > keep_count {
-        for file_entry in files.iter().take(files.len() - keep_count) {
-            if let Err(e) = std::fs::remove_file(file_entry.path()) {
-                eprintln!("⚠️ 파일 삭제 실패: {}", e);
-            }
+    for (client_name, writer) in writers {
+        if write_line(&writer, message).is_err() {
+            // 연결이 끊어진 클라이언트 제거
+            // 창은 해당 클라이언트의 handle_client가 읽기 오류로 끝날 때 닫음
+            remove_writer(clients, &client_name, &writer);
         }
     }
-    
-    Ok(())
 }
 
-fn broadcast_message(clients: &ClientMap, message: &str) {
+fn write_line(writer: &ClientWriter, line: &str) -> std::io::Result<()> {
+    let mut stream = writer.lock().unwrap();
+    writeln!(stream, "{}", line).and_then(|_| stream.flush())
+}
+
+// 쓰는 사이에 같은 이름으로 다시 등록된 연결은 지우지 않음
+fn remove_writer(clients: &ClientMap, client_name: &str, writer: &ClientWriter) {
     let mut clients_lock = clients.lock().unwrap();
-    let mut disconnected_clients = Vec::new();
-    
-    for (client_name, stream) in clients_lock.iter_mut() {
-        if let Err(_) = writeln!(stream, "{}", message) {
-            disconnected_clients.push(client_name.clone());
+    if clients_lock.get(client_name).is_some_and(|current| Arc::ptr_eq(current, writer)) {
+        clients_lock.remove(client_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_webcam::udp::header::PixelFormat;
+    use super::super::wire::WireFormat;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn start() -> TcpWebcamServer {
+        TcpWebcamServer::start_on(TcpListener::bind("127.0.0.1:0").unwrap(), ServerConfig::default()).unwrap()
+    }
+
+    fn frame(frame_id: u32) -> Frame {
+        Frame {
+            stream_id: 0,
+            source: String::new(),
+            frame_id,
+            width: 4,
+            height: 2,
+            channels: 3,
+            pixel_format: PixelFormat::Bgr8,
+            stride: 12,
+            timestamp_us: 0,
+            data: vec![frame_id as u8; 24],
         }
     }
-    
-    // 연결이 끊어진 클라이언트 제거
-    for client_name in disconnected_clients {
-        clients_lock.remove(&client_name);
-        
-        // 연결 끊어진 클라이언트의 창도 닫기
-        let window_name = format!("Live Stream - {}", client_name);
-        highgui::destroy_window(&window_name).unwrap_or_default();
-    }
-}
\ No newline at end of file
+
+    fn connect(server: &TcpWebcamServer) -> TcpStream {
+        let stream = TcpStream::connect(server.local_addr()).unwrap();
+        stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+        let Ok(ServerEvent::Connected { client, addr, encrypted }) = server.events().recv_timeout(TIMEOUT) else {
+            panic!("Connected 이벤트가 없음");
+        };
+        assert_eq!((client.as_str(), addr, encrypted), ("Client_0", stream.local_addr().unwrap(), false));
+        stream
+    }
+
+    #[test]
+    fn credit_client_frames_and_stop() {
+        let server = start();
+        let mut stream = connect(&server);
+        let mut replies = BufReader::new(stream.try_clone().unwrap());
+
+        writeln!(stream, "{}\n{}", CREDIT_LINE, BINARY_LINE).unwrap();
+        wire::send_frame(&mut stream, &frame(7), WireFormat::Binary).unwrap();
+
+        let received = server.frames().recv_timeout(TIMEOUT).unwrap();
+        assert_eq!((received.stream_id, received.source.as_str(), received.frame_id), (0, "Client_0", 7));
+        assert_eq!(received.data, frame(7).data);
+        // credit 모드라서 프레임마다 READY_LINE을 돌려줌
+        let mut line = String::new();
+        replies.read_line(&mut line).unwrap();
+        assert_eq!(line.trim(), READY_LINE);
+        assert_eq!(server.latest_frame("Client_0").map(|f| f.frame_id), Some(7));
+        assert_eq!(server.clients(), ["Client_0"]);
+
+        let events = server.stop();
+        assert!(events.contains(&ServerEvent::Disconnected { client: "Client_0".to_string(), reason: DisconnectReason::Stopped }), "{:?}", events);
+        // 정지하면서 연결도 닫힘
+        line.clear();
+        assert_eq!(replies.read_line(&mut line).unwrap(), 0);
+    }
+
+    #[test]
+    fn client_close_is_reported() {
+        let server = start();
+        let stream = connect(&server);
+        drop(stream);
+
+        assert_eq!(
+            server.events().recv_timeout(TIMEOUT).unwrap(),
+            ServerEvent::Disconnected { client: "Client_0".to_string(), reason: DisconnectReason::Closed }
+        );
+        assert!(server.clients().is_empty());
+        assert!(server.latest_frame("Client_0").is_none());
+    }
+}
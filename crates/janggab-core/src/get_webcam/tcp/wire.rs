@@ -0,0 +1,342 @@
+use std::io::{self, ErrorKind, Read, Write};
+
+use bincode::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+
+use crate::get_webcam::udp::convert::OutputFormat;
+use crate::get_webcam::udp::frame::Frame;
+use crate::get_webcam::udp::header::PixelFormat;
+
+// 클라이언트 → 서버 스트림 형식
+// 바이너리 모드는 연결 직후 BINARY_LINE 한 줄을 보낸 뒤 메시지를 이어 붙임
+// 메시지: kind(1) | header_len(4) | payload_len(4) | header(bincode) | payload
+// 서버 → 클라이언트는 두 모드 모두 텍스트 줄 (heartbeat, READY_LINE, 채팅 메시지)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WireFormat {
+    // 픽셀 버퍼를 그대로 보냄 (320x240 BGR 프레임이 약 230KB)
+    #[default]
+    Binary,
+    // 픽셀마다 JSON 객체를 만든 한 줄 (디버깅용, 같은 프레임이 수 MB)
+    Json,
+}
+
+pub(crate) const BINARY_LINE: &str = "__janggab_binary__";
+
+const KIND_FRAME: u8 = 1;
+const KIND_HEARTBEAT: u8 = 2;
+const MESSAGE_HEADER_SIZE: usize = 1 + 4 + 4;
+const MAX_FRAME_HEADER_SIZE: usize = 256;
+// 이보다 큰 페이로드는 잘못된 길이로 보고 연결을 끊음
+const MAX_PAYLOAD_SIZE: usize = 256 << 20;
+
+// 바이너리 프레임 메시지의 헤더 (픽셀은 payload에 행 순서로 패딩 없이)
+#[derive(Encode, Decode, Debug, Clone, Copy, PartialEq, Eq)]
+struct FrameMeta {
+    frame_id: u64,
+    width: u32,
+    height: u32,
+    pixel_format: u8,
+    // 클라이언트 시계 기준 캡처 시각 (µs)
+    timestamp_us: u64,
+}
+
+// JSON 모드의 픽셀 하나
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct PixelData {
+    pub x: i32,
+    pub y: i32,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub timestamp: u64,
+}
+
+// JSON 모드의 프레임 한 줄
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct ScreenFrame {
+    pub width: i32,
+    pub height: i32,
+    pub pixels: Vec<PixelData>,
+    pub frame_id: u64,
+}
+
+impl ScreenFrame {
+    // 프레임의 모든 픽셀을 RGB로 나열 (timestamp는 ms)
+    pub fn from_frame(frame: &Frame) -> Self {
+        let rgb = frame.convert(OutputFormat::Rgb8);
+        let timestamp = frame.timestamp_us / 1000;
+        let pixels = rgb.data.chunks_exact(3).enumerate().map(|(i, pixel)| PixelData {
+            x: (i % rgb.width) as i32,
+            y: (i / rgb.width) as i32,
+            r: pixel[0],
+            g: pixel[1],
+            b: pixel[2],
+            timestamp,
+        }).collect();
+
+        Self {
+            width: frame.width as i32,
+            height: frame.height as i32,
+            pixels,
+            frame_id: frame.frame_id as u64,
+        }
+    }
+
+    // 픽셀 목록을 RGB 프레임으로 (범위 밖 좌표는 무시)
+    // 바이너리 모드와 같은 크기 제한을 넘는 해상도는 할당하기 전에 거절
+    pub fn to_frame(&self, client_name: &str) -> io::Result<Frame> {
+        let width = self.width.max(0) as usize;
+        let height = self.height.max(0) as usize;
+        let size = width.checked_mul(height).and_then(|pixels| pixels.checked_mul(3))
+            .filter(|&size| size <= MAX_PAYLOAD_SIZE)
+            .ok_or_else(|| invalid("프레임 해상도가 너무 큼"))?;
+        let mut data = vec![0u8; size];
+
+        for pixel_data in &self.pixels {
+            let (x, y) = (pixel_data.x, pixel_data.y);
+            if x >= 0 && (x as usize) < width && y >= 0 && (y as usize) < height {
+                let offset = (y as usize * width + x as usize) * 3;
+                data[offset..offset + 3].copy_from_slice(&[pixel_data.r, pixel_data.g, pixel_data.b]);
+            }
+        }
+
+        Ok(Frame {
+            stream_id: 0,
+            source: client_name.to_string(),
+            frame_id: self.frame_id as u32,
+            width,
+            height,
+            channels: 3,
+            pixel_format: PixelFormat::Rgb8,
+            stride: width * 3,
+            timestamp_us: self.pixels.first().map_or(0, |p| p.timestamp * 1000),
+            data,
+        })
+    }
+}
+
+// 바이너리 모드 메시지
+#[derive(Debug)]
+pub(crate) enum Message {
+    Frame(Frame),
+    Heartbeat,
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message.to_string())
+}
+
+fn write_message(stream: &mut dyn Write, kind: u8, header: &[u8], payload: &[u8]) -> io::Result<()> {
+    let mut prefix = Vec::with_capacity(MESSAGE_HEADER_SIZE + header.len());
+    prefix.push(kind);
+    prefix.extend_from_slice(&(header.len() as u32).to_be_bytes());
+    prefix.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    prefix.extend_from_slice(header);
+    stream.write_all(&prefix)?;
+    stream.write_all(payload)?;
+    stream.flush()
+}
+
+// pixels는 stride 패딩 없는 연속 버퍼
+pub(crate) fn write_frame(stream: &mut dyn Write, frame_id: u64, width: usize, height: usize, pixel_format: PixelFormat, timestamp_us: u64, pixels: &[u8]) -> io::Result<()> {
+    if pixels.len() != width * height * pixel_format.channels() {
+        return Err(io::Error::new(ErrorKind::InvalidInput, "픽셀 버퍼 길이가 해상도와 맞지 않음"));
+    }
+    let meta = FrameMeta {
+        frame_id,
+        width: width as u32,
+        height: height as u32,
+        pixel_format: pixel_format.to_u8(),
+        timestamp_us,
+    };
+    let header = bincode::encode_to_vec(meta, bincode::config::standard())
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    write_message(stream, KIND_FRAME, &header, pixels)
+}
+
+pub(crate) fn write_heartbeat(stream: &mut dyn Write) -> io::Result<()> {
+    write_message(stream, KIND_HEARTBEAT, &[], &[])
+}
+
+// 프레임 하나를 format에 맞게 직렬화해서 보냄
+pub(crate) fn send_frame(stream: &mut dyn Write, frame: &Frame, format: WireFormat) -> io::Result<()> {
+    match format {
+        WireFormat::Binary => write_frame(stream, frame.frame_id as u64, frame.width, frame.height, frame.pixel_format, frame.timestamp_us, &frame.packed()),
+        WireFormat::Json => {
+            let json_data = serde_json::to_string(&ScreenFrame::from_frame(frame))?;
+            writeln!(stream, "{}", json_data)?;
+            stream.flush()
+        }
+    }
+}
+
+// 메시지 경계에서 연결이 끊기면 Ok(None)
+pub(crate) fn read_message(stream: &mut dyn Read, client_name: &str) -> io::Result<Option<Message>> {
+    let mut prefix = [0u8; MESSAGE_HEADER_SIZE];
+    // 첫 바이트부터 없을 때만 경계에서 끊긴 것 (머리 중간에서 끊기면 오류)
+    loop {
+        match stream.read(&mut prefix[..1]) {
+            Ok(0) => return Ok(None),
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    stream.read_exact(&mut prefix[1..])?;
+    let header_len = u32::from_be_bytes(prefix[1..5].try_into().unwrap()) as usize;
+    let payload_len = u32::from_be_bytes(prefix[5..9].try_into().unwrap()) as usize;
+    if header_len > MAX_FRAME_HEADER_SIZE || payload_len > MAX_PAYLOAD_SIZE {
+        return Err(invalid("메시지 길이가 잘못됨"));
+    }
+    // 헤더는 작으니 먼저 읽고, 페이로드는 길이가 헤더와 맞을 때만 할당
+    let mut header = vec![0u8; header_len];
+    stream.read_exact(&mut header)?;
+
+    match prefix[0] {
+        KIND_FRAME => {
+            let (meta, _): (FrameMeta, usize) = bincode::decode_from_slice(&header, bincode::config::standard())
+                .map_err(|_| invalid("프레임 헤더 해석 실패"))?;
+            let pixel_format = PixelFormat::from_u8(meta.pixel_format)
+                .ok_or_else(|| invalid("알 수 없는 픽셀 포맷"))?;
+            let (width, height) = (meta.width as usize, meta.height as usize);
+            let channels = pixel_format.channels();
+            let expected = width.checked_mul(height).and_then(|pixels| pixels.checked_mul(channels));
+            if expected != Some(payload_len) {
+                return Err(invalid("프레임 길이가 해상도와 맞지 않음"));
+            }
+            let mut payload = vec![0u8; payload_len];
+            stream.read_exact(&mut payload)?;
+            Ok(Some(Message::Frame(Frame {
+                stream_id: 0,
+                source: client_name.to_string(),
+                frame_id: meta.frame_id as u32,
+                width,
+                height,
+                channels,
+                pixel_format,
+                stride: width * channels,
+                timestamp_us: meta.timestamp_us,
+                data: payload,
+            })))
+        }
+        KIND_HEARTBEAT if header_len == 0 && payload_len == 0 => Ok(Some(Message::Heartbeat)),
+        KIND_HEARTBEAT => Err(invalid("heartbeat에 내용이 있음")),
+        _ => Err(invalid("알 수 없는 메시지 종류")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame() -> Frame {
+        Frame {
+            stream_id: 0,
+            source: "Client_1".to_string(),
+            frame_id: 3,
+            width: 4,
+            height: 2,
+            channels: 3,
+            pixel_format: PixelFormat::Bgr8,
+            stride: 12,
+            timestamp_us: 5_000,
+            data: (0..24).collect(),
+        }
+    }
+
+    fn read(bytes: &[u8]) -> io::Result<Option<Message>> {
+        read_message(&mut &bytes[..], "Client_1")
+    }
+
+    // kind | header_len | payload_len 만 있는 메시지 머리
+    fn prefix(kind: u8, header_len: u32, payload_len: u32) -> Vec<u8> {
+        let mut bytes = vec![kind];
+        bytes.extend_from_slice(&header_len.to_be_bytes());
+        bytes.extend_from_slice(&payload_len.to_be_bytes());
+        bytes
+    }
+
+    fn assert_invalid(result: io::Result<Option<Message>>) {
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let mut bytes = Vec::new();
+        send_frame(&mut bytes, &frame(), WireFormat::Binary).unwrap();
+        write_heartbeat(&mut bytes).unwrap();
+
+        let mut stream = &bytes[..];
+        let Some(Message::Frame(received)) = read_message(&mut stream, "Client_1").unwrap() else {
+            panic!("프레임이 아님");
+        };
+        assert_eq!(received.data, frame().data);
+        assert_eq!((received.frame_id, received.width, received.height), (3, 4, 2));
+        assert_eq!((received.pixel_format, received.timestamp_us), (PixelFormat::Bgr8, 5_000));
+        assert!(matches!(read_message(&mut stream, "Client_1"), Ok(Some(Message::Heartbeat))));
+        // 메시지 경계에서 끝나면 정상 종료
+        assert!(matches!(read_message(&mut stream, "Client_1"), Ok(None)));
+    }
+
+    #[test]
+    fn truncated_input() {
+        let mut bytes = Vec::new();
+        send_frame(&mut bytes, &frame(), WireFormat::Binary).unwrap();
+        for len in [1, MESSAGE_HEADER_SIZE + 2, bytes.len() - 1] {
+            assert_eq!(read(&bytes[..len]).unwrap_err().kind(), ErrorKind::UnexpectedEof, "{} 바이트", len);
+        }
+    }
+
+    #[test]
+    fn oversized_lengths() {
+        assert_invalid(read(&prefix(KIND_FRAME, MAX_FRAME_HEADER_SIZE as u32 + 1, 0)));
+        assert_invalid(read(&prefix(KIND_FRAME, 16, MAX_PAYLOAD_SIZE as u32 + 1)));
+        assert_invalid(read(&prefix(KIND_HEARTBEAT, 0, 1)));
+        assert_invalid(read(&prefix(7, 0, 0)));
+    }
+
+    #[test]
+    fn payload_must_match_header() {
+        // 헤더만 보내고 해상도와 다른 길이를 적으면 페이로드를 읽기 전에 거절
+        let meta = FrameMeta { frame_id: 1, width: 4, height: 2, pixel_format: PixelFormat::Bgr8.to_u8(), timestamp_us: 0 };
+        let header = bincode::encode_to_vec(meta, bincode::config::standard()).unwrap();
+        for payload_len in [23, 25, MAX_PAYLOAD_SIZE as u32] {
+            let mut bytes = prefix(KIND_FRAME, header.len() as u32, payload_len);
+            bytes.extend_from_slice(&header);
+            assert_invalid(read(&bytes));
+        }
+
+        // 곱하면 넘치는 해상도
+        let huge = FrameMeta { width: u32::MAX, height: u32::MAX, ..meta };
+        let header = bincode::encode_to_vec(huge, bincode::config::standard()).unwrap();
+        let mut bytes = prefix(KIND_FRAME, header.len() as u32, 24);
+        bytes.extend_from_slice(&header);
+        assert_invalid(read(&bytes));
+
+        let mut bytes = prefix(KIND_FRAME, 3, 0);
+        bytes.extend_from_slice(&[0xff; 3]);
+        assert_invalid(read(&bytes));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut bytes = Vec::new();
+        send_frame(&mut bytes, &frame(), WireFormat::Json).unwrap();
+        let line = std::str::from_utf8(&bytes).unwrap().trim();
+        let received = serde_json::from_str::<ScreenFrame>(line).unwrap().to_frame("Client_1").unwrap();
+        // JSON 모드는 RGB로 보냄
+        assert_eq!(received.pixel_format, PixelFormat::Rgb8);
+        assert_eq!(received.data[..3], [2, 1, 0]);
+        assert_eq!(received.timestamp_us, 5_000);
+    }
+
+    #[test]
+    fn json_oversized_dimensions() {
+        let screen_frame = |width, height| ScreenFrame { width, height, pixels: Vec::new(), frame_id: 1 };
+        // 곱이 넘치지 않더라도 크기 제한을 넘으면 거절
+        assert_eq!(screen_frame(i32::MAX, i32::MAX).to_frame("Client_1").unwrap_err().kind(), ErrorKind::InvalidData);
+        assert_eq!(screen_frame(100_000, 100_000).to_frame("Client_1").unwrap_err().kind(), ErrorKind::InvalidData);
+        // 음수는 0으로 보고 빈 프레임
+        assert_eq!(screen_frame(-5, 2).to_frame("Client_1").unwrap().data.len(), 0);
+    }
+}
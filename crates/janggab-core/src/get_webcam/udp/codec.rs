@@ -0,0 +1,230 @@
+use std::fmt;
+use std::io::Cursor;
+
+use image::{DynamicImage, ExtendedColorType, ImageEncoder, ImageFormat};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::codecs::qoi::QoiEncoder;
+
+use super::header::PixelFormat;
+
+// 프레임 페이로드 압축 방식
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Codec {
+    #[default]
+    Raw,
+    Jpeg,
+    Png,
+    Qoi,
+}
+
+impl Codec {
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            Codec::Raw => 0,
+            Codec::Jpeg => 1,
+            Codec::Png => 2,
+            Codec::Qoi => 3,
+        }
+    }
+
+    pub(crate) fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Codec::Raw),
+            1 => Some(Codec::Jpeg),
+            2 => Some(Codec::Png),
+            3 => Some(Codec::Qoi),
+            _ => None,
+        }
+    }
+
+    fn image_format(self) -> Option<ImageFormat> {
+        match self {
+            Codec::Raw => None,
+            Codec::Jpeg => Some(ImageFormat::Jpeg),
+            Codec::Png => Some(ImageFormat::Png),
+            Codec::Qoi => Some(ImageFormat::Qoi),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CodecError {
+    Unsupported { codec: Codec, pixel_format: PixelFormat },
+    SizeMismatch { expected: (u32, u32), actual: (u32, u32) },
+    Image(image::ImageError),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CodecError::Unsupported { codec, pixel_format } => {
+                write!(f, "{:?} 코덱은 {:?} 픽셀 포맷을 지원하지 않음", codec, pixel_format)
+            }
+            CodecError::SizeMismatch { expected, actual } => {
+                write!(f, "디코딩된 이미지 크기 불일치: 헤더 {}x{}, 실제 {}x{}", expected.0, expected.1, actual.0, actual.1)
+            }
+            CodecError::Image(e) => write!(f, "이미지 코덱 오류: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for CodecError {}
+
+impl From<image::ImageError> for CodecError {
+    fn from(e: image::ImageError) -> Self {
+        CodecError::Image(e)
+    }
+}
+
+// 인코더는 RGB 순서를 기대하므로 BGR 계열은 채널을 바꿔서 넘김
+fn to_encoder_layout(pixels: &[u8], pixel_format: PixelFormat) -> (Vec<u8>, ExtendedColorType) {
+    let mut pixels = pixels.to_vec();
+    let color = match pixel_format {
+        PixelFormat::Bgr8 | PixelFormat::Rgb8 => ExtendedColorType::Rgb8,
+        PixelFormat::Bgra8 | PixelFormat::Rgba8 => ExtendedColorType::Rgba8,
+        PixelFormat::Gray8 => ExtendedColorType::L8,
+    };
+    if matches!(pixel_format, PixelFormat::Bgr8 | PixelFormat::Bgra8) {
+        swap_red_blue(&mut pixels, pixel_format.channels());
+    }
+    (pixels, color)
+}
+
+fn swap_red_blue(pixels: &mut [u8], channels: usize) {
+    for px in pixels.chunks_exact_mut(channels) {
+        px.swap(0, 2);
+    }
+}
+
+// 연속된(stride 패딩 없는) 픽셀을 압축
+pub fn encode(
+    codec: Codec,
+    pixels: &[u8],
+    width: u32,
+    height: u32,
+    pixel_format: PixelFormat,
+    jpeg_quality: u8,
+) -> Result<Vec<u8>, CodecError> {
+    if codec == Codec::Raw {
+        return Ok(pixels.to_vec());
+    }
+
+    let unsupported = CodecError::Unsupported { codec, pixel_format };
+    match (codec, pixel_format) {
+        (Codec::Jpeg, PixelFormat::Bgra8 | PixelFormat::Rgba8) => return Err(unsupported),
+        (Codec::Qoi, PixelFormat::Gray8) => return Err(unsupported),
+        _ => {}
+    }
+
+    let (pixels, color) = to_encoder_layout(pixels, pixel_format);
+    let mut out = Vec::new();
+    match codec {
+        Codec::Jpeg => JpegEncoder::new_with_quality(&mut out, jpeg_quality.clamp(1, 100))
+            .write_image(&pixels, width, height, color)?,
+        Codec::Png => PngEncoder::new(&mut out)
+            .write_image(&pixels, width, height, color)?,
+        Codec::Qoi => QoiEncoder::new(&mut out)
+            .write_image(&pixels, width, height, color)?,
+        Codec::Raw => unreachable!(),
+    }
+    Ok(out)
+}
+
+// 압축된 페이로드를 헤더의 픽셀 포맷으로 복원 (결과는 stride 패딩 없음)
+pub fn decode(
+    codec: Codec,
+    payload: &[u8],
+    width: u32,
+    height: u32,
+    pixel_format: PixelFormat,
+) -> Result<Vec<u8>, CodecError> {
+    let Some(format) = codec.image_format() else {
+        return Ok(payload.to_vec());
+    };
+
+    let image = image::load(Cursor::new(payload), format)?;
+    if (image.width(), image.height()) != (width, height) {
+        return Err(CodecError::SizeMismatch {
+            expected: (width, height),
+            actual: (image.width(), image.height()),
+        });
+    }
+
+    Ok(from_decoded(image, pixel_format))
+}
+
+fn from_decoded(image: DynamicImage, pixel_format: PixelFormat) -> Vec<u8> {
+    let mut pixels = match pixel_format {
+        PixelFormat::Bgr8 | PixelFormat::Rgb8 => image.into_rgb8().into_raw(),
+        PixelFormat::Bgra8 | PixelFormat::Rgba8 => image.into_rgba8().into_raw(),
+        PixelFormat::Gray8 => image.into_luma8().into_raw(),
+    };
+    if matches!(pixel_format, PixelFormat::Bgr8 | PixelFormat::Bgra8) {
+        swap_red_blue(&mut pixels, pixel_format.channels());
+    }
+    pixels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 4x2 그라데이션
+    fn pixels(pixel_format: PixelFormat) -> Vec<u8> {
+        (0..4 * 2 * pixel_format.channels()).map(|i| (i * 9) as u8).collect()
+    }
+
+    #[test]
+    fn lossless_round_trip() {
+        for codec in [Codec::Raw, Codec::Png, Codec::Qoi] {
+            for pixel_format in [PixelFormat::Bgr8, PixelFormat::Rgb8, PixelFormat::Bgra8, PixelFormat::Rgba8] {
+                let pixels = pixels(pixel_format);
+                let encoded = encode(codec, &pixels, 4, 2, pixel_format, 80).unwrap();
+                assert_eq!(decode(codec, &encoded, 4, 2, pixel_format).unwrap(), pixels, "{:?} {:?}", codec, pixel_format);
+            }
+        }
+
+        let gray = pixels(PixelFormat::Gray8);
+        let encoded = encode(Codec::Png, &gray, 4, 2, PixelFormat::Gray8, 80).unwrap();
+        assert_eq!(decode(Codec::Png, &encoded, 4, 2, PixelFormat::Gray8).unwrap(), gray);
+    }
+
+    #[test]
+    fn jpeg_round_trip_is_close() {
+        // 단색 이미지는 JPEG 손실이 거의 없고, 채널 순서(BGR)가 유지돼야 함
+        let pixels: Vec<u8> = [200u8, 100, 30].repeat(8 * 8);
+        let encoded = encode(Codec::Jpeg, &pixels, 8, 8, PixelFormat::Bgr8, 95).unwrap();
+        let decoded = decode(Codec::Jpeg, &encoded, 8, 8, PixelFormat::Bgr8).unwrap();
+        assert_eq!(decoded.len(), pixels.len());
+        assert!(decoded.iter().zip(&pixels).all(|(&a, &b)| a.abs_diff(b) <= 4));
+    }
+
+    #[test]
+    fn unsupported_combinations() {
+        let rgba = pixels(PixelFormat::Rgba8);
+        assert!(matches!(encode(Codec::Jpeg, &rgba, 4, 2, PixelFormat::Rgba8, 80), Err(CodecError::Unsupported { .. })));
+        let gray = pixels(PixelFormat::Gray8);
+        assert!(matches!(encode(Codec::Qoi, &gray, 4, 2, PixelFormat::Gray8, 80), Err(CodecError::Unsupported { .. })));
+    }
+
+    #[test]
+    fn decode_errors() {
+        let encoded = encode(Codec::Png, &pixels(PixelFormat::Bgr8), 4, 2, PixelFormat::Bgr8, 80).unwrap();
+        assert!(matches!(
+            decode(Codec::Png, &encoded, 2, 4, PixelFormat::Bgr8),
+            Err(CodecError::SizeMismatch { expected: (2, 4), actual: (4, 2) })
+        ));
+        assert!(matches!(decode(Codec::Qoi, &encoded, 4, 2, PixelFormat::Bgr8), Err(CodecError::Image(_))));
+    }
+
+    #[test]
+    fn codec_ids() {
+        for codec in [Codec::Raw, Codec::Jpeg, Codec::Png, Codec::Qoi] {
+            assert_eq!(Codec::from_u8(codec.to_u8()), Some(codec));
+        }
+        // 모르는 코덱 번호는 디코딩하지 않음 (헤더 디코딩에서 UnknownCodec)
+        assert_eq!(Codec::from_u8(4), None);
+        assert_eq!(Codec::from_u8(u8::MAX), None);
+    }
+}
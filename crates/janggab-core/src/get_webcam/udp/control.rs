@@ -0,0 +1,177 @@
+use std::fmt;
+
+use super::header::PROTOCOL_VERSION;
+use super::pacing::ReceiverReport;
+
+// 프레임 청크와 구분되는 제어 메시지
+// magic(2) | version(1) | type(1) | body
+pub const CONTROL_MAGIC: [u8; 2] = *b"JC";
+const CONTROL_HEADER_SIZE: usize = 2 + 1 + 1;
+
+const TYPE_NACK: u8 = 1;
+const TYPE_REPORT: u8 = 2;
+const TYPE_HEARTBEAT: u8 = 3;
+const TYPE_KEYFRAME_REQUEST: u8 = 4;
+
+// NACK 하나에 담는 최대 청크 인덱스 수 (MTU 안에 들어가도록)
+pub const MAX_NACK_ENTRIES: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlMessage {
+    // 수신측이 아직 못 받은 청크 인덱스 목록
+    Nack { frame_id: u32, missing: Vec<u32> },
+    // 혼잡 제어용 수신 상태 보고
+    Report(ReceiverReport),
+    // 보낼 데이터가 없어도 살아 있음을 알림 (양방향)
+    Heartbeat,
+    // 수신측이 델타를 적용할 기준을 잃어서 다음 프레임을 키프레임으로 요청
+    KeyframeRequest,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlError {
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownType(u8),
+    Truncated,
+}
+
+impl fmt::Display for ControlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ControlError::BadMagic => write!(f, "janggab 제어 메시지가 아님 (magic 불일치)"),
+            ControlError::UnsupportedVersion(v) => write!(f, "지원하지 않는 프로토콜 버전 {} (지원: {})", v, PROTOCOL_VERSION),
+            ControlError::UnknownType(t) => write!(f, "알 수 없는 제어 메시지 타입 {}", t),
+            ControlError::Truncated => write!(f, "제어 메시지가 잘림"),
+        }
+    }
+}
+
+impl std::error::Error for ControlError {}
+
+pub fn is_control(buf: &[u8]) -> bool {
+    buf.len() >= 2 && buf[0..2] == CONTROL_MAGIC
+}
+
+impl ControlMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(64);
+        out.extend_from_slice(&CONTROL_MAGIC);
+        out.push(PROTOCOL_VERSION);
+        match self {
+            ControlMessage::Nack { frame_id, missing } => {
+                out.push(TYPE_NACK);
+                out.extend_from_slice(&frame_id.to_be_bytes());
+                out.extend_from_slice(&(missing.len() as u16).to_be_bytes());
+                for index in missing {
+                    out.extend_from_slice(&index.to_be_bytes());
+                }
+            }
+            ControlMessage::Report(report) => {
+                out.push(TYPE_REPORT);
+                out.extend_from_slice(&report.received_chunks.to_be_bytes());
+                out.extend_from_slice(&report.lost_chunks.to_be_bytes());
+                out.extend_from_slice(&report.queuing_delay_us.to_be_bytes());
+            }
+            ControlMessage::Heartbeat => out.push(TYPE_HEARTBEAT),
+            ControlMessage::KeyframeRequest => out.push(TYPE_KEYFRAME_REQUEST),
+        }
+        out
+    }
+
+    pub fn decode(buf: &[u8]) -> Result<Self, ControlError> {
+        if buf.len() < CONTROL_HEADER_SIZE {
+            return Err(ControlError::Truncated);
+        }
+        if buf[0..2] != CONTROL_MAGIC {
+            return Err(ControlError::BadMagic);
+        }
+        if buf[2] != PROTOCOL_VERSION {
+            return Err(ControlError::UnsupportedVersion(buf[2]));
+        }
+
+        let body = &buf[CONTROL_HEADER_SIZE..];
+        match buf[3] {
+            TYPE_NACK => {
+                if body.len() < 6 {
+                    return Err(ControlError::Truncated);
+                }
+                let frame_id = u32::from_be_bytes(body[0..4].try_into().unwrap());
+                let count = u16::from_be_bytes(body[4..6].try_into().unwrap()) as usize;
+                let entries = &body[6..];
+                if entries.len() < count * 4 {
+                    return Err(ControlError::Truncated);
+                }
+                let missing = entries[..count * 4]
+                    .chunks_exact(4)
+                    .map(|b| u32::from_be_bytes(b.try_into().unwrap()))
+                    .collect();
+                Ok(ControlMessage::Nack { frame_id, missing })
+            }
+            TYPE_REPORT => {
+                if body.len() < 12 {
+                    return Err(ControlError::Truncated);
+                }
+                Ok(ControlMessage::Report(ReceiverReport {
+                    received_chunks: u32::from_be_bytes(body[0..4].try_into().unwrap()),
+                    lost_chunks: u32::from_be_bytes(body[4..8].try_into().unwrap()),
+                    queuing_delay_us: u32::from_be_bytes(body[8..12].try_into().unwrap()),
+                }))
+            }
+            TYPE_HEARTBEAT => Ok(ControlMessage::Heartbeat),
+            TYPE_KEYFRAME_REQUEST => Ok(ControlMessage::KeyframeRequest),
+            other => Err(ControlError::UnknownType(other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<ControlMessage> {
+        vec![
+            ControlMessage::Nack { frame_id: 0xdead_beef, missing: vec![0, 7, 0x1_0000] },
+            ControlMessage::Nack { frame_id: 1, missing: Vec::new() },
+            ControlMessage::Report(ReceiverReport { received_chunks: 1000, lost_chunks: 3, queuing_delay_us: 4500 }),
+            ControlMessage::Heartbeat,
+            ControlMessage::KeyframeRequest,
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        for message in messages() {
+            let packet = message.encode();
+            assert!(is_control(&packet));
+            assert_eq!(ControlMessage::decode(&packet), Ok(message));
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        for message in messages() {
+            let packet = message.encode();
+            // 헤더든 본문이든 어디서 잘려도 Truncated
+            for len in 0..packet.len() {
+                assert_eq!(ControlMessage::decode(&packet[..len]), Err(ControlError::Truncated), "{:?} {}", message, len);
+            }
+        }
+    }
+
+    #[test]
+    fn decode_errors() {
+        let packet = ControlMessage::Heartbeat.encode();
+        let with = |at: usize, value: u8| {
+            let mut packet = packet.clone();
+            packet[at] = value;
+            ControlMessage::decode(&packet)
+        };
+
+        assert_eq!(with(0, b'X'), Err(ControlError::BadMagic));
+        assert_eq!(with(2, PROTOCOL_VERSION + 1), Err(ControlError::UnsupportedVersion(PROTOCOL_VERSION + 1)));
+        assert_eq!(with(3, 200), Err(ControlError::UnknownType(200)));
+        // 프레임 청크(JG)는 제어 메시지로 보지 않음
+        assert!(!is_control(b"JG"));
+    }
+}
@@ -0,0 +1,269 @@
+use super::frame::Frame;
+use super::header::PixelFormat;
+
+// 수신측이 돌려받을 픽셀 포맷
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Rgb8,
+    Rgba8,
+    Bgr8,
+    Gray8,
+    // Y 평면(width x height) 뒤에 U, V 평면(각각 절반 크기, 홀수면 올림)
+    // BT.601 limited range (Y 16~235)
+    I420,
+}
+
+impl OutputFormat {
+    // I420은 Y 평면 기준
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            OutputFormat::Rgb8 | OutputFormat::Bgr8 => 3,
+            OutputFormat::Rgba8 => 4,
+            OutputFormat::Gray8 | OutputFormat::I420 => 1,
+        }
+    }
+}
+
+// 요청한 포맷으로 변환한 프레임 (행 사이 패딩 없음)
+#[derive(Debug, Clone)]
+pub struct OutputFrame {
+    pub stream_id: u32,
+    pub source: String,
+    pub frame_id: u32,
+    pub width: usize,
+    pub height: usize,
+    pub format: OutputFormat,
+    // 한 행의 바이트 수 (I420은 Y 평면, U/V 평면은 chroma_stride)
+    pub stride: usize,
+    pub timestamp_us: u64,
+    pub data: Vec<u8>,
+}
+
+impl OutputFrame {
+    // I420의 U/V 평면 한 행의 바이트 수 (다른 포맷은 0)
+    pub fn chroma_stride(&self) -> usize {
+        match self.format {
+            OutputFormat::I420 => self.width.div_ceil(2),
+            _ => 0,
+        }
+    }
+
+    // (평면 데이터, stride) 목록: I420은 Y, U, V 순서, 나머지는 하나
+    pub fn planes(&self) -> Vec<(&[u8], usize)> {
+        if self.format != OutputFormat::I420 {
+            return vec![(&self.data[..], self.stride)];
+        }
+        let luma = self.stride * self.height;
+        let chroma = self.chroma_stride() * self.height.div_ceil(2);
+        let (y, rest) = self.data.split_at(luma);
+        let (u, v) = rest.split_at(chroma);
+        vec![(y, self.stride), (u, self.chroma_stride()), (v, self.chroma_stride())]
+    }
+}
+
+// 픽셀 하나의 (R, G, B, A)
+fn rgba_at(pixel: &[u8], format: PixelFormat) -> [u8; 4] {
+    match format {
+        PixelFormat::Bgr8 => [pixel[2], pixel[1], pixel[0], 255],
+        PixelFormat::Rgb8 => [pixel[0], pixel[1], pixel[2], 255],
+        PixelFormat::Bgra8 => [pixel[2], pixel[1], pixel[0], pixel[3]],
+        PixelFormat::Rgba8 => [pixel[0], pixel[1], pixel[2], pixel[3]],
+        PixelFormat::Gray8 => [pixel[0], pixel[0], pixel[0], 255],
+    }
+}
+
+// BT.601 full range 밝기 (Gray8 출력용)
+fn gray(r: u8, g: u8, b: u8) -> u8 {
+    ((77 * r as u32 + 150 * g as u32 + 29 * b as u32 + 128) >> 8) as u8
+}
+
+// BT.601 limited range YUV
+fn luma(r: i32, g: i32, b: i32) -> u8 {
+    (((66 * r + 129 * g + 25 * b + 128) >> 8) + 16) as u8
+}
+
+fn chroma(r: i32, g: i32, b: i32) -> (u8, u8) {
+    let u = ((-38 * r - 74 * g + 112 * b + 128) >> 8) + 128;
+    let v = ((112 * r - 94 * g - 18 * b + 128) >> 8) + 128;
+    (u.clamp(0, 255) as u8, v.clamp(0, 255) as u8)
+}
+
+// 프레임 하나를 format으로 변환 (stride 패딩은 제거됨)
+pub fn convert(frame: &Frame, format: OutputFormat) -> OutputFrame {
+    OutputFrame {
+        stream_id: frame.stream_id,
+        source: frame.source.clone(),
+        frame_id: frame.frame_id,
+        width: frame.width,
+        height: frame.height,
+        format,
+        stride: frame.width * format.bytes_per_pixel(),
+        timestamp_us: frame.timestamp_us,
+        data: convert_pixels(frame, format),
+    }
+}
+
+fn convert_pixels(frame: &Frame, format: OutputFormat) -> Vec<u8> {
+    let input = frame.pixel_format;
+    let write: fn(&mut Vec<u8>, [u8; 4]) = match (format, input) {
+        // 같은 포맷이면 패딩만 제거
+        (OutputFormat::Rgb8, PixelFormat::Rgb8)
+        | (OutputFormat::Rgba8, PixelFormat::Rgba8)
+        | (OutputFormat::Bgr8, PixelFormat::Bgr8)
+        | (OutputFormat::Gray8, PixelFormat::Gray8) => return frame.packed().into_owned(),
+        (OutputFormat::I420, _) => return to_i420(frame),
+        (OutputFormat::Rgb8, _) => |out, [r, g, b, _]| out.extend_from_slice(&[r, g, b]),
+        (OutputFormat::Rgba8, _) => |out, rgba| out.extend_from_slice(&rgba),
+        (OutputFormat::Bgr8, _) => |out, [r, g, b, _]| out.extend_from_slice(&[b, g, r]),
+        (OutputFormat::Gray8, _) => |out, [r, g, b, _]| out.push(gray(r, g, b)),
+    };
+
+    let mut data = Vec::with_capacity(frame.width * frame.height * format.bytes_per_pixel());
+    for y in 0..frame.height {
+        for pixel in frame.row(y).chunks_exact(input.channels()) {
+            write(&mut data, rgba_at(pixel, input));
+        }
+    }
+    data
+}
+
+// U/V는 2x2 블록의 평균 색으로 계산
+fn to_i420(frame: &Frame) -> Vec<u8> {
+    let (width, height) = (frame.width, frame.height);
+    let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+    let channels = frame.pixel_format.channels();
+    let rgb = |x: usize, y: usize| {
+        let [r, g, b, _] = rgba_at(&frame.row(y)[x * channels..], frame.pixel_format);
+        [r as i32, g as i32, b as i32]
+    };
+
+    let mut data = vec![0u8; width * height + 2 * chroma_width * chroma_height];
+    let (y_plane, uv) = data.split_at_mut(width * height);
+    let (u_plane, v_plane) = uv.split_at_mut(chroma_width * chroma_height);
+
+    for y in 0..height {
+        for x in 0..width {
+            let [r, g, b] = rgb(x, y);
+            y_plane[y * width + x] = luma(r, g, b);
+        }
+    }
+    for cy in 0..chroma_height {
+        for cx in 0..chroma_width {
+            let mut sum = [0i32; 3];
+            let mut count = 0;
+            for y in cy * 2..(cy * 2 + 2).min(height) {
+                for x in cx * 2..(cx * 2 + 2).min(width) {
+                    let [r, g, b] = rgb(x, y);
+                    sum = [sum[0] + r, sum[1] + g, sum[2] + b];
+                    count += 1;
+                }
+            }
+            let (u, v) = chroma(sum[0] / count, sum[1] / count, sum[2] / count);
+            u_plane[cy * chroma_width + cx] = u;
+            v_plane[cy * chroma_width + cx] = v;
+        }
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [u8; 3] = [0, 0, 255];
+    const WHITE: [u8; 3] = [255, 255, 255];
+    const BLACK: [u8; 3] = [0, 0, 0];
+
+    // BGR 픽셀로 만든 프레임, 행 끝에 padding 바이트(0xEE)를 붙임
+    fn bgr_frame(width: usize, height: usize, padding: usize, pixel: impl Fn(usize, usize) -> [u8; 3]) -> Frame {
+        let stride = width * 3 + padding;
+        let mut data = Vec::with_capacity(stride * height);
+        for y in 0..height {
+            for x in 0..width {
+                data.extend_from_slice(&pixel(x, y));
+            }
+            data.resize(data.len() + padding, 0xEE);
+        }
+        Frame {
+            stream_id: 0,
+            source: String::new(),
+            frame_id: 0,
+            width,
+            height,
+            channels: 3,
+            pixel_format: PixelFormat::Bgr8,
+            stride,
+            timestamp_us: 0,
+            data,
+        }
+    }
+
+    #[test]
+    fn known_colours() {
+        // 빨강, 흰색
+        let frame = bgr_frame(2, 1, 0, |x, _| if x == 0 { RED } else { WHITE });
+        assert_eq!(convert(&frame, OutputFormat::Rgb8).data, [255, 0, 0, 255, 255, 255]);
+        assert_eq!(convert(&frame, OutputFormat::Bgr8).data, [0, 0, 255, 255, 255, 255]);
+        assert_eq!(convert(&frame, OutputFormat::Rgba8).data, [255, 0, 0, 255, 255, 255, 255, 255]);
+        assert_eq!(convert(&frame, OutputFormat::Gray8).data, [77, 255]);
+
+        // BT.601 limited range: 빨강 (82, 90, 240), 흰색 Y 235, 검정 Y 16
+        let red = convert(&bgr_frame(2, 2, 0, |_, _| RED), OutputFormat::I420);
+        assert_eq!(red.data, [82, 82, 82, 82, 90, 240]);
+        let white = convert(&bgr_frame(2, 2, 0, |_, _| WHITE), OutputFormat::I420);
+        assert_eq!(white.data, [235, 235, 235, 235, 128, 128]);
+        let black = convert(&bgr_frame(2, 2, 0, |_, _| BLACK), OutputFormat::I420);
+        assert_eq!(black.data, [16, 16, 16, 16, 128, 128]);
+    }
+
+    #[test]
+    fn other_input_formats() {
+        let rgba = Frame {
+            channels: 4,
+            pixel_format: PixelFormat::Rgba8,
+            stride: 4,
+            data: vec![10, 20, 30, 40],
+            ..bgr_frame(1, 1, 0, |_, _| BLACK)
+        };
+        assert_eq!(convert(&rgba, OutputFormat::Bgr8).data, [30, 20, 10]);
+        assert_eq!(convert(&rgba, OutputFormat::Rgba8).data, [10, 20, 30, 40]);
+
+        let gray = Frame { channels: 1, pixel_format: PixelFormat::Gray8, stride: 1, data: vec![99], ..rgba };
+        assert_eq!(convert(&gray, OutputFormat::Rgb8).data, [99, 99, 99]);
+        assert_eq!(convert(&gray, OutputFormat::Rgba8).data, [99, 99, 99, 255]);
+    }
+
+    #[test]
+    fn odd_dimension_i420_planes() {
+        // 오른쪽 열만 빨강: 가장자리 chroma 블록은 1열(또는 1픽셀)만 평균
+        let frame = bgr_frame(3, 3, 0, |x, _| if x == 2 { RED } else { BLACK });
+        let output = convert(&frame, OutputFormat::I420);
+        assert_eq!(output.stride, 3);
+        assert_eq!(output.chroma_stride(), 2);
+        assert_eq!(output.data.len(), 9 + 2 * 4);
+
+        let planes = output.planes();
+        assert_eq!(planes.len(), 3);
+        assert_eq!(planes.iter().map(|(plane, stride)| (plane.len(), *stride)).collect::<Vec<_>>(), [(9, 3), (4, 2), (4, 2)]);
+        assert_eq!(planes[0].0, [16, 16, 82, 16, 16, 82, 16, 16, 82]);
+        assert_eq!(planes[1].0, [128, 90, 128, 90]);
+        assert_eq!(planes[2].0, [128, 240, 128, 240]);
+
+        // I420이 아니면 평면 하나
+        let rgb = convert(&frame, OutputFormat::Rgb8);
+        assert_eq!(rgb.planes(), [(&rgb.data[..], 9)]);
+    }
+
+    #[test]
+    fn padded_stride_input() {
+        let padded = bgr_frame(2, 2, 2, |x, y| [x as u8, y as u8, 7]);
+        let packed = bgr_frame(2, 2, 0, |x, y| [x as u8, y as u8, 7]);
+        for format in [OutputFormat::Rgb8, OutputFormat::Rgba8, OutputFormat::Bgr8, OutputFormat::Gray8, OutputFormat::I420] {
+            let output = convert(&padded, format);
+            assert_eq!(output.data, convert(&packed, format).data, "{:?}", format);
+            assert_eq!(output.stride, 2 * format.bytes_per_pixel());
+        }
+        // 같은 포맷이면 패딩만 제거
+        assert_eq!(convert(&padded, OutputFormat::Bgr8).data, [0, 0, 7, 1, 0, 7, 0, 1, 7, 1, 1, 7]);
+    }
+}
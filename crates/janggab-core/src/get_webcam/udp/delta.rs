@@ -0,0 +1,366 @@
+use std::fmt;
+
+use super::header::{FrameHeader, PixelFormat};
+
+// 델타 프레임 페이로드
+// base_frame_id(4) | tile_size(2) | tile_count(4) | (tile_index(4) | 타일 픽셀)*
+// 타일 픽셀은 가장자리 타일이면 잘린 크기 그대로, 행 순서로 이어 붙임
+const DELTA_HEADER_SIZE: usize = 4 + 2 + 4;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DeltaConfig {
+    // 타일 한 변의 픽셀 수
+    pub tile_size: u16,
+    // 수신측 화면과 이 값보다 더 차이 나는 바이트가 하나라도 있는 타일만 전송
+    // 작은 변화(글자 하나)도 놓치지 않으면서 카메라 노이즈는 무시하도록 (화면 공유는 0)
+    pub threshold: u8,
+    // 이 프레임 수마다 전체 프레임(키프레임)을 보냄 (0 = 요청이 있을 때만)
+    pub keyframe_interval: u32,
+}
+
+impl Default for DeltaConfig {
+    fn default() -> Self {
+        Self {
+            tile_size: 32,
+            threshold: 8,
+            keyframe_interval: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeltaError {
+    // 기준이 되는 키프레임을 아직 받지 못함
+    NoReference,
+    // 이전 델타가 빠져서 수신측 화면이 송신측과 다름
+    BaseMismatch { expected: u32, actual: u32 },
+    GeometryChanged,
+    Truncated,
+    BadTile(u32),
+}
+
+impl fmt::Display for DeltaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeltaError::NoReference => write!(f, "키프레임 없이 델타 프레임 수신"),
+            DeltaError::BaseMismatch { expected, actual } => {
+                write!(f, "델타 기준 프레임 불일치 (마지막 프레임 {}, 델타 기준 {})", expected, actual)
+            }
+            DeltaError::GeometryChanged => write!(f, "키프레임과 델타 프레임의 크기/포맷이 다름"),
+            DeltaError::Truncated => write!(f, "델타 프레임이 잘림"),
+            DeltaError::BadTile(index) => write!(f, "범위를 벗어난 타일 {}", index),
+        }
+    }
+}
+
+impl std::error::Error for DeltaError {}
+
+// 송신측/수신측이 같은 방식으로 나누는 타일 격자
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TileGrid {
+    width: usize,
+    height: usize,
+    channels: usize,
+    tile_size: usize,
+}
+
+impl TileGrid {
+    fn columns(&self) -> usize {
+        self.width.div_ceil(self.tile_size)
+    }
+
+    fn count(&self) -> usize {
+        self.columns() * self.height.div_ceil(self.tile_size)
+    }
+
+    // 타일의 (행 시작 바이트 오프셋들, 한 행의 바이트 수)
+    fn rows(&self, index: usize) -> (impl Iterator<Item = usize>, usize) {
+        let row_bytes = self.width * self.channels;
+        let x = (index % self.columns()) * self.tile_size;
+        let y = (index / self.columns()) * self.tile_size;
+        let tile_width = self.tile_size.min(self.width - x);
+        let tile_height = self.tile_size.min(self.height - y);
+        let offsets = (y..y + tile_height).map(move |row| row * row_bytes + x * self.channels);
+        (offsets, tile_width * self.channels)
+    }
+
+    fn tile_bytes(&self, index: usize) -> usize {
+        let (rows, len) = self.rows(index);
+        rows.count() * len
+    }
+}
+
+// 송신측에서 프레임을 어떻게 보낼지
+pub enum DeltaFrame {
+    // 전체 프레임을 평소처럼 코덱으로 압축해서 보냄
+    Key,
+    // 바뀐 타일만 담은 페이로드 (압축 안 함)
+    Delta(Vec<u8>),
+}
+
+// 송신측: 수신측이 갖고 있을 화면을 기억하고 바뀐 타일만 골라냄
+pub struct DeltaEncoder {
+    config: DeltaConfig,
+    // (격자, 픽셀 포맷, 수신측 화면)
+    reference: Option<(TileGrid, PixelFormat, Vec<u8>)>,
+    last_frame_id: u32,
+    since_keyframe: u32,
+    force_keyframe: bool,
+}
+
+impl DeltaEncoder {
+    pub fn new(config: DeltaConfig) -> Self {
+        Self {
+            config,
+            reference: None,
+            last_frame_id: 0,
+            since_keyframe: 0,
+            force_keyframe: true,
+        }
+    }
+
+    // 수신측이 요청했거나 재연결한 경우 다음 프레임을 키프레임으로
+    pub fn request_keyframe(&mut self) {
+        self.force_keyframe = true;
+    }
+
+    // pixels는 stride 패딩 없는 프레임
+    pub fn encode(&mut self, frame_id: u32, pixels: &[u8], width: usize, height: usize, pixel_format: PixelFormat) -> DeltaFrame {
+        let grid = TileGrid {
+            width,
+            height,
+            channels: pixel_format.channels(),
+            tile_size: self.config.tile_size.max(1) as usize,
+        };
+        let interval_due = self.config.keyframe_interval > 0 && self.since_keyframe + 1 >= self.config.keyframe_interval;
+        // 크기가 맞지 않는 프레임은 타일로 나누지 않고 평소대로 보냄
+        let valid = pixels.len() == width * height * grid.channels;
+        let base_frame_id = self.last_frame_id;
+        self.last_frame_id = frame_id;
+
+        let reference = match &mut self.reference {
+            Some((g, f, reference)) if valid && !self.force_keyframe && !interval_due && *g == grid && *f == pixel_format => reference,
+            _ => {
+                self.reference = Some((grid, pixel_format, pixels.to_vec()));
+                self.force_keyframe = false;
+                self.since_keyframe = 0;
+                return DeltaFrame::Key;
+            }
+        };
+        self.since_keyframe += 1;
+
+        let mut payload = Vec::with_capacity(DELTA_HEADER_SIZE);
+        payload.extend_from_slice(&base_frame_id.to_be_bytes());
+        payload.extend_from_slice(&(grid.tile_size as u16).to_be_bytes());
+        payload.extend_from_slice(&0u32.to_be_bytes());
+
+        let threshold = self.config.threshold;
+        let mut changed = 0u32;
+        for index in 0..grid.count() {
+            let (rows, len) = grid.rows(index);
+            let rows: Vec<usize> = rows.collect();
+            let dirty = rows.iter().any(|&offset| {
+                pixels[offset..offset + len].iter()
+                    .zip(&reference[offset..offset + len])
+                    .any(|(a, b)| a.abs_diff(*b) > threshold)
+            });
+            if !dirty {
+                continue;
+            }
+
+            payload.extend_from_slice(&(index as u32).to_be_bytes());
+            for offset in rows {
+                payload.extend_from_slice(&pixels[offset..offset + len]);
+                reference[offset..offset + len].copy_from_slice(&pixels[offset..offset + len]);
+            }
+            changed += 1;
+        }
+        payload[6..10].copy_from_slice(&changed.to_be_bytes());
+        DeltaFrame::Delta(payload)
+    }
+}
+
+// 수신측이 마지막으로 만든 화면
+struct Screen {
+    frame_id: u32,
+    width: usize,
+    height: usize,
+    pixel_format: PixelFormat,
+    pixels: Vec<u8>,
+}
+
+// 수신측: 마지막 화면에 델타 타일을 덮어씀
+#[derive(Default)]
+pub struct DeltaDecoder {
+    last: Option<Screen>,
+}
+
+impl DeltaDecoder {
+    // 키프레임(또는 델타를 쓰지 않는 프레임)을 새 기준으로 기억
+    pub fn keyframe(&mut self, frame_id: u32, pixels: Vec<u8>, width: usize, height: usize, pixel_format: PixelFormat) {
+        self.last = Some(Screen { frame_id, width, height, pixel_format, pixels });
+    }
+
+    // 델타를 적용한 전체 화면 (stride 패딩 없음)
+    pub fn apply(&mut self, header: &FrameHeader, payload: &[u8]) -> Result<Vec<u8>, DeltaError> {
+        let screen = self.last.as_mut().ok_or(DeltaError::NoReference)?;
+        if payload.len() < DELTA_HEADER_SIZE {
+            return Err(DeltaError::Truncated);
+        }
+        let base_frame_id = u32::from_be_bytes(payload[0..4].try_into().unwrap());
+        if base_frame_id != screen.frame_id {
+            return Err(DeltaError::BaseMismatch { expected: screen.frame_id, actual: base_frame_id });
+        }
+        if (screen.width, screen.height) != (header.width as usize, header.height as usize) || screen.pixel_format != header.pixel_format {
+            return Err(DeltaError::GeometryChanged);
+        }
+
+        let grid = TileGrid {
+            width: screen.width,
+            height: screen.height,
+            channels: screen.pixel_format.channels(),
+            tile_size: u16::from_be_bytes(payload[4..6].try_into().unwrap()).max(1) as usize,
+        };
+        let count = u32::from_be_bytes(payload[6..10].try_into().unwrap());
+
+        // 끝까지 확인한 뒤에 적용해서 잘린 델타가 화면을 반쯤 바꾸지 않도록
+        let mut patched = screen.pixels.clone();
+        let mut cursor = DELTA_HEADER_SIZE;
+        for _ in 0..count {
+            let index_bytes = payload.get(cursor..cursor + 4).ok_or(DeltaError::Truncated)?;
+            let index = u32::from_be_bytes(index_bytes.try_into().unwrap());
+            cursor += 4;
+            if index as usize >= grid.count() {
+                return Err(DeltaError::BadTile(index));
+            }
+            let tile = payload.get(cursor..cursor + grid.tile_bytes(index as usize)).ok_or(DeltaError::Truncated)?;
+            cursor += tile.len();
+
+            let (rows, len) = grid.rows(index as usize);
+            for (offset, row) in rows.zip(tile.chunks_exact(len)) {
+                patched[offset..offset + len].copy_from_slice(row);
+            }
+        }
+
+        screen.pixels = patched;
+        screen.frame_id = header.frame_id;
+        Ok(screen.pixels.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_webcam::udp::codec::Codec;
+    use crate::get_webcam::udp::header::FLAG_DELTA;
+
+    // 10x7 BGR 프레임을 4픽셀 타일로 나누면 3x2 격자, 가장자리 타일은 잘림
+    const WIDTH: usize = 10;
+    const HEIGHT: usize = 7;
+    const FORMAT: PixelFormat = PixelFormat::Bgr8;
+
+    fn header(frame_id: u32) -> FrameHeader {
+        FrameHeader {
+            flags: FLAG_DELTA,
+            frame_id,
+            total_chunks: 1,
+            chunk_index: 0,
+            width: WIDTH as u16,
+            height: HEIGHT as u16,
+            channels: 3,
+            pixel_format: FORMAT,
+            codec: Codec::Raw,
+            stride: (WIDTH * 3) as u32,
+            fec_group: 0,
+            payload_len: 1,
+            timestamp_us: 0,
+            session_id: 1,
+        }
+    }
+
+    fn encoder(keyframe_interval: u32) -> DeltaEncoder {
+        DeltaEncoder::new(DeltaConfig { tile_size: 4, threshold: 2, keyframe_interval })
+    }
+
+    fn delta(encoder: &mut DeltaEncoder, frame_id: u32, pixels: &[u8]) -> Vec<u8> {
+        match encoder.encode(frame_id, pixels, WIDTH, HEIGHT, FORMAT) {
+            DeltaFrame::Delta(payload) => payload,
+            DeltaFrame::Key => panic!("프레임 {}이 키프레임", frame_id),
+        }
+    }
+
+    fn set_pixel(pixels: &mut [u8], x: usize, y: usize, value: u8) {
+        let offset = (y * WIDTH + x) * 3;
+        pixels[offset..offset + 3].fill(value);
+    }
+
+    #[test]
+    fn encode_apply_round_trip() {
+        let mut encoder = encoder(0);
+        let mut decoder = DeltaDecoder::default();
+        let mut pixels = vec![10u8; WIDTH * HEIGHT * 3];
+        assert!(matches!(encoder.encode(0, &pixels, WIDTH, HEIGHT, FORMAT), DeltaFrame::Key));
+        decoder.keyframe(0, pixels.clone(), WIDTH, HEIGHT, FORMAT);
+
+        // threshold 이하의 변화는 보내지 않음
+        pixels[0] = 12;
+        let payload = delta(&mut encoder, 1, &pixels);
+        assert_eq!(payload.len(), DELTA_HEADER_SIZE);
+        assert_eq!(decoder.apply(&header(1), &payload).unwrap()[0], 10);
+
+        // 오른쪽 아래 가장자리 타일(2x3)만 바뀜
+        set_pixel(&mut pixels, 9, 6, 200);
+        let payload = delta(&mut encoder, 2, &pixels);
+        assert_eq!(payload.len(), DELTA_HEADER_SIZE + 4 + 2 * 3 * 3);
+        let screen = decoder.apply(&header(2), &payload).unwrap();
+        assert_eq!(screen[(6 * WIDTH + 9) * 3], 200);
+
+        // 인코더가 기억한 화면과 디코더 화면이 같음
+        pixels[0] = 10;
+        assert_eq!(screen, pixels);
+    }
+
+    #[test]
+    fn keyframes() {
+        let mut encoder = encoder(3);
+        let pixels = vec![0u8; WIDTH * HEIGHT * 3];
+        let kinds: Vec<bool> = (0..7)
+            .map(|frame_id| matches!(encoder.encode(frame_id, &pixels, WIDTH, HEIGHT, FORMAT), DeltaFrame::Key))
+            .collect();
+        assert_eq!(kinds, [true, false, false, true, false, false, true]);
+
+        encoder.request_keyframe();
+        assert!(matches!(encoder.encode(7, &pixels, WIDTH, HEIGHT, FORMAT), DeltaFrame::Key));
+        // 해상도가 바뀌면 키프레임
+        assert!(matches!(encoder.encode(8, &pixels[..WIDTH * 3], WIDTH, 1, FORMAT), DeltaFrame::Key));
+    }
+
+    #[test]
+    fn apply_errors() {
+        let mut encoder = encoder(0);
+        let mut decoder = DeltaDecoder::default();
+        let mut pixels = vec![0u8; WIDTH * HEIGHT * 3];
+        encoder.encode(0, &pixels, WIDTH, HEIGHT, FORMAT);
+        set_pixel(&mut pixels, 0, 0, 100);
+        let payload = delta(&mut encoder, 1, &pixels);
+        assert_eq!(decoder.apply(&header(1), &payload), Err(DeltaError::NoReference));
+
+        decoder.keyframe(0, vec![0u8; WIDTH * HEIGHT * 3], WIDTH, HEIGHT, FORMAT);
+        assert_eq!(decoder.apply(&header(1), &payload[..payload.len() - 1]), Err(DeltaError::Truncated));
+        assert_eq!(decoder.apply(&FrameHeader { width: 8, ..header(1) }, &payload), Err(DeltaError::GeometryChanged));
+
+        let mut bad_tile = payload.clone();
+        bad_tile[DELTA_HEADER_SIZE..DELTA_HEADER_SIZE + 4].copy_from_slice(&6u32.to_be_bytes());
+        assert_eq!(decoder.apply(&header(1), &bad_tile), Err(DeltaError::BadTile(6)));
+
+        // 실패한 델타는 화면을 바꾸지 않으므로 원래 델타는 그대로 적용됨
+        assert!(decoder.apply(&header(1), &payload).is_ok());
+
+        // 2번 델타가 빠지면 3번은 기준이 맞지 않음
+        set_pixel(&mut pixels, 5, 0, 50);
+        delta(&mut encoder, 2, &pixels);
+        set_pixel(&mut pixels, 5, 5, 50);
+        let payload = delta(&mut encoder, 3, &pixels);
+        assert_eq!(decoder.apply(&header(3), &payload), Err(DeltaError::BaseMismatch { expected: 1, actual: 2 }));
+    }
+}
@@ -0,0 +1,88 @@
+use std::ops::Range;
+
+// XOR 패리티 FEC
+// 데이터 청크를 group_size개씩 묶고 그룹마다 패리티 청크 1개를 추가로 보냄
+// 그룹 안에서 데이터 청크 1개가 빠지면 패리티와 나머지 청크로 복구 가능
+
+// 오버헤드 비율(예: 0.25 = 데이터 4개당 패리티 1개)을 그룹 크기로 변환, 0이면 FEC 끔
+pub fn group_size_for_overhead(overhead: f32) -> u8 {
+    if overhead.is_nan() || overhead <= 0.0 {
+        return 0;
+    }
+    (1.0 / overhead).ceil().clamp(1.0, u8::MAX as f32) as u8
+}
+
+pub fn group_count(total_chunks: usize, group_size: usize) -> usize {
+    if group_size == 0 {
+        return 0;
+    }
+    total_chunks.div_ceil(group_size)
+}
+
+// 그룹에 속한 데이터 청크 인덱스 범위
+pub fn group_range(group_index: usize, group_size: usize, total_chunks: usize) -> Range<usize> {
+    let start = group_index * group_size;
+    start.min(total_chunks)..(start + group_size).min(total_chunks)
+}
+
+// 길이가 다르면 짧은 쪽을 0으로 채운 것으로 간주
+pub fn xor_into(dst: &mut Vec<u8>, src: &[u8]) {
+    if dst.len() < src.len() {
+        dst.resize(src.len(), 0);
+    }
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d ^= s;
+    }
+}
+
+pub fn parity<'a>(chunks: impl IntoIterator<Item = &'a [u8]>) -> Vec<u8> {
+    let mut parity = Vec::new();
+    for chunk in chunks {
+        xor_into(&mut parity, chunk);
+    }
+    parity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overhead_to_group_size() {
+        assert_eq!(group_size_for_overhead(0.0), 0);
+        assert_eq!(group_size_for_overhead(f32::NAN), 0);
+        assert_eq!(group_size_for_overhead(0.25), 4);
+        assert_eq!(group_size_for_overhead(0.3), 4);
+        assert_eq!(group_size_for_overhead(2.0), 1);
+        assert_eq!(group_size_for_overhead(0.0001), u8::MAX);
+    }
+
+    #[test]
+    fn groups_cover_all_chunks() {
+        assert_eq!(group_count(10, 0), 0);
+        assert_eq!(group_count(10, 4), 3);
+        assert_eq!(group_range(0, 4, 10), 0..4);
+        // 마지막 그룹은 남은 청크만
+        assert_eq!(group_range(2, 4, 10), 8..10);
+    }
+
+    #[test]
+    fn recovers_any_single_lost_chunk() {
+        // 마지막 청크가 짧아도 0으로 채운 것으로 보고 복구
+        let payload: Vec<u8> = (0..3500u32).map(|i| (i * 7 % 256) as u8).collect();
+        let chunks: Vec<&[u8]> = payload.chunks(1000).collect();
+        let parity = parity(chunks.iter().copied());
+        assert_eq!(parity.len(), 1000);
+
+        for lost in 0..chunks.len() {
+            let mut recovered = parity.clone();
+            for (i, chunk) in chunks.iter().enumerate() {
+                if i != lost {
+                    xor_into(&mut recovered, chunk);
+                }
+            }
+            recovered.truncate(chunks[lost].len());
+            assert_eq!(recovered, chunks[lost], "lost {}", lost);
+        }
+    }
+}
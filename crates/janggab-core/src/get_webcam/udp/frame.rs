@@ -0,0 +1,127 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use super::codec::{self, Codec, CodecError};
+use super::convert::{self, OutputFormat, OutputFrame};
+use super::header::{FrameHeader, PixelFormat};
+
+// 헤더 정보로 재구성한 프레임
+#[derive(Debug, Clone)]
+pub struct Frame {
+    // 수신측이 송신측마다 붙이는 번호 (같은 송신측이 재연결해도 유지, 보낼 때는 무시됨)
+    pub stream_id: u32,
+    // 송신측 이름표 (없으면 송신측 주소, 보낼 때는 무시됨)
+    pub source: String,
+    pub frame_id: u32,
+    pub width: usize,
+    pub height: usize,
+    pub channels: usize,
+    pub pixel_format: PixelFormat,
+    pub stride: usize,
+    // 송신측 단조 시계 기준 캡처 시각 (WebcamSender::timestamp_us)
+    pub timestamp_us: u64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    StrideTooSmall { stride: usize, row_bytes: usize },
+    LengthMismatch { expected: usize, actual: usize },
+    SizeMismatch { expected: (usize, usize), actual: (usize, usize) },
+    Codec(CodecError),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::StrideTooSmall { stride, row_bytes } => {
+                write!(f, "stride {} 바이트가 한 줄 크기 {} 바이트보다 작음", stride, row_bytes)
+            }
+            FrameError::LengthMismatch { expected, actual } => {
+                write!(f, "프레임 데이터 길이 불일치: 헤더 기준 {} 바이트, 실제 {} 바이트", expected, actual)
+            }
+            FrameError::SizeMismatch { expected, actual } => {
+                write!(f, "프레임 크기 불일치: 예상 {}x{}, 수신 {}x{}", expected.0, expected.1, actual.0, actual.1)
+            }
+            FrameError::Codec(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+impl Frame {
+    pub fn from_parts(header: &FrameHeader, data: Vec<u8>) -> Result<Self, FrameError> {
+        let width = header.width as usize;
+        let height = header.height as usize;
+        let channels = header.channels as usize;
+        let row_bytes = width * channels;
+        let stride = header.stride as usize;
+        if stride < row_bytes {
+            return Err(FrameError::StrideTooSmall { stride, row_bytes });
+        }
+
+        // 압축된 프레임은 디코딩 후 패딩 없는 연속 데이터가 됨
+        let (data, stride) = match header.codec {
+            Codec::Raw => (data, stride),
+            codec => {
+                let decoded = codec::decode(codec, &data, width as u32, height as u32, header.pixel_format)
+                    .map_err(FrameError::Codec)?;
+                (decoded, row_bytes)
+            }
+        };
+        if data.len() != stride * height {
+            return Err(FrameError::LengthMismatch { expected: stride * height, actual: data.len() });
+        }
+
+        Ok(Self {
+            stream_id: 0,
+            source: String::new(),
+            frame_id: header.frame_id,
+            width,
+            height,
+            channels,
+            pixel_format: header.pixel_format,
+            stride,
+            timestamp_us: header.timestamp_us,
+            data,
+        })
+    }
+
+    pub fn row_bytes(&self) -> usize {
+        self.width * self.channels
+    }
+
+    pub fn row(&self, y: usize) -> &[u8] {
+        let start = y * self.stride;
+        &self.data[start..start + self.row_bytes()]
+    }
+
+    // stride 패딩을 제거한 연속 바이트 (패딩이 없으면 복사하지 않음)
+    pub fn packed(&self) -> Cow<'_, [u8]> {
+        if self.stride == self.row_bytes() {
+            return Cow::Borrowed(&self.data);
+        }
+        let mut packed = Vec::with_capacity(self.row_bytes() * self.height);
+        for y in 0..self.height {
+            packed.extend_from_slice(self.row(y));
+        }
+        Cow::Owned(packed)
+    }
+
+    pub fn into_packed(self) -> Vec<u8> {
+        if self.stride == self.row_bytes() {
+            return self.data;
+        }
+        let mut packed = Vec::with_capacity(self.row_bytes() * self.height);
+        for y in 0..self.height {
+            packed.extend_from_slice(self.row(y));
+        }
+        packed
+    }
+
+    // 요청한 포맷으로 변환 (BGR/RGB 채널 순서를 호출하는 쪽에서 신경 쓰지 않도록)
+    pub fn convert(&self, format: OutputFormat) -> OutputFrame {
+        convert::convert(self, format)
+    }
+}
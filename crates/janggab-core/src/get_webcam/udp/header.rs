@@ -0,0 +1,314 @@
+use std::fmt;
+
+use super::codec::Codec;
+
+// 모든 프레임 청크 앞에 붙는 헤더
+// magic(2) | version(1) | flags(1) | pixel_format(1) | codec(1) | channels(1) | fec_group(1)
+// | frame_id(4) | total_chunks(2) | chunk_index(2) | width(2) | height(2) | stride(4)
+// | payload_len(4) | timestamp_us(8) | session_id(8)
+// FLAG_WIDE이면 뒤에 total_chunks 상위(2) | chunk_index 상위(2)가 더 붙음
+pub const MAGIC: [u8; 2] = *b"JG";
+pub const PROTOCOL_VERSION: u8 = 6;
+pub const HEADER_SIZE: usize = 2 + 1 + 1 + 1 + 1 + 1 + 1 + 4 + 2 + 2 + 2 + 2 + 4 + 4 + 8 + 8;
+pub const WIDE_HEADER_SIZE: usize = HEADER_SIZE + 2 + 2;
+// 한 프레임의 최대 청크 수 (수신측이 이보다 큰 프레임은 조립하지 않음)
+pub const MAX_TOTAL_CHUNKS: u32 = 1 << 20;
+
+// 이 청크가 데이터가 아니라 FEC 패리티 청크임 (chunk_index = 그룹 번호)
+pub const FLAG_PARITY: u8 = 0x01;
+// 델타 모드의 전체 프레임 (수신측이 이후 델타의 기준으로 기억)
+pub const FLAG_KEYFRAME: u8 = 0x02;
+// 바뀐 타일만 담은 델타 프레임 (codec은 항상 Raw, 페이로드 형식은 delta.rs)
+pub const FLAG_DELTA: u8 = 0x04;
+// 청크 수가 u16을 넘는 큰 프레임 (write_to가 알아서 붙이고 decode가 떼어냄)
+pub const FLAG_WIDE: u8 = 0x08;
+
+// frame_id는 u32를 다 쓰면 0으로 돌아가므로 차이의 부호로 순서를 비교
+// (2^31 프레임 이상 떨어진 프레임끼리는 비교할 일이 없음)
+pub fn frame_id_newer(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) > 0
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Bgr8,
+    Rgb8,
+    Bgra8,
+    Rgba8,
+    Gray8,
+}
+
+impl PixelFormat {
+    pub fn channels(self) -> usize {
+        match self {
+            PixelFormat::Bgr8 | PixelFormat::Rgb8 => 3,
+            PixelFormat::Bgra8 | PixelFormat::Rgba8 => 4,
+            PixelFormat::Gray8 => 1,
+        }
+    }
+
+    // OpenCV Mat의 채널 수로 포맷 추정 (OpenCV는 BGR 순서)
+    pub fn from_opencv_channels(channels: usize) -> Option<Self> {
+        match channels {
+            1 => Some(PixelFormat::Gray8),
+            3 => Some(PixelFormat::Bgr8),
+            4 => Some(PixelFormat::Bgra8),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn to_u8(self) -> u8 {
+        match self {
+            PixelFormat::Bgr8 => 0,
+            PixelFormat::Rgb8 => 1,
+            PixelFormat::Bgra8 => 2,
+            PixelFormat::Rgba8 => 3,
+            PixelFormat::Gray8 => 4,
+        }
+    }
+
+    pub(crate) fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(PixelFormat::Bgr8),
+            1 => Some(PixelFormat::Rgb8),
+            2 => Some(PixelFormat::Bgra8),
+            3 => Some(PixelFormat::Rgba8),
+            4 => Some(PixelFormat::Gray8),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameHeader {
+    pub flags: u8,
+    pub frame_id: u32,
+    pub total_chunks: u32,
+    pub chunk_index: u32,
+    pub width: u16,
+    pub height: u16,
+    pub channels: u8,
+    pub pixel_format: PixelFormat,
+    pub codec: Codec,
+    pub stride: u32,
+    // 0이면 FEC 없음, 아니면 데이터 청크 N개마다 패리티 청크 1개
+    pub fec_group: u8,
+    // 압축 후 전체 페이로드 길이 (청크 패딩 제거용)
+    pub payload_len: u32,
+    pub timestamp_us: u64,
+    // handshake에서 받은 세션 id (수신측이 송신측마다 재조립 상태를 나눔)
+    pub session_id: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderError {
+    TooShort(usize),
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownPixelFormat(u8),
+    UnknownCodec(u8),
+    ChannelMismatch { pixel_format: PixelFormat, channels: u8 },
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::TooShort(size) => write!(f, "패킷이 헤더보다 짧음 ({} 바이트, 최소 {} 바이트)", size, HEADER_SIZE),
+            HeaderError::BadMagic => write!(f, "janggab 프레임 패킷이 아님 (magic 불일치)"),
+            HeaderError::UnsupportedVersion(v) => write!(f, "지원하지 않는 프로토콜 버전 {} (지원: {})", v, PROTOCOL_VERSION),
+            HeaderError::UnknownPixelFormat(v) => write!(f, "알 수 없는 픽셀 포맷 {}", v),
+            HeaderError::UnknownCodec(v) => write!(f, "알 수 없는 코덱 {}", v),
+            HeaderError::ChannelMismatch { pixel_format, channels } => {
+                write!(f, "픽셀 포맷 {:?}는 {} 채널이지만 헤더에는 {} 채널", pixel_format, pixel_format.channels(), channels)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HeaderError {}
+
+impl FrameHeader {
+    fn is_wide(&self) -> bool {
+        self.total_chunks > u16::MAX as u32
+    }
+
+    // write_to가 쓰는 헤더 길이
+    pub fn encoded_len(&self) -> usize {
+        if self.is_wide() { WIDE_HEADER_SIZE } else { HEADER_SIZE }
+    }
+
+    pub fn write_to(&self, packet: &mut Vec<u8>) {
+        let wide = self.is_wide();
+        packet.extend_from_slice(&MAGIC);
+        packet.push(PROTOCOL_VERSION);
+        packet.push(if wide { self.flags | FLAG_WIDE } else { self.flags & !FLAG_WIDE });
+        packet.push(self.pixel_format.to_u8());
+        packet.push(self.codec.to_u8());
+        packet.push(self.channels);
+        packet.push(self.fec_group);
+        packet.extend_from_slice(&self.frame_id.to_be_bytes());
+        packet.extend_from_slice(&(self.total_chunks as u16).to_be_bytes());
+        packet.extend_from_slice(&(self.chunk_index as u16).to_be_bytes());
+        packet.extend_from_slice(&self.width.to_be_bytes());
+        packet.extend_from_slice(&self.height.to_be_bytes());
+        packet.extend_from_slice(&self.stride.to_be_bytes());
+        packet.extend_from_slice(&self.payload_len.to_be_bytes());
+        packet.extend_from_slice(&self.timestamp_us.to_be_bytes());
+        packet.extend_from_slice(&self.session_id.to_be_bytes());
+        if wide {
+            packet.extend_from_slice(&((self.total_chunks >> 16) as u16).to_be_bytes());
+            packet.extend_from_slice(&((self.chunk_index >> 16) as u16).to_be_bytes());
+        }
+    }
+
+    pub fn decode(buf: &[u8]) -> Result<Self, HeaderError> {
+        if buf.len() < HEADER_SIZE {
+            return Err(HeaderError::TooShort(buf.len()));
+        }
+        if buf[0..2] != MAGIC {
+            return Err(HeaderError::BadMagic);
+        }
+        if buf[2] != PROTOCOL_VERSION {
+            return Err(HeaderError::UnsupportedVersion(buf[2]));
+        }
+
+        let pixel_format = PixelFormat::from_u8(buf[4])
+            .ok_or(HeaderError::UnknownPixelFormat(buf[4]))?;
+        let codec = Codec::from_u8(buf[5])
+            .ok_or(HeaderError::UnknownCodec(buf[5]))?;
+        let channels = buf[6];
+        if pixel_format.channels() != channels as usize {
+            return Err(HeaderError::ChannelMismatch { pixel_format, channels });
+        }
+
+        let read_u16 = |at: usize| u16::from_be_bytes(buf[at..at + 2].try_into().unwrap()) as u32;
+        let (total_high, index_high) = if buf[3] & FLAG_WIDE != 0 {
+            if buf.len() < WIDE_HEADER_SIZE {
+                return Err(HeaderError::TooShort(buf.len()));
+            }
+            (read_u16(HEADER_SIZE), read_u16(HEADER_SIZE + 2))
+        } else {
+            (0, 0)
+        };
+
+        Ok(Self {
+            flags: buf[3] & !FLAG_WIDE,
+            frame_id: u32::from_be_bytes(buf[8..12].try_into().unwrap()),
+            total_chunks: total_high << 16 | read_u16(12),
+            chunk_index: index_high << 16 | read_u16(14),
+            width: u16::from_be_bytes(buf[16..18].try_into().unwrap()),
+            height: u16::from_be_bytes(buf[18..20].try_into().unwrap()),
+            channels,
+            pixel_format,
+            codec,
+            stride: u32::from_be_bytes(buf[20..24].try_into().unwrap()),
+            fec_group: buf[7],
+            payload_len: u32::from_be_bytes(buf[24..28].try_into().unwrap()),
+            timestamp_us: u64::from_be_bytes(buf[28..36].try_into().unwrap()),
+            session_id: u64::from_be_bytes(buf[36..44].try_into().unwrap()),
+        })
+    }
+
+    pub fn is_parity(&self) -> bool {
+        self.flags & FLAG_PARITY != 0
+    }
+
+    pub fn is_keyframe(&self) -> bool {
+        self.flags & FLAG_KEYFRAME != 0
+    }
+
+    pub fn is_delta(&self) -> bool {
+        self.flags & FLAG_DELTA != 0
+    }
+
+    // 같은 프레임의 청크끼리 이미지 정보가 일치하는지 확인
+    pub fn same_geometry(&self, other: &FrameHeader) -> bool {
+        self.total_chunks == other.total_chunks
+            && self.width == other.width
+            && self.height == other.height
+            && self.channels == other.channels
+            && self.pixel_format == other.pixel_format
+            && self.codec == other.codec
+            && self.stride == other.stride
+            && self.fec_group == other.fec_group
+            && self.payload_len == other.payload_len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(total_chunks: u32, chunk_index: u32) -> FrameHeader {
+        FrameHeader {
+            flags: FLAG_KEYFRAME,
+            frame_id: 7,
+            total_chunks,
+            chunk_index,
+            width: 32,
+            height: 24,
+            channels: 3,
+            pixel_format: PixelFormat::Bgr8,
+            codec: Codec::Png,
+            stride: 96,
+            fec_group: 4,
+            payload_len: 1234,
+            timestamp_us: 99,
+            session_id: 0x0123_4567_89ab_cdef,
+        }
+    }
+
+    fn encode(header: &FrameHeader) -> Vec<u8> {
+        let mut packet = Vec::new();
+        header.write_to(&mut packet);
+        packet
+    }
+
+    #[test]
+    fn round_trip() {
+        let header = header(3, 1);
+        let packet = encode(&header);
+        assert_eq!(packet.len(), HEADER_SIZE);
+        assert_eq!(packet[3] & FLAG_WIDE, 0);
+        assert_eq!(FrameHeader::decode(&packet), Ok(header));
+    }
+
+    #[test]
+    fn wide_round_trip() {
+        // 청크 수가 u16을 넘으면 상위 16비트를 헤더 뒤에 붙임
+        let header = header(0x1_0002, 0x1_0001);
+        let packet = encode(&header);
+        assert_eq!(packet.len(), WIDE_HEADER_SIZE);
+        assert_eq!(header.encoded_len(), WIDE_HEADER_SIZE);
+        assert_ne!(packet[3] & FLAG_WIDE, 0);
+        // FLAG_WIDE는 flags에 남기지 않음
+        assert_eq!(FrameHeader::decode(&packet), Ok(header));
+
+        assert_eq!(FrameHeader::decode(&packet[..HEADER_SIZE]), Err(HeaderError::TooShort(HEADER_SIZE)));
+    }
+
+    #[test]
+    fn decode_errors() {
+        let packet = encode(&header(3, 1));
+        let with = |at: usize, value: u8| {
+            let mut packet = packet.clone();
+            packet[at] = value;
+            FrameHeader::decode(&packet)
+        };
+
+        assert_eq!(FrameHeader::decode(&packet[..HEADER_SIZE - 1]), Err(HeaderError::TooShort(HEADER_SIZE - 1)));
+        assert_eq!(with(0, b'X'), Err(HeaderError::BadMagic));
+        assert_eq!(with(2, PROTOCOL_VERSION + 1), Err(HeaderError::UnsupportedVersion(PROTOCOL_VERSION + 1)));
+        assert_eq!(with(4, 200), Err(HeaderError::UnknownPixelFormat(200)));
+        assert_eq!(with(5, 200), Err(HeaderError::UnknownCodec(200)));
+        assert_eq!(with(6, 4), Err(HeaderError::ChannelMismatch { pixel_format: PixelFormat::Bgr8, channels: 4 }));
+    }
+
+    #[test]
+    fn frame_id_wraps() {
+        assert!(frame_id_newer(1, 0));
+        assert!(frame_id_newer(0, u32::MAX));
+        assert!(!frame_id_newer(u32::MAX, 0));
+        assert!(!frame_id_newer(5, 5));
+    }
+}
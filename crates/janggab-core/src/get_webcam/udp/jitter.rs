@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use super::header::{frame_id_newer, FrameHeader};
+
+// 완성된 프레임을 언제, 어떤 순서로 내보낼지
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JitterPolicy {
+    // 완성되는 즉시 내보내고, 이미 내보낸 프레임보다 오래된 프레임은 버림
+    #[default]
+    LowestLatency,
+    // 캡처 타임스탬프 간격을 유지하도록 delay만큼 늦춰서 frame_id 순서대로 내보냄
+    // delay 안에 늦게 완성된 프레임은 순서에 맞게 끼워 넣고, 그보다 늦으면 버림
+    Smooth { delay: Duration },
+}
+
+struct Pending {
+    header: FrameHeader,
+    payload: Vec<u8>,
+    release_at: Instant,
+}
+
+// 수신측이 송신측마다 두는 지터 버퍼
+pub struct JitterBuffer {
+    policy: JitterPolicy,
+    // frame_id 순서로 정렬
+    pending: VecDeque<Pending>,
+    last_released: Option<u32>,
+    // 수신 시각 - 캡처 타임스탬프의 최소값 (송신/수신 시계 차이)
+    base_offset: Option<i64>,
+    origin: Instant,
+    late: u64,
+}
+
+impl JitterBuffer {
+    pub fn new(policy: JitterPolicy) -> Self {
+        Self {
+            policy,
+            pending: VecDeque::new(),
+            last_released: None,
+            base_offset: None,
+            origin: Instant::now(),
+            late: 0,
+        }
+    }
+
+    // 이미 내보낸 프레임보다 오래되어 버린 프레임 수
+    pub fn late_frames(&self) -> u64 {
+        self.late
+    }
+
+    // 마지막으로 내보낸 frame_id (이보다 오래된 청크는 재조립할 필요 없음)
+    pub fn last_released(&self) -> Option<u32> {
+        self.last_released
+    }
+
+    pub fn push(&mut self, header: FrameHeader, payload: Vec<u8>, now: Instant) {
+        let frame_id = header.frame_id;
+        if self.last_released.is_some_and(|last| !frame_id_newer(frame_id, last)) {
+            self.late += 1;
+            return;
+        }
+
+        let release_at = match self.policy {
+            JitterPolicy::LowestLatency => now,
+            JitterPolicy::Smooth { delay } => {
+                let offset = now.duration_since(self.origin).as_micros() as i64 - header.timestamp_us as i64;
+                let base = self.base_offset.map_or(offset, |b| b.min(offset));
+                self.base_offset = Some(base);
+                // 가장 빨리 도착한 프레임 기준으로 캡처 간격을 그대로 재현
+                let release_us = (header.timestamp_us as i64 + base).max(0) as u64;
+                self.origin + Duration::from_micros(release_us) + delay
+            }
+        };
+
+        let position = self.pending.iter()
+            .position(|p| frame_id_newer(p.header.frame_id, frame_id))
+            .unwrap_or(self.pending.len());
+        if position > 0 && self.pending[position - 1].header.frame_id == frame_id {
+            return;
+        }
+        self.pending.insert(position, Pending { header, payload, release_at });
+    }
+
+    // 내보낼 때가 된 프레임
+    pub fn pop(&mut self, now: Instant) -> Option<(FrameHeader, Vec<u8>)> {
+        let next = match self.policy {
+            // 가장 새 프레임만 내보내고 나머지는 버림
+            JitterPolicy::LowestLatency => {
+                let newest = self.pending.pop_back()?;
+                self.late += self.pending.len() as u64;
+                self.pending.clear();
+                newest
+            }
+            JitterPolicy::Smooth { .. } => {
+                if self.pending.front()?.release_at > now {
+                    return None;
+                }
+                self.pending.pop_front()?
+            }
+        };
+        self.last_released = Some(next.header.frame_id);
+        Some((next.header, next.payload))
+    }
+
+    // 다음 프레임을 내보낼 시각
+    pub fn next_release_at(&self) -> Option<Instant> {
+        match self.policy {
+            JitterPolicy::LowestLatency => self.pending.back().map(|p| p.release_at),
+            JitterPolicy::Smooth { .. } => self.pending.front().map(|p| p.release_at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_webcam::udp::codec::Codec;
+    use crate::get_webcam::udp::header::PixelFormat;
+
+    fn header(frame_id: u32, timestamp_us: u64) -> FrameHeader {
+        FrameHeader {
+            flags: 0,
+            frame_id,
+            total_chunks: 1,
+            chunk_index: 0,
+            width: 1,
+            height: 1,
+            channels: 1,
+            pixel_format: PixelFormat::Gray8,
+            codec: Codec::Raw,
+            stride: 1,
+            fec_group: 0,
+            payload_len: 1,
+            timestamp_us,
+            session_id: 1,
+        }
+    }
+
+    fn drain(buffer: &mut JitterBuffer, now: Instant) -> Vec<u32> {
+        std::iter::from_fn(|| buffer.pop(now)).map(|(header, _)| header.frame_id).collect()
+    }
+
+    #[test]
+    fn smooth_orders_across_wraparound() {
+        let mut buffer = JitterBuffer::new(JitterPolicy::Smooth { delay: Duration::from_millis(50) });
+        let now = Instant::now();
+        // frame_id가 u32::MAX에서 0으로 넘어가는 구간을 뒤섞어서 넣음
+        for (frame_id, timestamp_us) in [(1, 40_000), (u32::MAX - 1, 0), (0, 30_000), (u32::MAX, 10_000)] {
+            buffer.push(header(frame_id, timestamp_us), vec![0], now);
+        }
+        buffer.push(header(0, 30_000), vec![0], now);
+
+        // delay가 지나기 전에는 내보내지 않음
+        assert!(buffer.pop(now).is_none());
+        assert_eq!(drain(&mut buffer, now + Duration::from_secs(1)), [u32::MAX - 1, u32::MAX, 0, 1]);
+        assert_eq!(buffer.last_released(), Some(1));
+
+        // 이미 내보낸 프레임보다 오래된 프레임은 버림
+        buffer.push(header(u32::MAX, 10_000), vec![0], now);
+        assert_eq!(buffer.late_frames(), 1);
+        assert!(buffer.next_release_at().is_none());
+    }
+
+    #[test]
+    fn lowest_latency_keeps_newest() {
+        let mut buffer = JitterBuffer::new(JitterPolicy::LowestLatency);
+        let now = Instant::now();
+        for frame_id in [u32::MAX, 0, 1] {
+            buffer.push(header(frame_id, 0), vec![0], now);
+        }
+        assert_eq!(drain(&mut buffer, now), [1]);
+        assert_eq!(buffer.late_frames(), 2);
+
+        buffer.push(header(0, 0), vec![0], now);
+        assert_eq!(buffer.late_frames(), 3);
+    }
+}
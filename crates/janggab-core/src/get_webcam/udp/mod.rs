@@ -1,2 +1,15 @@
 pub mod client;
+pub mod codec;
+pub mod control;
+pub mod convert;
+pub mod delta;
+pub mod fec;
+pub mod frame;
+pub mod header;
+pub mod jitter;
+pub mod pacing;
+pub mod reassembly;
+pub mod receiver;
+pub mod sender;
 pub mod server;
+pub mod timing;
@@ -0,0 +1,295 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct PacingConfig {
+    // 시작 전송률 (bit/s)
+    pub target_bitrate: u64,
+    pub min_bitrate: u64,
+    pub max_bitrate: u64,
+    // 한 번에 몰아서 보낼 수 있는 최대 바이트
+    pub burst_bytes: usize,
+    // 손실률이 이보다 크면 전송률을 줄임
+    pub loss_threshold: f64,
+    // 큐잉 지연이 이보다 크면 전송률을 줄임
+    pub delay_threshold: Duration,
+}
+
+impl Default for PacingConfig {
+    fn default() -> Self {
+        Self {
+            target_bitrate: 50_000_000,
+            min_bitrate: 1_000_000,
+            max_bitrate: 400_000_000,
+            burst_bytes: 16 * 1400,
+            loss_threshold: 0.02,
+            delay_threshold: Duration::from_millis(30),
+        }
+    }
+}
+
+// 수신측이 주기적으로 보내는 링크 상태 보고
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceiverReport {
+    pub received_chunks: u32,
+    pub lost_chunks: u32,
+    // 최소 지연 대비 늘어난 지연 (큐에 쌓인 정도)
+    pub queuing_delay_us: u32,
+}
+
+impl ReceiverReport {
+    pub fn loss_fraction(&self) -> f64 {
+        let total = self.received_chunks as u64 + self.lost_chunks as u64;
+        if total == 0 {
+            return 0.0;
+        }
+        self.lost_chunks as f64 / total as f64
+    }
+}
+
+// 수신측: 프레임 도착 지연이 최소값보다 얼마나 늘었는지 추적
+// 송신/수신 시계의 차이는 최소값에 흡수되므로 상대값만 의미 있음
+pub struct DelayTracker {
+    base: Option<i64>,
+    window_min: Option<i64>,
+    window_started: Instant,
+    sum: i64,
+    count: u32,
+}
+
+const DELAY_BASE_WINDOW: Duration = Duration::from_secs(10);
+
+impl Default for DelayTracker {
+    fn default() -> Self {
+        Self {
+            base: None,
+            window_min: None,
+            window_started: Instant::now(),
+            sum: 0,
+            count: 0,
+        }
+    }
+}
+
+impl DelayTracker {
+    // one_way_us = 수신 시각 - 송신측 타임스탬프 (시계 기준은 달라도 됨)
+    pub fn on_sample(&mut self, one_way_us: i64) {
+        self.base = Some(self.base.map_or(one_way_us, |b| b.min(one_way_us)));
+        self.window_min = Some(self.window_min.map_or(one_way_us, |m| m.min(one_way_us)));
+
+        // 시계가 조금씩 어긋나도 기준이 낡지 않도록 주기적으로 갱신
+        if self.window_started.elapsed() >= DELAY_BASE_WINDOW {
+            self.base = self.window_min;
+            self.window_min = None;
+            self.window_started = Instant::now();
+        }
+
+        self.sum += one_way_us - self.base.unwrap_or(one_way_us);
+        self.count += 1;
+    }
+
+    // 마지막 호출 이후 평균 큐잉 지연
+    pub fn take_average_us(&mut self) -> u32 {
+        let average = if self.count == 0 { 0 } else { self.sum / self.count as i64 };
+        self.sum = 0;
+        self.count = 0;
+        average.clamp(0, u32::MAX as i64) as u32
+    }
+}
+
+pub struct TokenBucket {
+    rate_bytes_per_sec: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(bitrate: u64, capacity: usize) -> Self {
+        Self {
+            rate_bytes_per_sec: bitrate as f64 / 8.0,
+            capacity: capacity as f64,
+            tokens: capacity as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn set_bitrate(&mut self, bitrate: u64) {
+        self.refill(Instant::now());
+        self.rate_bytes_per_sec = bitrate as f64 / 8.0;
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate_bytes_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    // bytes 만큼 보내려면 얼마나 기다려야 하는지
+    pub fn wait_time(&mut self, bytes: usize, now: Instant) -> Duration {
+        self.refill(now);
+        let deficit = bytes as f64 - self.tokens;
+        if deficit <= 0.0 || self.rate_bytes_per_sec <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(deficit / self.rate_bytes_per_sec)
+    }
+
+    // 토큰이 모자라면 음수로 내려가고 다음 전송이 그만큼 늦춰짐
+    pub fn consume(&mut self, bytes: usize) {
+        self.tokens -= bytes as f64;
+    }
+}
+
+// 손실은 AIMD, 지연 증가는 완만한 감소로 대응
+pub struct CongestionController {
+    config: PacingConfig,
+    bitrate: u64,
+}
+
+impl CongestionController {
+    pub fn new(config: PacingConfig) -> Self {
+        Self {
+            config,
+            bitrate: config.target_bitrate.clamp(config.min_bitrate, config.max_bitrate),
+        }
+    }
+
+    pub fn bitrate(&self) -> u64 {
+        self.bitrate
+    }
+
+    pub fn on_report(&mut self, report: &ReceiverReport) -> u64 {
+        let loss = report.loss_fraction();
+        let queuing_delay = Duration::from_micros(report.queuing_delay_us as u64);
+
+        let next = if loss > self.config.loss_threshold {
+            self.bitrate as f64 * (1.0 - loss / 2.0).max(0.5)
+        } else if queuing_delay > self.config.delay_threshold {
+            self.bitrate as f64 * 0.9
+        } else if report.received_chunks > 0 {
+            // 최대치의 1% 또는 현재의 5% 중 큰 만큼 증가
+            self.bitrate as f64 + (self.config.max_bitrate as f64 * 0.01).max(self.bitrate as f64 * 0.05)
+        } else {
+            self.bitrate as f64
+        };
+
+        self.bitrate = (next as u64).clamp(self.config.min_bitrate, self.config.max_bitrate);
+        self.bitrate
+    }
+}
+
+pub struct Pacer {
+    bucket: TokenBucket,
+    controller: CongestionController,
+}
+
+impl Pacer {
+    pub fn new(config: PacingConfig) -> Self {
+        let controller = CongestionController::new(config);
+        Self {
+            bucket: TokenBucket::new(controller.bitrate(), config.burst_bytes),
+            controller,
+        }
+    }
+
+    pub fn bitrate(&self) -> u64 {
+        self.controller.bitrate()
+    }
+
+    pub fn on_report(&mut self, report: &ReceiverReport) {
+        let bitrate = self.controller.on_report(report);
+        self.bucket.set_bitrate(bitrate);
+    }
+
+    // 전송률을 넘지 않도록 필요한 만큼 대기한 뒤 토큰 차감
+    pub fn pace(&mut self, bytes: usize) {
+        let wait = self.bucket.wait_time(bytes, Instant::now());
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+        self.bucket.consume(bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PacingConfig {
+        PacingConfig {
+            target_bitrate: 5_000_000,
+            min_bitrate: 1_000_000,
+            max_bitrate: 10_000_000,
+            ..Default::default()
+        }
+    }
+
+    fn report(received_chunks: u32, lost_chunks: u32, queuing_delay_us: u32) -> ReceiverReport {
+        ReceiverReport { received_chunks, lost_chunks, queuing_delay_us }
+    }
+
+    fn assert_close(actual: Duration, expected: Duration) {
+        assert!(actual.abs_diff(expected) < Duration::from_micros(1), "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        // 8000 bit/s = 1000 B/s, 최대 100바이트
+        let mut bucket = TokenBucket::new(8000, 100);
+        let start = Instant::now();
+        bucket.last_refill = start;
+
+        assert_eq!(bucket.wait_time(100, start), Duration::ZERO);
+        bucket.consume(100);
+        assert_close(bucket.wait_time(50, start), Duration::from_millis(50));
+        assert_close(bucket.wait_time(50, start + Duration::from_millis(50)), Duration::ZERO);
+
+        // 오래 쉬어도 capacity 이상 쌓이지 않음
+        assert_close(bucket.wait_time(150, start + Duration::from_secs(10)), Duration::from_millis(50));
+
+        // 모자란 채로 보내면 음수만큼 다음 전송이 늦어짐
+        bucket.consume(150);
+        assert_close(bucket.wait_time(50, start + Duration::from_secs(10)), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn additive_increase() {
+        let mut controller = CongestionController::new(config());
+        // 최대치의 1%(100k)보다 현재의 5%(250k)가 큼
+        assert_eq!(controller.on_report(&report(100, 0, 0)), 5_250_000);
+        // 받은 청크가 없으면 그대로
+        assert_eq!(controller.on_report(&report(0, 0, 0)), 5_250_000);
+    }
+
+    #[test]
+    fn multiplicative_decrease() {
+        let mut controller = CongestionController::new(config());
+        // 손실률 10% → 5% 감소
+        assert_eq!(controller.on_report(&report(90, 10, 0)), 4_750_000);
+        // 손실이 아무리 커도 한 번에 절반까지만 감소
+        assert_eq!(controller.on_report(&report(0, 100, 0)), 2_375_000);
+        // 손실 임계값 이하의 손실은 증가로 처리
+        let mut controller = CongestionController::new(config());
+        assert_eq!(controller.on_report(&report(99, 1, 0)), 5_250_000);
+    }
+
+    #[test]
+    fn queuing_delay_decrease() {
+        let mut controller = CongestionController::new(config());
+        assert_eq!(controller.on_report(&report(100, 0, 40_000)), 4_500_000);
+        assert_eq!(controller.on_report(&report(100, 0, 30_000)), 4_725_000);
+    }
+
+    #[test]
+    fn clamps_to_floor_and_ceiling() {
+        let mut controller = CongestionController::new(PacingConfig { target_bitrate: 50_000_000, ..config() });
+        assert_eq!(controller.bitrate(), 10_000_000);
+        assert_eq!(controller.on_report(&report(100, 0, 0)), 10_000_000);
+
+        for _ in 0..10 {
+            controller.on_report(&report(0, 100, 0));
+        }
+        assert_eq!(controller.bitrate(), 1_000_000);
+    }
+}
@@ -0,0 +1,419 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use super::fec;
+use super::header::{frame_id_newer, FrameHeader, FLAG_PARITY, MAX_TOTAL_CHUNKS};
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReassemblyConfig {
+    // 첫 청크 수신 후 이 시간이 지나도 완성되지 않으면 버림
+    pub max_age: Duration,
+    // 동시에 조립 중인 프레임 최대 개수 (넘치면 가장 오래된 것부터 버림)
+    pub max_in_flight: usize,
+}
+
+impl Default for ReassemblyConfig {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_millis(500),
+            max_in_flight: 8,
+        }
+    }
+}
+
+// NACK 기반 재전송 요청 설정
+#[derive(Debug, Clone, Copy)]
+pub struct NackConfig {
+    // 마지막 청크 이후 이 시간 동안 새 청크가 없으면 빠진 청크를 요청
+    pub delay: Duration,
+    // 같은 프레임에 대해 NACK을 다시 보내는 간격
+    pub interval: Duration,
+    // 첫 청크 이후 이 시간이 지나면 더 이상 요청하지 않음 (max_age보다 짧게)
+    pub deadline: Duration,
+}
+
+impl Default for NackConfig {
+    fn default() -> Self {
+        Self {
+            delay: Duration::from_millis(10),
+            interval: Duration::from_millis(40),
+            deadline: Duration::from_millis(300),
+        }
+    }
+}
+
+// 링크 품질 확인용 누적 카운터
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReassemblyStats {
+    pub chunks_received: u64,
+    pub frames_completed: u64,
+    pub frames_dropped: u64,
+    pub chunks_lost: u64,
+    pub chunks_recovered: u64,
+    pub nacks_sent: u64,
+    // 완성됐지만 이미 더 새 프레임을 내보내서 버린 프레임 (지터 버퍼)
+    pub frames_late: u64,
+}
+
+// 여러 스트림의 통계를 합칠 때 사용
+impl std::ops::Add for ReassemblyStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            chunks_received: self.chunks_received + other.chunks_received,
+            frames_completed: self.frames_completed + other.frames_completed,
+            frames_dropped: self.frames_dropped + other.frames_dropped,
+            chunks_lost: self.chunks_lost + other.chunks_lost,
+            chunks_recovered: self.chunks_recovered + other.chunks_recovered,
+            nacks_sent: self.nacks_sent + other.nacks_sent,
+            frames_late: self.frames_late + other.frames_late,
+        }
+    }
+}
+
+struct PartialFrame {
+    header: FrameHeader,
+    chunks: Vec<Option<Vec<u8>>>,
+    parity: Vec<Option<Vec<u8>>>,
+    received: u32,
+    first_seen: Instant,
+    last_chunk_at: Instant,
+    last_nack_at: Option<Instant>,
+}
+
+impl PartialFrame {
+    fn group_size(&self) -> usize {
+        self.header.fec_group as usize
+    }
+
+    // 그룹에서 빠진 데이터 청크가 정확히 1개이고 패리티가 있으면 복구
+    fn try_recover(&mut self, group_index: usize) -> bool {
+        let Some(Some(parity)) = self.parity.get(group_index) else {
+            return false;
+        };
+        let range = fec::group_range(group_index, self.group_size(), self.chunks.len());
+        let mut missing = range.clone().filter(|&i| self.chunks[i].is_none());
+        let (Some(missing_index), None) = (missing.next(), missing.next()) else {
+            return false;
+        };
+
+        // 마지막 청크의 0 패딩은 조립할 때 payload_len으로 잘라냄
+        let mut recovered = parity.clone();
+        for i in range {
+            if let Some(chunk) = &self.chunks[i] {
+                fec::xor_into(&mut recovered, chunk);
+            }
+        }
+        self.chunks[missing_index] = Some(recovered);
+        self.received += 1;
+        true
+    }
+}
+
+pub struct Reassembler {
+    config: ReassemblyConfig,
+    frames: HashMap<u32, PartialFrame>,
+    // 이 frame_id까지는 이미 내보냈으므로 더 받지 않음
+    floor: Option<u32>,
+    // 늦게 온 중복 청크로 같은 프레임을 다시 만들지 않도록 기억
+    recently_completed: VecDeque<u32>,
+    // 헤더의 total_chunks가 이보다 크면 할당하지 않고 버림
+    max_total_chunks: u32,
+    stats: ReassemblyStats,
+}
+
+const RECENTLY_COMPLETED: usize = 64;
+
+impl Reassembler {
+    pub fn new(config: ReassemblyConfig) -> Self {
+        Self {
+            config,
+            frames: HashMap::new(),
+            floor: None,
+            recently_completed: VecDeque::new(),
+            max_total_chunks: MAX_TOTAL_CHUNKS,
+            stats: ReassemblyStats::default(),
+        }
+    }
+
+    // 합의된 해상도/MTU로 나올 수 있는 최대 청크 수로 제한 (MAX_TOTAL_CHUNKS보다 크게는 못 늘림)
+    pub fn with_max_total_chunks(mut self, max_total_chunks: u32) -> Self {
+        self.max_total_chunks = max_total_chunks.min(MAX_TOTAL_CHUNKS);
+        self
+    }
+
+    pub fn stats(&self) -> ReassemblyStats {
+        self.stats
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.frames.len()
+    }
+
+    // 청크 하나를 넣고, 프레임이 완성되면 헤더와 전체 페이로드를 반환
+    pub fn push(&mut self, header: FrameHeader, chunk: &[u8]) -> Option<(FrameHeader, Vec<u8>)> {
+        let frame_id = header.frame_id;
+        let total_chunks = header.total_chunks;
+        let chunk_index = header.chunk_index as usize;
+        let group_count = fec::group_count(total_chunks as usize, header.fec_group as usize);
+
+        let index_limit = if header.is_parity() { group_count } else { total_chunks as usize };
+        if chunk_index >= index_limit {
+            return None;
+        }
+        // 청크마다 1바이트 이상 담기므로 페이로드보다 청크가 많으면 잘못된 헤더
+        if total_chunks > self.max_total_chunks || total_chunks > header.payload_len {
+            return None;
+        }
+
+        // 이미 내보낸 프레임보다 오래된 청크나 완성된 프레임의 중복 청크는 무시
+        if self.floor.is_some_and(|floor| !frame_id_newer(frame_id, floor))
+            || self.recently_completed.contains(&frame_id)
+        {
+            return None;
+        }
+
+        if !self.frames.contains_key(&frame_id) {
+            while self.frames.len() >= self.config.max_in_flight.max(1) {
+                let oldest = *self.frames.keys().min_by_key(|&&id| id.wrapping_sub(frame_id) as i32).unwrap();
+                // 조립 중인 어떤 프레임보다도 오래된 청크면 새 프레임을 버리지 않고 이 청크를 버림
+                if !frame_id_newer(frame_id, oldest) {
+                    return None;
+                }
+                self.drop_frame(oldest);
+            }
+            self.frames.insert(frame_id, PartialFrame {
+                header,
+                chunks: vec![None; total_chunks as usize],
+                parity: vec![None; group_count],
+                received: 0,
+                first_seen: Instant::now(),
+                last_chunk_at: Instant::now(),
+                last_nack_at: None,
+            });
+        }
+
+        let partial = self.frames.get_mut(&frame_id).unwrap();
+        if !partial.header.same_geometry(&header) {
+            return None;
+        }
+
+        partial.last_chunk_at = Instant::now();
+        if header.is_parity() {
+            if partial.parity[chunk_index].is_none() {
+                partial.parity[chunk_index] = Some(chunk.to_vec());
+            }
+        } else if partial.chunks[chunk_index].is_none() {
+            partial.chunks[chunk_index] = Some(chunk.to_vec());
+            partial.received += 1;
+            self.stats.chunks_received += 1;
+        }
+
+        if partial.received < total_chunks && partial.group_size() > 0 {
+            let group_index = if header.is_parity() { chunk_index } else { chunk_index / partial.group_size() };
+            if partial.try_recover(group_index) {
+                self.stats.chunks_recovered += 1;
+            }
+        }
+
+        if partial.received < total_chunks {
+            return None;
+        }
+
+        let partial = self.frames.remove(&frame_id).unwrap();
+        self.stats.frames_completed += 1;
+        self.recently_completed.push_back(frame_id);
+        if self.recently_completed.len() > RECENTLY_COMPLETED {
+            self.recently_completed.pop_front();
+        }
+
+        let mut payload: Vec<u8> = partial.chunks.into_iter().flatten().flatten().collect();
+        payload.truncate(partial.header.payload_len as usize);
+        // 처음 받은 청크가 패리티여도 프레임 플래그(키프레임/델타)는 그대로 전달
+        let header = FrameHeader { flags: partial.header.flags & !FLAG_PARITY, ..partial.header };
+        Some((header, payload))
+    }
+
+    // frame_id까지 내보냈으니 그보다 오래된 미완성 프레임은 버리고 이후 청크도 무시
+    pub fn advance_floor(&mut self, frame_id: u32) {
+        if self.floor.is_some_and(|floor| !frame_id_newer(frame_id, floor)) {
+            return;
+        }
+        self.floor = Some(frame_id);
+        let stale: Vec<u32> = self.frames.keys().copied().filter(|&id| !frame_id_newer(id, frame_id)).collect();
+        for id in stale {
+            self.drop_frame(id);
+        }
+    }
+
+    // 재전송을 요청할 (frame_id, 빠진 청크 인덱스) 목록
+    pub fn take_nacks(&mut self, now: Instant, config: &NackConfig) -> Vec<(u32, Vec<u32>)> {
+        let mut nacks = Vec::new();
+        for (&frame_id, partial) in self.frames.iter_mut() {
+            if now.saturating_duration_since(partial.first_seen) > config.deadline
+                || now.saturating_duration_since(partial.last_chunk_at) < config.delay
+                || partial.last_nack_at.is_some_and(|t| now.saturating_duration_since(t) < config.interval)
+            {
+                continue;
+            }
+
+            let missing: Vec<u32> = partial.chunks.iter()
+                .enumerate()
+                .filter(|(_, chunk)| chunk.is_none())
+                .map(|(i, _)| i as u32)
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+
+            partial.last_nack_at = Some(now);
+            self.stats.nacks_sent += 1;
+            nacks.push((frame_id, missing));
+        }
+        nacks
+    }
+
+    // max_age를 넘긴 미완성 프레임 정리
+    pub fn evict_expired(&mut self, now: Instant) {
+        let max_age = self.config.max_age;
+        let expired: Vec<u32> = self.frames.iter()
+            .filter(|(_, partial)| now.saturating_duration_since(partial.first_seen) > max_age)
+            .map(|(&id, _)| id)
+            .collect();
+        for id in expired {
+            self.drop_frame(id);
+        }
+    }
+
+    fn drop_frame(&mut self, frame_id: u32) {
+        if let Some(partial) = self.frames.remove(&frame_id) {
+            self.stats.frames_dropped += 1;
+            self.stats.chunks_lost += (partial.header.total_chunks - partial.received) as u64;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::get_webcam::udp::codec::Codec;
+    use crate::get_webcam::udp::header::PixelFormat;
+
+    // 청크 2개짜리 프레임 (페이로드 4바이트)
+    fn header(frame_id: u32, chunk_index: u32) -> FrameHeader {
+        FrameHeader {
+            flags: 0,
+            frame_id,
+            total_chunks: 2,
+            chunk_index,
+            width: 2,
+            height: 2,
+            channels: 1,
+            pixel_format: PixelFormat::Gray8,
+            codec: Codec::Raw,
+            stride: 2,
+            fec_group: 0,
+            payload_len: 4,
+            timestamp_us: 0,
+            session_id: 1,
+        }
+    }
+
+    #[test]
+    fn completes_and_ignores_duplicates() {
+        let mut reassembler = Reassembler::new(ReassemblyConfig::default());
+        assert!(reassembler.push(header(1, 1), &[3, 4]).is_none());
+        let (done, payload) = reassembler.push(header(1, 0), &[1, 2]).unwrap();
+        assert_eq!(done.frame_id, 1);
+        assert_eq!(payload, [1, 2, 3, 4]);
+
+        // 완성된 프레임의 늦은 중복 청크로 다시 조립하지 않음
+        assert!(reassembler.push(header(1, 0), &[1, 2]).is_none());
+        assert_eq!(reassembler.in_flight(), 0);
+        assert_eq!(reassembler.stats().frames_completed, 1);
+    }
+
+    #[test]
+    fn evicts_oldest_when_full() {
+        let mut reassembler = Reassembler::new(ReassemblyConfig { max_in_flight: 2, ..Default::default() });
+        reassembler.push(header(1, 0), &[1, 2]);
+        reassembler.push(header(2, 0), &[1, 2]);
+        reassembler.push(header(3, 0), &[1, 2]);
+        assert_eq!(reassembler.in_flight(), 2);
+        assert_eq!(reassembler.stats().frames_dropped, 1);
+        assert_eq!(reassembler.stats().chunks_lost, 1);
+
+        // 버린 1번 대신 2번은 아직 완성할 수 있음
+        assert!(reassembler.push(header(2, 1), &[3, 4]).is_some());
+    }
+
+    #[test]
+    fn drops_chunk_older_than_in_flight_when_full() {
+        let mut reassembler = Reassembler::new(ReassemblyConfig { max_in_flight: 2, ..Default::default() });
+        reassembler.push(header(5, 0), &[1, 2]);
+        reassembler.push(header(6, 0), &[1, 2]);
+
+        // 늦게 도착한 4번 청크가 조립 중인 5번을 밀어내지 않음
+        assert!(reassembler.push(header(4, 0), &[1, 2]).is_none());
+        assert_eq!(reassembler.in_flight(), 2);
+        assert_eq!(reassembler.stats().frames_dropped, 0);
+        assert!(reassembler.push(header(5, 1), &[3, 4]).is_some());
+        assert!(reassembler.push(header(6, 1), &[3, 4]).is_some());
+    }
+
+    #[test]
+    fn evicts_expired() {
+        let mut reassembler = Reassembler::new(ReassemblyConfig { max_age: Duration::from_millis(100), ..Default::default() });
+        reassembler.push(header(1, 0), &[1, 2]);
+        reassembler.evict_expired(Instant::now());
+        assert_eq!(reassembler.in_flight(), 1);
+        reassembler.evict_expired(Instant::now() + Duration::from_millis(200));
+        assert_eq!(reassembler.in_flight(), 0);
+        assert_eq!(reassembler.stats().frames_dropped, 1);
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let mut reassembler = Reassembler::new(ReassemblyConfig::default());
+        assert!(reassembler.push(header(1, 2), &[1, 2]).is_none());
+        assert!(reassembler.push(FrameHeader { payload_len: 1, ..header(1, 0) }, &[1]).is_none());
+        assert_eq!(reassembler.in_flight(), 0);
+
+        // 같은 프레임인데 이미지 정보가 다른 청크는 무시
+        reassembler.push(header(1, 0), &[1, 2]);
+        assert!(reassembler.push(FrameHeader { width: 4, ..header(1, 1) }, &[3, 4]).is_none());
+        assert_eq!(reassembler.in_flight(), 1);
+    }
+
+    #[test]
+    fn rejects_total_chunks_above_limit() {
+        let mut reassembler = Reassembler::new(ReassemblyConfig::default()).with_max_total_chunks(100);
+        let wide = FrameHeader { total_chunks: 0x1_0000, payload_len: 0x10_0000, ..header(1, 0) };
+        assert!(reassembler.push(wide, &[1; 16]).is_none());
+        assert_eq!(reassembler.in_flight(), 0);
+
+        // 제한 이하의 헤더는 그대로 받음
+        assert!(reassembler.push(FrameHeader { total_chunks: 100, payload_len: 200, ..header(1, 0) }, &[1, 2]).is_none());
+        assert_eq!(reassembler.in_flight(), 1);
+    }
+
+    #[test]
+    fn advance_floor_drops_older_frames_across_wraparound() {
+        let mut reassembler = Reassembler::new(ReassemblyConfig::default());
+        reassembler.push(header(u32::MAX - 1, 0), &[1, 2]);
+        reassembler.push(header(u32::MAX, 0), &[1, 2]);
+        reassembler.push(header(1, 0), &[1, 2]);
+
+        reassembler.advance_floor(u32::MAX);
+        assert_eq!(reassembler.in_flight(), 1);
+        assert_eq!(reassembler.stats().frames_dropped, 2);
+
+        // floor 이하의 청크는 무시하고, 뒤로 가는 floor는 적용하지 않음
+        assert!(reassembler.push(header(u32::MAX, 1), &[3, 4]).is_none());
+        reassembler.advance_floor(u32::MAX - 5);
+        assert_eq!(reassembler.push(header(1, 1), &[3, 4]).map(|(header, _)| header.frame_id), Some(1));
+        assert!(reassembler.push(header(u32::MAX - 1, 1), &[3, 4]).is_none());
+        assert_eq!(reassembler.in_flight(), 0);
+    }
+}
@@ -0,0 +1,805 @@
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+use std::io::ErrorKind;
+use std::sync::mpsc::Sender;
+use anyhow::{Result, Context};
+
+use crate::auth::PairingKey;
+use crate::connection::{ConnectionState, LivenessConfig};
+use crate::crypto::{self, Protection, SecurityConfig};
+use crate::endpoint::EndpointConfig;
+use crate::handshake::{self, Accept, AcceptPolicy, Responder, Session, StreamKind};
+
+use super::codec::Codec;
+use super::convert::{OutputFormat, OutputFrame};
+use super::control::{self, ControlMessage, MAX_NACK_ENTRIES};
+use super::delta::DeltaDecoder;
+use super::frame::Frame;
+use super::header::{FrameHeader, MAX_TOTAL_CHUNKS, WIDE_HEADER_SIZE};
+use super::jitter::{JitterBuffer, JitterPolicy};
+use super::pacing::{DelayTracker, ReceiverReport};
+use super::reassembly::{NackConfig, Reassembler, ReassemblyConfig, ReassemblyStats};
+use super::sender::MIN_PROBE_MTU;
+use super::timing::{FrameTiming, TimingTracker};
+
+const MAX_RECV_BUFFER_SIZE: usize = 65535;
+// IPv4 UDP 데이터그램에 담을 수 있는 최대 크기
+pub const MAX_DATAGRAM_SIZE: u16 = 65507;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+// 키프레임이 올 때까지 요청을 다시 보내는 간격
+const KEYFRAME_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone)]
+pub struct ReceiverConfig {
+    // 바인딩할 주소와 포트 (포트 0이면 local_addr()로 실제 포트 확인)
+    pub endpoint: EndpointConfig,
+    // 송신측이 제안한 코덱 중 여기 있는 것만 허용
+    pub accepted_codecs: Vec<Codec>,
+    pub reassembly: ReassemblyConfig,
+    // 완성된 프레임을 바로 내보낼지, 순서대로 일정하게 내보낼지
+    pub jitter: JitterPolicy,
+    // Some이면 빠진 청크를 송신측에 다시 요청 (송신측 retransmit_frames도 켜야 함)
+    pub nack: Option<NackConfig>,
+    // 송신측 혼잡 제어에 쓰이는 수신 상태 보고 주기 (None = 보고 안 함)
+    pub report_interval: Option<Duration>,
+    // Some이면 같은 키를 가진 송신측만 받아들이고 MAC이 틀린 패킷은 버림
+    pub psk: Option<PairingKey>,
+    // true면 암호화하지 않는 송신측은 거절 (false여도 송신측이 요청하면 암호화)
+    pub encrypt: bool,
+    pub liveness: LivenessConfig,
+    // 스트림마다 연결 상태가 바뀔 때 전달
+    pub events: Option<Sender<StreamEvent>>,
+    // 한 포트에서 동시에 받을 송신측 수 (넘으면 새 송신측은 Busy로 거절)
+    pub max_streams: usize,
+    // 송신측이 제안한 MTU를 이 값으로 제한 (송신측 SenderConfig.mtu와 작은 쪽 사용)
+    pub max_mtu: u16,
+}
+
+impl Default for ReceiverConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: EndpointConfig::default(),
+            accepted_codecs: vec![Codec::Raw, Codec::Jpeg, Codec::Png, Codec::Qoi],
+            reassembly: ReassemblyConfig::default(),
+            jitter: JitterPolicy::default(),
+            nack: None,
+            report_interval: Some(Duration::from_millis(200)),
+            psk: None,
+            encrypt: false,
+            liveness: LivenessConfig::default(),
+            events: None,
+            max_streams: 4,
+            max_mtu: MAX_DATAGRAM_SIZE,
+        }
+    }
+}
+
+// 송신측 하나의 연결 정보
+#[derive(Debug, Clone)]
+pub struct StreamInfo {
+    pub stream_id: u32,
+    pub source: String,
+    pub peer: SocketAddr,
+    // 연결 시 합의된 코덱, 해상도, MTU 등
+    pub session: Accept,
+    pub state: ConnectionState,
+    // 페어링 키로 패킷마다 MAC을 확인하는지, 암호화까지 하는지
+    pub authenticated: bool,
+    pub encrypted: bool,
+    pub stats: ReassemblyStats,
+    // 캡처/수신 FPS와 프레임 지연 (카메라 멈춤과 네트워크 막힘 구분용)
+    pub timing: FrameTiming,
+}
+
+// 수신기가 출력 없이 버린 패킷/프레임의 원인별 누적 수
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DropStats {
+    // 처리하지 못한 연결 요청 (형식 오류, 인증 실패 등)
+    pub bad_handshakes: u64,
+    // 연결되지 않은 주소에서 온 패킷
+    pub unknown_peer: u64,
+    // MAC이 맞지 않거나 복호화되지 않는 패킷
+    pub unauthenticated: u64,
+    pub bad_headers: u64,
+    // 같은 주소에서 온 이전 세션의 늦은 청크
+    pub stale_session: u64,
+    // 기준 화면이 어긋나 키프레임을 기다리며 버린 델타 프레임
+    pub broken_deltas: u64,
+    // 복원할 수 없는 프레임
+    pub undecodable_frames: u64,
+    // expect_size()와 크기가 다른 프레임
+    pub size_mismatches: u64,
+    // 새 송신측에게 자리를 내주려고 정리한 끊긴 스트림
+    pub streams_evicted: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamEvent {
+    pub stream_id: u32,
+    pub source: String,
+    pub state: ConnectionState,
+}
+
+// 송신측마다 따로 두는 세션/재조립 상태
+struct Stream {
+    id: u32,
+    source: String,
+    peer: SocketAddr,
+    session: Accept,
+    protection: Option<Protection>,
+    reassembler: Reassembler,
+    jitter: JitterBuffer,
+    last_report_at: Instant,
+    last_report_stats: ReassemblyStats,
+    delay: DelayTracker,
+    timing: TimingTracker,
+    delta: DeltaDecoder,
+    keyframe_requested_at: Option<Instant>,
+    state: ConnectionState,
+    last_heard: Instant,
+    last_heartbeat_at: Instant,
+}
+
+impl Stream {
+    fn info(&self) -> StreamInfo {
+        StreamInfo {
+            stream_id: self.id,
+            source: self.source.clone(),
+            peer: self.peer,
+            session: self.session,
+            state: self.state,
+            authenticated: self.protection.is_some(),
+            encrypted: self.protection.as_ref().is_some_and(Protection::is_encrypted),
+            stats: self.stats(),
+            timing: self.timing.timing(Instant::now()),
+        }
+    }
+
+    fn stats(&self) -> ReassemblyStats {
+        ReassemblyStats { frames_late: self.jitter.late_frames(), ..self.reassembler.stats() }
+    }
+
+    fn is_lost(&self) -> bool {
+        self.state == ConnectionState::Lost
+    }
+}
+
+// 합의된 해상도의 프레임이 나뉠 수 있는 최대 청크 수
+// 코덱/델타 오버헤드와 stride 패딩을 감안해 4채널 원본 크기의 2배까지 허용하고
+// 송신측이 MTU 탐색으로 청크를 줄일 수 있으므로 탐색 하한을 기준으로 나눔
+fn max_total_chunks(session: &Session) -> u32 {
+    let accept = &session.accept;
+    if accept.width == 0 || accept.height == 0 {
+        return MAX_TOTAL_CHUNKS;
+    }
+    let max_frame_bytes = accept.width as usize * accept.height as usize * 4 * 2;
+    let chunk_size = (accept.mtu.min(MIN_PROBE_MTU) as usize)
+        .saturating_sub(WIDE_HEADER_SIZE + crypto::overhead(session.protection.as_ref()))
+        .max(1);
+    max_frame_bytes.div_ceil(chunk_size).min(MAX_TOTAL_CHUNKS as usize) as u32
+}
+
+// 이름표가 없으면 송신측 주소로 구분
+fn source_label(session: &Session) -> String {
+    if session.hello.source.is_empty() {
+        session.peer.to_string()
+    } else {
+        session.hello.source.clone()
+    }
+}
+
+// 한 포트에서 여러 송신측(세션)의 프레임을 받는 수신기
+// 송신측마다 재조립 상태를 따로 두고, 프레임에 stream_id와 source를 붙여서 돌려줌
+// 송신측이 다시 handshake하면 같은 stream_id로 이어서 받음
+pub struct WebcamReceiver {
+    socket: UdpSocket,
+    local_addr: SocketAddr,
+    responder: Responder,
+    streams: Vec<Stream>,
+    next_stream_id: u32,
+    max_streams: usize,
+    buf: Vec<u8>,
+    expected_size: Option<(usize, usize)>,
+    reassembly: ReassemblyConfig,
+    jitter: JitterPolicy,
+    nack: Option<NackConfig>,
+    report_interval: Option<Duration>,
+    started: Instant,
+    liveness: LivenessConfig,
+    events: Option<Sender<StreamEvent>>,
+    drops: DropStats,
+}
+
+impl WebcamReceiver {
+    pub fn bind() -> Result<Self> {
+        Self::bind_with(ReceiverConfig::default())
+    }
+
+    // 바인딩만 하고 바로 반환 (송신측은 프레임을 받는 동안 연결됨)
+    pub fn bind_with(config: ReceiverConfig) -> Result<Self> {
+        let socket = config.endpoint.bind_udp()
+            .with_context(|| format!("서버 바인딩 실패: {}", config.endpoint.listen_addr()))?;
+        socket.set_read_timeout(Some(POLL_INTERVAL))
+            .context("소켓 읽기 타임아웃 설정 실패")?;
+        let server_addr = socket.local_addr().context("바인딩 주소 확인 실패")?;
+
+        let policy = AcceptPolicy {
+            stream_kind: StreamKind::Webcam,
+            codecs: config.accepted_codecs,
+            max_mtu: config.max_mtu.min(MAX_DATAGRAM_SIZE),
+        };
+        Ok(Self {
+            socket,
+            local_addr: server_addr,
+            responder: Responder::new(policy, SecurityConfig { psk: config.psk, encrypt: config.encrypt }),
+            streams: Vec::new(),
+            next_stream_id: 1,
+            max_streams: config.max_streams.max(1),
+            buf: vec![0u8; MAX_RECV_BUFFER_SIZE],
+            expected_size: None,
+            reassembly: config.reassembly,
+            jitter: config.jitter,
+            nack: config.nack,
+            report_interval: config.report_interval,
+            started: Instant::now(),
+            liveness: config.liveness,
+            events: config.events,
+            drops: DropStats::default(),
+        })
+    }
+
+    // 실제로 바인딩된 주소 (포트 0으로 바인딩했을 때 확인용)
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    // 지정하면 크기가 다른 프레임은 버림 (drops().size_mismatches로 확인)
+    pub fn expect_size(&mut self, width: usize, height: usize) {
+        self.expected_size = Some((width, height));
+    }
+
+    // 현재 알고 있는 송신측들 (끊긴 송신측도 재연결을 기다리는 동안 포함)
+    pub fn streams(&self) -> Vec<StreamInfo> {
+        self.streams.iter().map(Stream::info).collect()
+    }
+
+    pub fn stream(&self, stream_id: u32) -> Option<StreamInfo> {
+        self.streams.iter().find(|s| s.id == stream_id).map(Stream::info)
+    }
+
+    // 모든 스트림의 합계
+    pub fn stats(&self) -> ReassemblyStats {
+        self.streams.iter().fold(ReassemblyStats::default(), |total, stream| total + stream.stats())
+    }
+
+    // 스트림에 속하지 않는 패킷까지 포함한 원인별 버림 수
+    pub fn drops(&self) -> DropStats {
+        self.drops
+    }
+
+    // 어느 송신측이든 프레임 하나가 완성될 때까지 대기
+    pub fn next_frame(&mut self) -> Result<Frame> {
+        loop {
+            if let Some(frame) = self.recv_until(None)? {
+                return Ok(frame);
+            }
+        }
+    }
+
+    // next_frame()과 같지만 요청한 픽셀 포맷으로 변환해서 반환
+    pub fn next_frame_as(&mut self, format: OutputFormat) -> Result<OutputFrame> {
+        Ok(self.next_frame()?.convert(format))
+    }
+
+    // 이미 도착한 패킷만 처리하고 바로 반환
+    pub fn try_recv(&mut self) -> Result<Option<Frame>> {
+        self.recv_until(Some(Instant::now()))
+    }
+
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Frame>> {
+        self.recv_until(Some(Instant::now() + timeout))
+    }
+
+    fn recv_until(&mut self, user_deadline: Option<Instant>) -> Result<Option<Frame>> {
+        loop {
+            let now = Instant::now();
+            for stream in &mut self.streams {
+                stream.reassembler.evict_expired(now);
+            }
+            self.check_liveness()?;
+            self.send_nacks()?;
+            self.send_report()?;
+            if let Some(frame) = self.release_ready()? {
+                return Ok(Some(frame));
+            }
+
+            // 지터 버퍼에서 다음 프레임을 내보낼 시각까지만 대기
+            let next_release = self.streams.iter().filter_map(|s| s.jitter.next_release_at()).min();
+            let deadline = match (user_deadline, next_release) {
+                (Some(d), Some(r)) => Some(d.min(r)),
+                (d, r) => d.or(r),
+            };
+            let remaining = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+            match remaining {
+                Some(r) if r.is_zero() => {
+                    self.socket.set_nonblocking(true)
+                        .context("소켓 논블로킹 설정 실패")?;
+                }
+                _ => {
+                    self.socket.set_nonblocking(false)
+                        .context("소켓 블로킹 설정 실패")?;
+                    let poll = self.nack.map_or(POLL_INTERVAL, |nack| {
+                        nack.delay.clamp(Duration::from_millis(1), POLL_INTERVAL)
+                    }).min(self.liveness.heartbeat_interval.max(Duration::from_millis(1)));
+                    self.socket.set_read_timeout(Some(remaining.unwrap_or(poll).min(poll)))
+                        .context("소켓 읽기 타임아웃 설정 실패")?;
+                }
+            }
+
+            match self.socket.recv_from(&mut self.buf) {
+                Ok((size, addr)) => self.handle_packet(size, addr)?,
+                // Windows는 heartbeat/NACK을 보낸 송신측 포트가 닫혀 있으면 ConnectionReset을 돌려줌
+                // (송신측이 재시작하는 중이므로 끝내지 않고 재연결을 기다림)
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::ConnectionReset) => {
+                    if user_deadline.is_some_and(|d| Instant::now() >= d) {
+                        return self.release_ready();
+                    }
+                }
+                Err(e) => return Err(e).context("UDP 수신 오류"),
+            }
+        }
+    }
+
+    fn notify(&self, stream: &Stream) {
+        if let Some(events) = &self.events {
+            let _ = events.send(StreamEvent { stream_id: stream.id, source: stream.source.clone(), state: stream.state });
+        }
+    }
+
+    // 조용한 송신측은 Lost, 살아 있는 송신측에는 주기적으로 heartbeat 전송
+    fn check_liveness(&mut self) -> Result<()> {
+        for index in 0..self.streams.len() {
+            let stream = &mut self.streams[index];
+            if stream.is_lost() {
+                continue;
+            }
+            if stream.last_heard.elapsed() > self.liveness.timeout {
+                stream.state = ConnectionState::Lost;
+                self.notify(&self.streams[index]);
+                continue;
+            }
+            if stream.last_heartbeat_at.elapsed() < self.liveness.heartbeat_interval {
+                continue;
+            }
+
+            stream.last_heartbeat_at = Instant::now();
+            let mut message = ControlMessage::Heartbeat.encode();
+            crypto::seal(stream.protection.as_mut(), &mut message);
+            self.socket.send_to(&message, stream.peer)
+                .context("heartbeat 전송 실패")?;
+        }
+        self.update_busy();
+        Ok(())
+    }
+
+    // 빈 자리도 끊긴 송신측도 없으면 새 송신측은 받지 않음
+    fn update_busy(&mut self) {
+        let full = self.streams.len() >= self.max_streams && !self.streams.iter().any(Stream::is_lost);
+        self.responder.set_busy(full);
+    }
+
+    // 새 세션을 스트림에 연결
+    // 같은 주소의 송신측이나, 끊긴 송신측과 이름표가 같으면 그 스트림을 이어받음
+    fn adopt_session(&mut self, session: Session) {
+        if self.streams.iter().any(|s| s.session.session_id == session.accept.session_id) {
+            return;
+        }
+        let source = source_label(&session);
+        let replaced = self.streams.iter().position(|s| {
+            s.peer == session.peer || (s.is_lost() && s.source == source)
+        });
+
+        let (id, state) = match replaced {
+            Some(index) => (self.streams.remove(index).id, ConnectionState::Reconnected),
+            None => {
+                // 자리가 없으면 가장 오래 조용한 끊긴 송신측을 내보냄
+                if self.streams.len() >= self.max_streams
+                    && let Some(index) = self.streams.iter()
+                        .enumerate()
+                        .filter(|(_, s)| s.is_lost())
+                        .min_by_key(|(_, s)| s.last_heard)
+                        .map(|(index, _)| index)
+                {
+                    self.streams.remove(index);
+                    self.drops.streams_evicted += 1;
+                }
+                let id = self.next_stream_id;
+                self.next_stream_id = self.next_stream_id.wrapping_add(1).max(1);
+                (id, ConnectionState::Connected)
+            }
+        };
+
+        let reassembler = Reassembler::new(self.reassembly).with_max_total_chunks(max_total_chunks(&session));
+        let now = Instant::now();
+        let stream = Stream {
+            id,
+            source,
+            peer: session.peer,
+            session: session.accept,
+            protection: session.protection,
+            reassembler,
+            jitter: JitterBuffer::new(self.jitter),
+            last_report_at: now,
+            last_report_stats: ReassemblyStats::default(),
+            delay: DelayTracker::default(),
+            timing: TimingTracker::default(),
+            delta: DeltaDecoder::default(),
+            keyframe_requested_at: None,
+            state,
+            last_heard: now,
+            last_heartbeat_at: now,
+        };
+        self.notify(&stream);
+        self.streams.push(stream);
+        self.update_busy();
+    }
+
+    fn send_nacks(&mut self) -> Result<()> {
+        let Some(nack) = self.nack else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        for stream in self.streams.iter_mut().filter(|s| !s.is_lost()) {
+            for (frame_id, missing) in stream.reassembler.take_nacks(now, &nack) {
+                for part in missing.chunks(MAX_NACK_ENTRIES) {
+                    let mut message = ControlMessage::Nack { frame_id, missing: part.to_vec() }.encode();
+                    crypto::seal(stream.protection.as_mut(), &mut message);
+                    self.socket.send_to(&message, stream.peer)
+                        .context("NACK 전송 실패")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn send_report(&mut self) -> Result<()> {
+        let Some(interval) = self.report_interval else {
+            return Ok(());
+        };
+
+        for stream in self.streams.iter_mut().filter(|s| !s.is_lost()) {
+            if stream.last_report_at.elapsed() < interval {
+                continue;
+            }
+
+            let stats = stream.reassembler.stats();
+            let report = ReceiverReport {
+                received_chunks: (stats.chunks_received - stream.last_report_stats.chunks_received) as u32,
+                lost_chunks: (stats.chunks_lost - stream.last_report_stats.chunks_lost) as u32,
+                queuing_delay_us: stream.delay.take_average_us(),
+            };
+            stream.last_report_stats = stats;
+            stream.last_report_at = Instant::now();
+
+            let mut message = ControlMessage::Report(report).encode();
+            crypto::seal(stream.protection.as_mut(), &mut message);
+            self.socket.send_to(&message, stream.peer)
+                .context("수신 상태 보고 전송 실패")?;
+        }
+        Ok(())
+    }
+
+    fn handle_packet(&mut self, size: usize, addr: SocketAddr) -> Result<()> {
+        // 새 송신측의 Hello, Accept 유실 시 재전송, 재연결 모두 handshake로 옴
+        if handshake::is_handshake(&self.buf[..size]) {
+            match self.responder.handle(&self.socket, addr, &self.buf[..size]) {
+                Ok(Some(session)) => self.adopt_session(session),
+                Ok(None) => {}
+                Err(_) => self.drops.bad_handshakes += 1,
+            }
+            return Ok(());
+        }
+
+        let Some(index) = self.streams.iter().position(|s| s.peer == addr) else {
+            self.drops.unknown_peer += 1;
+            return Ok(());
+        };
+        let stream = &mut self.streams[index];
+
+        // MAC이 맞지 않거나 복호화되지 않는 패킷은 조용히 버림
+        let Some(packet) = crypto::open(stream.protection.as_mut(), &self.buf[..size]) else {
+            self.drops.unauthenticated += 1;
+            return Ok(());
+        };
+        stream.last_heard = Instant::now();
+        if stream.is_lost() {
+            stream.state = ConnectionState::Reconnected;
+            let id = stream.id;
+            if let Some(events) = &self.events {
+                let _ = events.send(StreamEvent { stream_id: id, source: stream.source.clone(), state: stream.state });
+            }
+        }
+
+        // 송신측 heartbeat
+        if control::is_control(&packet) {
+            return Ok(());
+        }
+
+        // 잘못된 패킷 하나 때문에 스트림을 끝내지 않음
+        let Ok(header) = FrameHeader::decode(&packet) else {
+            self.drops.bad_headers += 1;
+            return Ok(());
+        };
+        // 같은 주소에서 온 이전 세션의 늦은 청크
+        if header.session_id != stream.session.session_id {
+            self.drops.stale_session += 1;
+            return Ok(());
+        }
+        let Some((header, payload)) = stream.reassembler.push(header, &packet[header.encoded_len()..]) else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        let now_us = self.started.elapsed().as_micros() as i64;
+        stream.delay.on_sample(now_us - header.timestamp_us as i64);
+        stream.timing.on_frame(header.timestamp_us, now);
+        stream.jitter.push(header, payload, now);
+        Ok(())
+    }
+
+    // 지터 버퍼에서 내보낼 때가 된 프레임을 하나 꺼내서 복원
+    fn release_ready(&mut self) -> Result<Option<Frame>> {
+        let now = Instant::now();
+        for index in 0..self.streams.len() {
+            while let Some((header, payload)) = self.streams[index].jitter.pop(now) {
+                self.streams[index].reassembler.advance_floor(header.frame_id);
+                if let Some(frame) = self.decode(index, header, payload)? {
+                    return Ok(Some(frame));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // 프레임 순서대로 호출해야 델타를 이어 붙일 수 있음
+    fn decode(&mut self, index: usize, header: FrameHeader, payload: Vec<u8>) -> Result<Option<Frame>> {
+        let stream = &mut self.streams[index];
+        let frame = if header.is_delta() {
+            let pixels = match stream.delta.apply(&header, &payload) {
+                Ok(pixels) => pixels,
+                Err(_) => {
+                    // 기준 화면이 어긋났으면 키프레임이 올 때까지 델타를 버림
+                    self.drops.broken_deltas += 1;
+                    if stream.keyframe_requested_at.is_none_or(|at| at.elapsed() >= KEYFRAME_REQUEST_INTERVAL) {
+                        stream.keyframe_requested_at = Some(Instant::now());
+                        let mut message = ControlMessage::KeyframeRequest.encode();
+                        crypto::seal(stream.protection.as_mut(), &mut message);
+                        self.socket.send_to(&message, stream.peer)
+                            .context("키프레임 요청 전송 실패")?;
+                    }
+                    return Ok(None);
+                }
+            };
+            let row_bytes = header.width as u32 * header.channels as u32;
+            Frame::from_parts(&FrameHeader { codec: Codec::Raw, stride: row_bytes, ..header }, pixels)
+        } else {
+            Frame::from_parts(&header, payload)
+        };
+        // 복원할 수 없는 프레임은 버리고 다음 프레임을 기다림
+        let mut frame = match frame {
+            Ok(frame) => frame,
+            Err(_) => {
+                self.drops.undecodable_frames += 1;
+                return Ok(None);
+            }
+        };
+
+        if header.is_keyframe() {
+            stream.delta.keyframe(frame.frame_id, frame.packed().into_owned(), frame.width, frame.height, frame.pixel_format);
+            stream.keyframe_requested_at = None;
+        }
+        frame.stream_id = stream.id;
+        frame.source = stream.source.clone();
+
+        if self.expected_size.is_some_and(|expected| expected != (frame.width, frame.height)) {
+            self.drops.size_mismatches += 1;
+            return Ok(None);
+        }
+
+        Ok(Some(frame))
+    }
+}
+
+// 블로킹 이터레이터: 프레임마다 next_frame()을 호출
+impl Iterator for WebcamReceiver {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_frame())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::mpsc;
+    use std::thread::{self, JoinHandle};
+
+    use crate::handshake::{HandshakeError, HandshakeMessage, Hello, RejectReason};
+    use super::super::header::PixelFormat;
+    use super::super::sender::{SenderConfig, WebcamSender};
+
+    const LOOPBACK: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    fn bind(config: ReceiverConfig) -> WebcamReceiver {
+        WebcamReceiver::bind_with(ReceiverConfig {
+            endpoint: EndpointConfig { bind_addr: Some(LOOPBACK), port: 0, ..Default::default() },
+            report_interval: None,
+            ..config
+        }).unwrap()
+    }
+
+    fn frame(fill: u8) -> Frame {
+        Frame {
+            stream_id: 0,
+            source: String::new(),
+            frame_id: 0,
+            width: 4,
+            height: 2,
+            channels: 3,
+            pixel_format: PixelFormat::Bgr8,
+            stride: 12,
+            timestamp_us: 0,
+            data: vec![fill; 24],
+        }
+    }
+
+    fn sender(receiver: SocketAddr, source: &str) -> Result<WebcamSender> {
+        let config = SenderConfig {
+            endpoint: EndpointConfig { peer: Some(LOOPBACK.to_string()), port: receiver.port(), ..Default::default() },
+            source: source.to_string(),
+            ..Default::default()
+        };
+        WebcamSender::connect_with(4, 2, config)
+    }
+
+    // 수신기를 스레드에서 돌리다가 stop으로 받은 개수만큼 프레임이 모이면 돌려줌
+    fn spawn_pump(mut receiver: WebcamReceiver) -> (mpsc::Sender<usize>, JoinHandle<(WebcamReceiver, Vec<Frame>)>) {
+        let (stop, wanted) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            let deadline = Instant::now() + Duration::from_secs(5);
+            let mut frames = Vec::new();
+            let mut target = None;
+            while Instant::now() < deadline && target.is_none_or(|n| frames.len() < n) {
+                if let Some(frame) = receiver.recv_timeout(Duration::from_millis(20)).unwrap() {
+                    frames.push(frame);
+                }
+                if let Ok(n) = wanted.try_recv() {
+                    target = Some(n);
+                }
+            }
+            (receiver, frames)
+        });
+        (stop, handle)
+    }
+
+    #[test]
+    fn frames_are_tagged_per_sender() {
+        let receiver = bind(ReceiverConfig::default());
+        let addr = receiver.local_addr();
+        let (stop, pump) = spawn_pump(receiver);
+
+        let mut left = sender(addr, "left").unwrap();
+        let mut right = sender(addr, "right").unwrap();
+        left.send(&frame(1)).unwrap();
+        right.send(&frame(2)).unwrap();
+        stop.send(2).unwrap();
+        let (receiver, frames) = pump.join().unwrap();
+
+        assert_eq!(frames.len(), 2);
+        let left_frame = frames.iter().find(|f| f.source == "left").unwrap();
+        let right_frame = frames.iter().find(|f| f.source == "right").unwrap();
+        assert!(left_frame.data.iter().all(|&b| b == 1));
+        assert!(right_frame.data.iter().all(|&b| b == 2));
+        assert_ne!(left_frame.stream_id, right_frame.stream_id);
+
+        let streams = receiver.streams();
+        assert_eq!(streams.len(), 2);
+        for frame in [left_frame, right_frame] {
+            let info = receiver.stream(frame.stream_id).unwrap();
+            assert_eq!(info.source, frame.source);
+            assert_eq!(info.state, ConnectionState::Connected);
+            assert_eq!(info.stats.frames_completed, 1);
+        }
+    }
+
+    #[test]
+    fn extra_sender_is_busy() {
+        let receiver = bind(ReceiverConfig { max_streams: 1, ..Default::default() });
+        let addr = receiver.local_addr();
+        let (stop, pump) = spawn_pump(receiver);
+
+        let _first = sender(addr, "first").unwrap();
+        let err = sender(addr, "second").err().expect("자리가 없으면 거절");
+        assert!(err.chain().any(|e| matches!(e.downcast_ref(), Some(HandshakeError::Rejected(RejectReason::Busy)))), "{:#}", err);
+        stop.send(0).unwrap();
+        let (receiver, _) = pump.join().unwrap();
+        assert_eq!(receiver.streams().len(), 1);
+    }
+
+    // 같은 소켓으로 handshake해서 Accept를 받음 (수신기는 이 스레드에서 직접 돌림)
+    fn handshake(receiver: &mut WebcamReceiver, client: &UdpSocket) -> Accept {
+        let hello = Hello { codecs: vec![Codec::Raw], width: 4, height: 2, source: "cam".to_string(), ..Hello::new(StreamKind::Webcam) };
+        client.send_to(&HandshakeMessage::Hello(hello).encode(), receiver.local_addr()).unwrap();
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).unwrap().is_none());
+
+        let mut buf = [0u8; 2048];
+        let size = client.recv(&mut buf).unwrap();
+        let Ok(HandshakeMessage::Accept(accept)) = HandshakeMessage::decode(&buf[..size]) else {
+            panic!("Accept가 아님");
+        };
+        accept
+    }
+
+    fn send_frame(client: &UdpSocket, to: SocketAddr, session_id: u64, fill: u8) {
+        let header = FrameHeader {
+            flags: 0,
+            frame_id: 0,
+            total_chunks: 1,
+            chunk_index: 0,
+            width: 4,
+            height: 2,
+            channels: 3,
+            pixel_format: PixelFormat::Bgr8,
+            codec: Codec::Raw,
+            stride: 12,
+            fec_group: 0,
+            payload_len: 24,
+            timestamp_us: 0,
+            session_id,
+        };
+        let mut packet = Vec::new();
+        header.write_to(&mut packet);
+        packet.extend_from_slice(&[fill; 24]);
+        client.send_to(&packet, to).unwrap();
+    }
+
+    #[test]
+    fn same_peer_replaces_session_and_drops_late_chunks() {
+        let (events, states) = mpsc::channel();
+        let mut receiver = bind(ReceiverConfig { events: Some(events), ..Default::default() });
+        let addr = receiver.local_addr();
+        let client = UdpSocket::bind((LOOPBACK, 0)).unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+
+        let old = handshake(&mut receiver, &client);
+        let new = handshake(&mut receiver, &client);
+        assert_ne!(old.session_id, new.session_id);
+        let states: Vec<StreamEvent> = states.try_iter().collect();
+        assert_eq!(states.iter().map(|e| e.state).collect::<Vec<_>>(), [ConnectionState::Connected, ConnectionState::Reconnected]);
+        assert_eq!(states[0].stream_id, states[1].stream_id);
+        assert_eq!(receiver.streams().len(), 1);
+
+        // 이전 세션의 늦은 청크는 새 세션의 재조립에 섞이지 않음
+        send_frame(&client, addr, old.session_id, 1);
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).unwrap().is_none());
+        assert_eq!(receiver.drops().stale_session, 1);
+
+        send_frame(&client, addr, new.session_id, 2);
+        let frame = receiver.recv_timeout(Duration::from_secs(1)).unwrap().expect("새 세션의 프레임");
+        assert_eq!(frame.stream_id, states[0].stream_id);
+        assert_eq!(frame.source, "cam");
+        assert!(frame.data.iter().all(|&b| b == 2));
+    }
+
+    #[test]
+    fn unknown_peer_is_counted() {
+        let mut receiver = bind(ReceiverConfig::default());
+        let client = UdpSocket::bind((LOOPBACK, 0)).unwrap();
+        send_frame(&client, receiver.local_addr(), 1, 0);
+        assert!(receiver.recv_timeout(Duration::from_millis(50)).unwrap().is_none());
+        assert_eq!(receiver.drops(), DropStats { unknown_peer: 1, ..Default::default() });
+    }
+}
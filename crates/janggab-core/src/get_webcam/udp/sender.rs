@@ -0,0 +1,551 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+use std::thread;
+use anyhow::{Result, Context};
+
+use crate::auth::PairingKey;
+use crate::connection::{ConnectionState, LivenessConfig, StateNotifier};
+use crate::crypto::{self, Protection, SecurityConfig};
+use crate::endpoint::EndpointConfig;
+use crate::handshake::{self, Accept, Hello, StreamKind};
+
+use super::codec::{self, Codec};
+use super::control::{self, ControlMessage};
+use super::fec;
+use super::frame::Frame;
+use super::delta::{DeltaConfig, DeltaEncoder, DeltaFrame};
+use super::header::{FrameHeader, FLAG_DELTA, FLAG_KEYFRAME, FLAG_PARITY, HEADER_SIZE, MAX_TOTAL_CHUNKS, WIDE_HEADER_SIZE};
+use super::pacing::{Pacer, PacingConfig};
+use super::timing::FrameScheduler;
+
+const CONNECTION_TIMEOUT_SECS: u64 = 5;
+
+// 대부분의 경로에서 조각나지 않는 데이터그램 크기
+pub const DEFAULT_MTU: u16 = 1400;
+// MTU 탐색의 하한 (이 크기는 어느 경로든 통과한다고 가정)
+pub(crate) const MIN_PROBE_MTU: u16 = 1200;
+// 이 개수의 패킷을 보낼 때마다 수신측 피드백(NACK, 보고)을 확인
+const FEEDBACK_CHECK_INTERVAL: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct SenderConfig {
+    // 수신측 주소와 포트
+    pub endpoint: EndpointConfig,
+    pub codec: Codec,
+    // Codec::Jpeg 일 때만 사용 (1~100)
+    pub jpeg_quality: u8,
+    // FEC 패리티 비율 (0.0 = 끔, 0.25 = 데이터 청크 4개당 패리티 1개)
+    pub fec_overhead: f32,
+    // NACK 재전송용으로 보관할 최근 프레임 수 (0 = 재전송 안 함)
+    pub retransmit_frames: usize,
+    pub pacing: PacingConfig,
+    // 수신측과 같은 페어링 키 (수신측이 키를 쓰면 필수)
+    pub psk: Option<PairingKey>,
+    // X25519 키 교환 후 ChaCha20-Poly1305로 모든 패킷을 암호화
+    pub encrypt: bool,
+    pub liveness: LivenessConfig,
+    // 연결 상태가 바뀔 때마다 전달
+    pub events: Option<Sender<ConnectionState>>,
+    // 수신측에서 이 카메라를 구분할 이름 (비우면 송신측 주소로 표시)
+    pub source: String,
+    // wait_next_frame()이 맞출 캡처 FPS (None = 카메라가 주는 대로)
+    pub target_fps: Option<f64>,
+    // Some이면 키프레임 사이에는 바뀐 타일만 보냄 (정적인 장면에서 대역폭 절약)
+    pub delta: Option<DeltaConfig>,
+    // 청크 하나를 담는 데이터그램 최대 크기 (수신측 max_mtu와 작은 쪽 사용)
+    // 루프백이나 WSL vEthernet처럼 큰 데이터그램이 통과하는 경로는 키울수록 패킷 수가 줄어듦
+    pub mtu: u16,
+    // true면 연결 직후 mtu 이하에서 실제로 수신측까지 가는 가장 큰 크기를 찾아서 사용
+    pub probe_mtu: bool,
+}
+
+impl Default for SenderConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: EndpointConfig::default(),
+            codec: Codec::Raw,
+            jpeg_quality: 80,
+            fec_overhead: 0.0,
+            retransmit_frames: 0,
+            pacing: PacingConfig::default(),
+            psk: None,
+            encrypt: false,
+            liveness: LivenessConfig::default(),
+            events: None,
+            source: String::new(),
+            target_fps: None,
+            delta: None,
+            mtu: DEFAULT_MTU,
+            probe_mtu: false,
+        }
+    }
+}
+
+// handshake로 코덱/MTU를 합의한 뒤 프레임을 청크로 나눠 보내는 송신기
+// 수신측이 응답하지 않으면 다시 handshake해서 이어서 보냄
+pub struct WebcamSender {
+    socket: UdpSocket,
+    server_addr: SocketAddr,
+    width: usize,
+    height: usize,
+    config: SenderConfig,
+    session: Accept,
+    protection: Option<Protection>,
+    chunk_data_size: usize,
+    next_frame_id: u32,
+    // (frame_id, 데이터 청크 패킷들)
+    retransmit_buffer: VecDeque<(u32, Vec<Vec<u8>>)>,
+    retransmitted_chunks: u64,
+    pacer: Pacer,
+    state: StateNotifier,
+    last_heard: Instant,
+    last_heartbeat_at: Instant,
+    last_reconnect_at: Instant,
+    // 캡처 타임스탬프의 기준 (재연결해도 유지)
+    clock: Instant,
+    scheduler: Option<FrameScheduler>,
+    delta: Option<DeltaEncoder>,
+}
+
+// 헤더와 Hello의 해상도는 u16
+fn frame_size(width: usize, height: usize) -> Result<(u16, u16)> {
+    match (u16::try_from(width), u16::try_from(height)) {
+        (Ok(width), Ok(height)) => Ok((width, height)),
+        _ => anyhow::bail!("해상도가 너무 큼: {}x{} (최대 {})", width, height, u16::MAX),
+    }
+}
+
+// 원하는 코덱이 거절되면 Raw로라도 연결
+fn handshake(socket: &UdpSocket, server: SocketAddr, width: usize, height: usize, config: &SenderConfig, timeout: Duration) -> Result<handshake::Session> {
+    let mut codecs = vec![config.codec];
+    if config.codec != Codec::Raw {
+        codecs.push(Codec::Raw);
+    }
+    let (width, height) = frame_size(width, height)?;
+    let hello = Hello {
+        codecs,
+        width,
+        height,
+        // 목표 FPS가 없으면 0 (제한 없음)
+        fps: config.target_fps.map_or(0, |fps| fps.round().clamp(0.0, u16::MAX as f64) as u16),
+        mtu: config.mtu,
+        source: config.source.clone(),
+        ..Hello::new(StreamKind::Webcam)
+    };
+    let security = SecurityConfig { psk: config.psk.clone(), encrypt: config.encrypt };
+    let mut session = handshake::connect(socket, server, &hello, &security, timeout)
+        .with_context(|| format!("서버 {} 연결 실패", server))?;
+    if config.probe_mtu {
+        // 이후 chunk_data_size와 session()이 탐색한 크기를 쓰도록 Accept에 반영
+        session.accept.mtu = handshake::probe_mtu(socket, &session, config.psk.as_ref(), MIN_PROBE_MTU, session.accept.mtu)
+            .context("MTU 탐색 실패")?;
+        println!("📏 MTU 탐색 결과: {} 바이트", session.accept.mtu);
+    }
+
+    let accept = session.accept;
+    println!("✅ 서버에 연결됨: {} (세션 {:016x}, {:?}{})", session.peer, accept.session_id, accept.codec,
+        match &session.protection {
+            Some(p) if p.is_encrypted() => ", 암호화됨",
+            Some(_) => ", 인증됨",
+            None => "",
+        });
+    if accept.codec != config.codec {
+        println!("⚠️ 서버가 {:?} 대신 {:?} 코덱을 선택함", config.codec, accept.codec);
+    }
+    Ok(session)
+}
+
+fn chunk_data_size(session: &handshake::Session) -> Result<usize> {
+    let chunk_data_size = (session.accept.mtu as usize)
+        .saturating_sub(HEADER_SIZE + crypto::overhead(session.protection.as_ref()));
+    // 큰 프레임은 헤더가 더 길어지므로 그만큼 남아 있어야 함
+    if chunk_data_size <= WIDE_HEADER_SIZE - HEADER_SIZE {
+        anyhow::bail!("서버가 너무 작은 MTU를 지정함: {}", session.accept.mtu);
+    }
+    Ok(chunk_data_size)
+}
+
+impl WebcamSender {
+    // server_ip로 config.endpoint.peer를 덮어씀 (포트는 config.endpoint.port)
+    pub fn connect(server_ip: &str, width: usize, height: usize, mut config: SenderConfig) -> Result<Self> {
+        config.endpoint.peer = Some(server_ip.to_string());
+        Self::connect_with(width, height, config)
+    }
+
+    // config.endpoint.peer의 수신측에 연결
+    pub fn connect_with(width: usize, height: usize, config: SenderConfig) -> Result<Self> {
+        let (socket, server) = config.endpoint.connect_udp()
+            .context("UDP 소켓 준비 실패")?;
+
+        let mut state = StateNotifier::new(config.events.clone());
+        state.set(ConnectionState::Connecting);
+        println!("🔄 서버 {}에 연결 시도 중...", server);
+
+        let session = handshake(&socket, server, width, height, &config, Duration::from_secs(CONNECTION_TIMEOUT_SECS))?;
+        let chunk_data_size = chunk_data_size(&session)?;
+        state.set(ConnectionState::Connected);
+
+        let now = Instant::now();
+        Ok(Self {
+            socket,
+            server_addr: session.peer,
+            width,
+            height,
+            pacer: Pacer::new(config.pacing),
+            scheduler: config.target_fps.map(FrameScheduler::new),
+            delta: config.delta.map(DeltaEncoder::new),
+            config,
+            session: session.accept,
+            protection: session.protection,
+            chunk_data_size,
+            next_frame_id: 0,
+            retransmit_buffer: VecDeque::new(),
+            retransmitted_chunks: 0,
+            state,
+            last_heard: now,
+            last_heartbeat_at: now,
+            last_reconnect_at: now,
+            clock: now,
+        })
+    }
+
+    pub fn config(&self) -> &SenderConfig {
+        &self.config
+    }
+
+    pub fn state(&self) -> ConnectionState {
+        self.state.state().unwrap_or(ConnectionState::Connecting)
+    }
+
+    // 연결 시 합의된 코덱, 해상도, MTU 등
+    pub fn session(&self) -> &Accept {
+        &self.session
+    }
+
+    pub fn retransmitted_chunks(&self) -> u64 {
+        self.retransmitted_chunks
+    }
+
+    // 혼잡 제어가 정한 현재 전송률 (bit/s)
+    pub fn bitrate(&self) -> u64 {
+        self.pacer.bitrate()
+    }
+
+    // 송신기가 만들어진 뒤 지난 시간 (µs)
+    // 캡처 직후에 읽어서 Frame.timestamp_us로 사용 (벽시계와 달리 뒤로 가지 않음)
+    pub fn timestamp_us(&self) -> u64 {
+        self.clock.elapsed().as_micros() as u64
+    }
+
+    // target_fps에 맞춰 다음 캡처 시각까지 대기 (target_fps가 없으면 바로 반환)
+    // 기다리는 동안에도 heartbeat와 피드백을 처리
+    pub fn wait_next_frame(&mut self) -> Result<()> {
+        let Some(next_at) = self.scheduler.as_ref().map(FrameScheduler::next_at) else {
+            return Ok(());
+        };
+        loop {
+            let remaining = next_at.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(self.config.liveness.heartbeat_interval));
+            self.keep_alive()?;
+        }
+        if let Some(scheduler) = &mut self.scheduler {
+            scheduler.advance(Instant::now());
+        }
+        Ok(())
+    }
+
+    // 캡처가 target_fps를 따라가지 못해 건너뛴 프레임 수
+    pub fn missed_frames(&self) -> u64 {
+        self.scheduler.as_ref().map_or(0, FrameScheduler::missed)
+    }
+
+    // 프레임을 보내지 않는 동안에도 주기적으로 호출하면
+    // 피드백 처리, heartbeat 전송, 끊김 감지/재연결을 수행
+    // 연결되어 있으면 true
+    pub fn keep_alive(&mut self) -> Result<bool> {
+        self.handle_feedback()?;
+
+        let liveness = self.config.liveness;
+        if self.last_heard.elapsed() <= liveness.timeout {
+            if self.last_heartbeat_at.elapsed() >= liveness.heartbeat_interval {
+                self.send_control(&ControlMessage::Heartbeat);
+            }
+            return Ok(true);
+        }
+
+        if !self.state.is_lost() {
+            println!("⚠️ 서버가 {:.1}초 동안 응답하지 않음. 다시 연결합니다", liveness.timeout.as_secs_f32());
+            self.state.set(ConnectionState::Lost);
+        }
+        if self.last_reconnect_at.elapsed() < liveness.reconnect_interval {
+            return Ok(false);
+        }
+        self.last_reconnect_at = Instant::now();
+        Ok(self.reconnect(liveness.reconnect_interval))
+    }
+
+    // 새 세션으로 다시 handshake (실패하면 false)
+    fn reconnect(&mut self, timeout: Duration) -> bool {
+        let session = match handshake(&self.socket, self.server_addr, self.width, self.height, &self.config, timeout)
+            .and_then(|session| chunk_data_size(&session).map(|size| (session, size)))
+        {
+            Ok(connected) => connected,
+            Err(e) => {
+                eprintln!("⏳ 재연결 실패: {:#}", e);
+                return false;
+            }
+        };
+        let (session, chunk_data_size) = session;
+
+        self.server_addr = session.peer;
+        self.session = session.accept;
+        self.protection = session.protection;
+        self.chunk_data_size = chunk_data_size;
+        self.retransmit_buffer.clear();
+        self.pacer = Pacer::new(self.config.pacing);
+        // 새 세션의 수신측은 기준 화면이 없음
+        if let Some(delta) = &mut self.delta {
+            delta.request_keyframe();
+        }
+        self.last_heard = Instant::now();
+        self.state.set(ConnectionState::Reconnected);
+        true
+    }
+
+    fn send_control(&mut self, message: &ControlMessage) {
+        let mut packet = message.encode();
+        crypto::seal(self.protection.as_mut(), &mut packet);
+        if let Err(e) = self.socket.send_to(&packet, self.server_addr) {
+            eprintln!("⚠️ 제어 메시지 전송 실패: {}", e);
+        }
+        self.last_heartbeat_at = Instant::now();
+    }
+
+    // 수신측에서 온 NACK 등 제어 메시지를 논블로킹으로 처리
+    pub fn handle_feedback(&mut self) -> Result<()> {
+        self.socket.set_nonblocking(true)
+            .context("소켓 논블로킹 설정 실패")?;
+
+        let mut buf = [0u8; 2048];
+        let result = loop {
+            match self.socket.recv_from(&mut buf) {
+                Ok((size, addr)) => {
+                    // Accept 재전송 등 handshake 패킷은 무시
+                    if addr != self.server_addr || handshake::is_handshake(&buf[..size]) {
+                        continue;
+                    }
+                    // MAC이 맞지 않거나 복호화되지 않는 패킷은 조용히 버림
+                    let Some(packet) = crypto::open(self.protection.as_mut(), &buf[..size]) else {
+                        continue;
+                    };
+                    if !control::is_control(&packet) {
+                        continue;
+                    }
+                    self.last_heard = Instant::now();
+                    match ControlMessage::decode(&packet) {
+                        Ok(ControlMessage::Nack { frame_id, missing }) => self.retransmit(frame_id, &missing),
+                        Ok(ControlMessage::Report(report)) => self.pacer.on_report(&report),
+                        Ok(ControlMessage::Heartbeat) => {}
+                        Ok(ControlMessage::KeyframeRequest) => {
+                            if let Some(delta) = &mut self.delta {
+                                delta.request_keyframe();
+                            }
+                        }
+                        Err(e) => eprintln!("⚠️ 제어 메시지 해석 실패: {}", e),
+                    }
+                }
+                // Windows는 수신측 포트가 닫혀 있으면 ConnectionReset을 돌려줌 (이번에는 피드백 없음)
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::ConnectionReset) => break Ok(()),
+                Err(e) => break Err(e).context("제어 메시지 수신 실패"),
+            }
+        };
+
+        self.socket.set_nonblocking(false)
+            .context("소켓 블로킹 설정 실패")?;
+        result
+    }
+
+    fn retransmit(&mut self, frame_id: u32, missing: &[u32]) {
+        let Some(position) = self.retransmit_buffer.iter().position(|(id, _)| *id == frame_id) else {
+            return;
+        };
+        for &index in missing {
+            if let Some(packet) = self.retransmit_buffer[position].1.get(index as usize) {
+                // 암호화 카운터가 재사용되지 않도록 매번 새로 봉인
+                let mut packet = packet.clone();
+                crypto::seal(self.protection.as_mut(), &mut packet);
+                self.pacer.pace(packet.len());
+                if let Err(e) = self.socket.send_to(&packet, self.server_addr) {
+                    eprintln!("⚠️ 재전송 실패 (ID: {}, 청크 {}): {}", frame_id, index, e);
+                    continue;
+                }
+                self.retransmitted_chunks += 1;
+            }
+        }
+    }
+
+    // 프레임을 압축/분할해서 전송하고 (frame_id, 청크 수)를 반환
+    // frame.frame_id는 무시하고 송신기가 새로 매김
+    // 연결이 끊겨 재연결을 기다리는 동안에는 프레임을 버리고 청크 수 0을 반환
+    pub fn send(&mut self, frame: &Frame) -> Result<(u32, usize)> {
+        if !self.keep_alive()? {
+            return Ok((self.next_frame_id, 0));
+        }
+        let (width, height) = frame_size(frame.width, frame.height)?;
+
+        let frame_id = self.next_frame_id;
+        self.next_frame_id = self.next_frame_id.wrapping_add(1);
+
+        let delta = self.delta.as_mut()
+            .map(|delta| delta.encode(frame_id, &frame.packed(), frame.width, frame.height, frame.pixel_format));
+        let (flags, codec) = match &delta {
+            Some(DeltaFrame::Delta(_)) => (FLAG_DELTA, Codec::Raw),
+            Some(DeltaFrame::Key) => (FLAG_KEYFRAME, self.session.codec),
+            None => (0, self.session.codec),
+        };
+        let (payload, stride) = match (delta, codec) {
+            (Some(DeltaFrame::Delta(tiles)), _) => (Cow::Owned(tiles), frame.row_bytes()),
+            (_, Codec::Raw) => (Cow::Borrowed(&frame.data[..]), frame.stride),
+            _ => {
+                let encoded = codec::encode(
+                    codec,
+                    &frame.packed(),
+                    frame.width as u32,
+                    frame.height as u32,
+                    frame.pixel_format,
+                    self.config.jpeg_quality,
+                )
+                .context("프레임 인코딩 실패")?;
+                (Cow::Owned(encoded), frame.row_bytes())
+            }
+        };
+
+        // 청크 수가 u16을 넘으면 헤더가 4바이트 길어지므로 청크를 그만큼 줄임
+        let mut chunk_size = self.chunk_data_size;
+        if payload.len().div_ceil(chunk_size) > u16::MAX as usize {
+            chunk_size -= WIDE_HEADER_SIZE - HEADER_SIZE;
+        }
+        let num_chunks = payload.len().div_ceil(chunk_size);
+        if num_chunks > MAX_TOTAL_CHUNKS as usize {
+            anyhow::bail!("프레임이 너무 큼: {} 바이트 ({}개 청크)", payload.len(), num_chunks);
+        }
+
+        let fec_group = fec::group_size_for_overhead(self.config.fec_overhead);
+        let header = FrameHeader {
+            flags,
+            frame_id,
+            total_chunks: num_chunks as u32,
+            chunk_index: 0,
+            width,
+            height,
+            channels: frame.channels as u8,
+            pixel_format: frame.pixel_format,
+            codec,
+            stride: stride as u32,
+            fec_group,
+            payload_len: payload.len() as u32,
+            timestamp_us: frame.timestamp_us,
+            session_id: self.session.session_id,
+        };
+
+        let chunks: Vec<&[u8]> = payload.chunks(chunk_size).collect();
+        let keep_packets = self.config.retransmit_frames > 0;
+        let mut data_packets = Vec::new();
+        let mut sent = 0;
+        for (chunk_index, chunk_data) in chunks.iter().enumerate() {
+            let packet = self.send_chunk(FrameHeader { chunk_index: chunk_index as u32, ..header }, chunk_data, &mut sent);
+            if keep_packets {
+                data_packets.push(packet);
+            }
+
+            // 그룹의 마지막 데이터 청크 뒤에 패리티 청크 전송
+            let group_size = fec_group as usize;
+            if group_size > 0 && ((chunk_index + 1) % group_size == 0 || chunk_index + 1 == num_chunks) {
+                let group_index = chunk_index / group_size;
+                let range = fec::group_range(group_index, group_size, num_chunks);
+                let parity = fec::parity(chunks[range].iter().copied());
+                let parity_header = FrameHeader {
+                    flags: header.flags | FLAG_PARITY,
+                    chunk_index: group_index as u32,
+                    ..header
+                };
+                self.send_chunk(parity_header, &parity, &mut sent);
+            }
+
+            if sent >= FEEDBACK_CHECK_INTERVAL {
+                sent = 0;
+                self.handle_feedback()?;
+            }
+        }
+
+        if keep_packets {
+            self.retransmit_buffer.push_back((frame_id, data_packets));
+            while self.retransmit_buffer.len() > self.config.retransmit_frames {
+                self.retransmit_buffer.pop_front();
+            }
+        }
+        self.handle_feedback()?;
+
+        Ok((frame_id, num_chunks))
+    }
+
+    // 재전송용으로 보관할 수 있도록 봉인 전 평문 패킷을 반환
+    fn send_chunk(&mut self, header: FrameHeader, chunk_data: &[u8], sent: &mut usize) -> Vec<u8> {
+        let mut packet_buffer = Vec::with_capacity(header.encoded_len() + chunk_data.len());
+        header.write_to(&mut packet_buffer);
+        packet_buffer.extend_from_slice(chunk_data);
+
+        let mut sealed = packet_buffer.clone();
+        crypto::seal(self.protection.as_mut(), &mut sealed);
+        self.pacer.pace(sealed.len());
+        if let Err(e) = self.socket.send_to(&sealed, self.server_addr) {
+            eprintln!("⚠️ 청크 전송 실패 (ID: {}, {}/{}): {}",
+                header.frame_id, header.chunk_index + 1, header.total_chunks, e);
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        *sent += 1;
+        packet_buffer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handshake::{AcceptPolicy, Responder};
+
+    #[test]
+    fn feedback_ignores_closed_receiver_port() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let port = server.local_addr().unwrap().port();
+        let responder = thread::spawn(move || {
+            let policy = AcceptPolicy { stream_kind: StreamKind::Webcam, codecs: vec![Codec::Raw], max_mtu: DEFAULT_MTU };
+            let mut responder = Responder::new(policy, SecurityConfig::default());
+            responder.wait(&server).unwrap();
+        });
+
+        let config = SenderConfig { endpoint: EndpointConfig { port, ..Default::default() }, ..Default::default() };
+        let mut sender = WebcamSender::connect("127.0.0.1", 4, 2, config).unwrap();
+        // 수신측 소켓이 닫힌 뒤 보낸 패킷은 ICMP port unreachable로 돌아옴
+        responder.join().unwrap();
+        sender.send_control(&ControlMessage::Heartbeat);
+        thread::sleep(Duration::from_millis(50));
+
+        sender.handle_feedback().unwrap();
+        assert!(sender.keep_alive().unwrap());
+    }
+
+    #[test]
+    fn rejects_frame_size_above_u16() {
+        assert_eq!(frame_size(640, 480).unwrap(), (640, 480));
+        assert!(frame_size(u16::MAX as usize + 1, 480).is_err());
+        assert!(frame_size(640, 70_000).is_err());
+    }
+}
@@ -1,166 +1,73 @@
-use std::net::UdpSocket;
-use std::time::Duration;
-use std::collections::HashMap;
 use anyhow::{Result, Context};
-use opencv::{
-    prelude::*,
-    highgui,
-    core,
-};
 
-const HEADER_SIZE: usize = 4 + 2 + 2;
-const MAX_RECV_BUFFER_SIZE: usize = 65535;
+use crate::get_webcam::sink::FrameSink;
 
-#[allow(warnings)]
-pub fn server_main(img_width: usize, img_height: usize) -> Result<Vec<u8>> {
-    let server_addr = "0.0.0.0:52525";
-    let socket = UdpSocket::bind(server_addr).context("서버 바인딩 실패")?;
-    socket.set_read_timeout(Some(Duration::from_secs(1)))
-        .context("소켓 읽기 타임아웃 설정 실패")?;
-
-    println!("🚀 UDP 서버가 {}에서 시작되었습니다", server_addr);
-    println!("📡 클라이언트 연결을 기다리는 중...\n");
-
-    let mut buf = [0u8; MAX_RECV_BUFFER_SIZE];
-    let mut client_addr = None;
-
-    // --- HELLO 메시지 수신 대기 ---
-    loop {
-        match socket.recv_from(&mut buf) {
-            Ok((size, addr)) => {
-                println!("📨 {}로부터 {} 바이트 수신: {:?}", addr, size, &buf[..size]);
-
-                if &buf[..size] == b"HELLO" {
-                    println!("✅ 클라이언트 연결됨: {}", addr);
-                    client_addr = Some(addr);
-                    socket.send_to(b"CONNECTED", addr)
-                        .context("CONNECTED 메시지 전송 실패")?;
-                    break;
-                }
-            }
-            Err(_) => {
-                println!("⏳ 클라이언트 연결 대기중...");
-            }
-        }
-    }
-
-    println!("📊 클라이언트로부터 이미지 데이터 수신 중...\n");
-
-    let mut incomplete_frames: HashMap<u32, (u16, Vec<Option<Vec<u8>>>)> = HashMap::new();
-    let mut received_counts: HashMap<u32, u16> = HashMap::new();
-
-    highgui::named_window("Received Image", highgui::WINDOW_AUTOSIZE)
-        .context("OpenCV 창 생성 실패")?;
-
-    loop {
-        match socket.recv_from(&mut buf) {
-            Ok((size, addr)) => {
-                println!("📨 {}로부터 {} 바이트 수신: {:?}", addr, size, &buf[..size]);
-
-                if let Some(expected_addr) = client_addr {
-                    if addr != expected_addr {
-                        eprintln!("⚠️ 예상치 못한 클라이언트({})로부터 패킷 수신. 무시", addr);
-                        continue;
-                    }
-                }
-
-                if size < HEADER_SIZE {
-                    eprintln!("⚠️ 너무 작은 패킷 수신 ({} 바이트). 무시", size);
-                    continue;
-                }
-
-                let frame_id = u32::from_be_bytes(buf[0..4].try_into().unwrap());
-                let total_chunks = u16::from_be_bytes(buf[4..6].try_into().unwrap());
-                let chunk_index = u16::from_be_bytes(buf[6..8].try_into().unwrap());
-                let chunk_data = &buf[HEADER_SIZE..size];
+use super::convert::{OutputFormat, OutputFrame};
+use super::frame::Frame;
+use super::receiver::{DropStats, ReceiverConfig, StreamInfo, WebcamReceiver};
 
-                let (existing_total_chunks, frame_chunks) = incomplete_frames
-                    .entry(frame_id)
-                    .or_insert_with(|| (total_chunks, vec![None; total_chunks as usize]));
-
-                if *existing_total_chunks != total_chunks {
-                    eprintln!("⚠️ 프레임 ID {}의 총 청크 수 불일치: 기존 {}, 현재 {}", frame_id, *existing_total_chunks, total_chunks);
-                }
-
-                if (chunk_index as usize) >= total_chunks as usize {
-                    eprintln!("⚠️ 잘못된 청크 인덱스 {} / {}", chunk_index, total_chunks);
-                    continue;
-                }
+// 화면 없이 프레임 하나를 받아서 데이터를 반환
+pub fn server_main(img_width: usize, img_height: usize) -> Result<Vec<u8>> {
+    server_main_with(img_width, img_height, ReceiverConfig::default())
+}
 
-                if frame_chunks[chunk_index as usize].is_none() {
-                    frame_chunks[chunk_index as usize] = Some(chunk_data.to_vec());
-                    *received_counts.entry(frame_id).or_insert(0) += 1;
-                }
+// 포트, 바인딩 주소 등을 지정할 때 사용
+pub fn server_main_with(img_width: usize, img_height: usize, config: ReceiverConfig) -> Result<Vec<u8>> {
+    receive_one(img_width, img_height, config, None)
+}
 
-                let current_received_count = *received_counts.get(&frame_id).unwrap_or(&0);
+// 받은 프레임을 sink(예: HighguiSink)에도 보냄
+pub fn server_main_with_sink(img_width: usize, img_height: usize, config: ReceiverConfig, sink: &mut dyn FrameSink) -> Result<Vec<u8>> {
+    receive_one(img_width, img_height, config, Some(sink))
+}
 
-                if current_received_count == total_chunks {
-                    println!("✅ 프레임 ID {}의 {}개 청크 수신 완료!", frame_id, total_chunks);
+// 송신측 포맷(웹캠은 BGR) 대신 format으로 변환한 프레임을 반환
+pub fn server_main_as(img_width: usize, img_height: usize, config: ReceiverConfig, format: OutputFormat) -> Result<OutputFrame> {
+    Ok(receive_frame(img_width, img_height, config, None)?.convert(format))
+}
 
-                    let mut full_image_data = Vec::new();
-                    let mut complete = true;
-                    for chunk_opt in frame_chunks.iter() {
-                        if let Some(chunk) = chunk_opt {
-                            full_image_data.extend_from_slice(chunk);
-                        } else {
-                            eprintln!("❌ 청크 누락 발생. 프레임 ID {}", frame_id);
-                            complete = false;
-                            break;
-                        }
-                    }
+fn receive_one(img_width: usize, img_height: usize, config: ReceiverConfig, sink: Option<&mut dyn FrameSink>) -> Result<Vec<u8>> {
+    // ✅ 수신한 이미지 데이터를 그대로 반환
+    Ok(receive_frame(img_width, img_height, config, sink)?.into_packed())
+}
 
-                    if complete {
-                        let expected_width = img_width;
-                        let expected_height = img_height;
-                        let expected_channels = 3;
-                        let expected_total_size = expected_width * expected_height * expected_channels;
+fn receive_frame(img_width: usize, img_height: usize, config: ReceiverConfig, sink: Option<&mut dyn FrameSink>) -> Result<Frame> {
+    let mut receiver = WebcamReceiver::bind_with(config)?;
+    receiver.expect_size(img_width, img_height);
 
-                        if full_image_data.len() == expected_total_size {
-                            println!("🎨 재구성 완료: {} 바이트", full_image_data.len());
+    println!("🚀 UDP 서버가 {}에서 시작되었습니다", receiver.local_addr());
+    println!("📡 클라이언트 연결을 기다리는 중...\n");
+    println!("📊 클라이언트로부터 이미지 데이터 수신 중...\n");
 
-                            let mut image_mat = core::Mat::zeros(
-                                expected_height as i32,
-                                expected_width as i32,
-                                core::CV_8UC3,
-                            )
-                            .context("MatExpr 생성 실패")?
-                            .to_mat()
-                            .context("Mat 변환 실패")?;
+    let frame = receiver.next_frame()?;
+    if let Some(info) = receiver.stream(frame.stream_id) {
+        print_stream(&info);
+    }
+    print_drops(&receiver.drops());
+    println!("🎨 [{}] {} 재구성 완료: {}x{} {:?}, {} 바이트",
+        frame.stream_id, frame.source, frame.width, frame.height, frame.pixel_format, frame.data.len());
 
-                            if let Ok(mat_data_slice) = image_mat.data_bytes_mut() {
-                                if mat_data_slice.len() == full_image_data.len() {
-                                    mat_data_slice.copy_from_slice(&full_image_data);
-                                } else {
-                                    eprintln!("❌ 이미지 크기 불일치!");
-                                    continue;
-                                }
-                            } else {
-                                eprintln!("❌ Mat 바이트 접근 실패");
-                                continue;
-                            }
+    if let Some(sink) = sink {
+        sink.show("Received Image", &frame)
+            .context("이미지 표시 실패")?;
+    }
 
-                            highgui::imshow("Received Image", &image_mat)
-                                .context("이미지 표시 실패")?;
-                            highgui::wait_key(1)
-                                .context("WaitKey 실패")?;
+    Ok(frame)
+}
 
-                            // ✅ 수신한 이미지 데이터를 그대로 반환
-                            return Ok(full_image_data);
-                        } else {
-                            eprintln!("❌ 데이터 길이 불일치! 예상 {} 바이트, 실제 {} 바이트",
-                                expected_total_size, full_image_data.len());
-                        }
-                    }
+fn print_stream(info: &StreamInfo) {
+    let protection = match (info.encrypted, info.authenticated) {
+        (true, _) => ", 암호화됨",
+        (false, true) => ", 인증됨",
+        (false, false) => "",
+    };
+    println!("✅ [{}] {} 연결됨: {} (세션 {:016x}, {:?}{})",
+        info.stream_id, info.source, info.peer, info.session.session_id, info.session.codec, protection);
+}
 
-                    incomplete_frames.remove(&frame_id);
-                    received_counts.remove(&frame_id);
-                }
-            }
-            Err(e) => {
-                if e.kind() != std::io::ErrorKind::WouldBlock {
-                    eprintln!("⚠️ UDP 수신 오류: {}", e);
-                }
-            }
-        }
+// 프레임을 받기 전에 버린 것이 있으면 원인별로 알림
+fn print_drops(drops: &DropStats) {
+    if *drops != DropStats::default() {
+        eprintln!("⚠️ 수신 중 버린 패킷/프레임: {:?}", drops);
     }
 }
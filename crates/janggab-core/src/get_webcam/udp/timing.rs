@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// 송신측: 목표 FPS에 맞춰 다음 캡처 시각을 정함
+// 시각은 시작 시각 + n * 간격으로 계산해서 sleep 오차가 쌓이지 않음
+#[derive(Debug, Clone)]
+pub struct FrameScheduler {
+    interval: Duration,
+    next_at: Instant,
+    missed: u64,
+}
+
+impl FrameScheduler {
+    pub fn new(fps: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / fps.max(0.001)),
+            next_at: Instant::now(),
+            missed: 0,
+        }
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    // 캡처가 늦어서 건너뛴 프레임 수
+    pub fn missed(&self) -> u64 {
+        self.missed
+    }
+
+    // 다음 캡처 시각 (이미 지났으면 바로)
+    pub fn next_at(&self) -> Instant {
+        self.next_at
+    }
+
+    // 이번 프레임을 캡처했으니 다음 시각으로 넘어감
+    // 한 간격 이상 밀렸으면 밀린 프레임을 몰아서 찍지 않고 건너뜀
+    pub fn advance(&mut self, now: Instant) {
+        self.next_at += self.interval;
+        if now >= self.next_at + self.interval {
+            let behind = now.duration_since(self.next_at);
+            let skipped = (behind.as_nanos() / self.interval.as_nanos()) as u32;
+            self.missed += skipped as u64;
+            self.next_at += self.interval * skipped;
+        }
+    }
+}
+
+// 최근 이 시간 동안의 프레임으로 FPS를 계산
+const TIMING_WINDOW: Duration = Duration::from_secs(2);
+// 캡처 시각이 이보다 크게 되돌아가면 통계를 처음부터 다시 셈 (µs)
+const RESET_JUMP_US: u64 = 1_000_000;
+
+// 수신측이 송신측마다 보는 프레임 타이밍
+// 카메라가 멈추면 capture_fps가 떨어지고, 네트워크가 막히면 frame_age가 늘어남
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrameTiming {
+    // 송신측 캡처 타임스탬프 기준 FPS
+    pub capture_fps: f64,
+    // 수신측에서 프레임이 완성된 FPS
+    pub receive_fps: f64,
+    // 마지막 프레임이 가장 빨리 도착했던 프레임보다 늦게 도착한 정도
+    // 시계 차이를 뺄 수 없어서 절대 지연이 아닌 추가 지연
+    pub frame_age: Duration,
+    // 마지막 두 프레임의 캡처 간격
+    pub capture_gap: Duration,
+    // 마지막 프레임이 완성된 뒤 지난 시간
+    pub since_last_frame: Duration,
+}
+
+pub struct TimingTracker {
+    // (캡처 타임스탬프, 도착 시각)
+    samples: VecDeque<(u64, Instant)>,
+    base_offset: Option<i64>,
+    frame_age: Duration,
+    origin: Instant,
+}
+
+impl Default for TimingTracker {
+    fn default() -> Self {
+        Self {
+            samples: VecDeque::new(),
+            base_offset: None,
+            frame_age: Duration::ZERO,
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl TimingTracker {
+    pub fn on_frame(&mut self, capture_us: u64, now: Instant) {
+        // 재조립이 늦게 끝난 이전 프레임은 통계에 넣지 않음
+        // 크게 되돌아가면 송신측 시계가 다시 시작된 것으로 보고 처음부터 (새 세션은 새 tracker)
+        if let Some(&(last, _)) = self.samples.back()
+            && capture_us < last
+        {
+            if last - capture_us <= RESET_JUMP_US {
+                return;
+            }
+            *self = Self::default();
+        }
+
+        let offset = now.duration_since(self.origin).as_micros() as i64 - capture_us as i64;
+        let base = self.base_offset.map_or(offset, |b| b.min(offset));
+        self.base_offset = Some(base);
+        self.frame_age = Duration::from_micros((offset - base) as u64);
+
+        self.samples.push_back((capture_us, now));
+        while self.samples.front().is_some_and(|&(_, at)| now.duration_since(at) > TIMING_WINDOW) {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn timing(&self, now: Instant) -> FrameTiming {
+        let Some(&(last_us, last_at)) = self.samples.back() else {
+            return FrameTiming::default();
+        };
+        let capture_gap = self.samples.iter().rev().nth(1)
+            .map_or(Duration::ZERO, |&(prev_us, _)| Duration::from_micros(last_us - prev_us));
+
+        // 프레임이 끊긴 동안에는 창 밖으로 밀려난 만큼 FPS가 떨어짐
+        let recent: Vec<_> = self.samples.iter()
+            .filter(|&&(_, at)| now.duration_since(at) <= TIMING_WINDOW)
+            .collect();
+        let (capture_fps, receive_fps) = match (recent.first(), recent.last()) {
+            (Some(&&(first_us, first_at)), Some(&&(last_us, last_at))) if recent.len() > 1 => {
+                let frames = (recent.len() - 1) as f64;
+                let rate = |span: f64| if span > 0.0 { frames / span } else { 0.0 };
+                (rate((last_us - first_us) as f64 / 1_000_000.0), rate(last_at.duration_since(first_at).as_secs_f64()))
+            }
+            _ => (0.0, 0.0),
+        };
+
+        FrameTiming {
+            capture_fps,
+            receive_fps,
+            frame_age: self.frame_age,
+            capture_gap,
+            since_last_frame: now.duration_since(last_at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn assert_fps(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn scheduler_keeps_fixed_grid() {
+        let mut scheduler = FrameScheduler::new(10.0);
+        let start = scheduler.next_at();
+        assert_eq!(scheduler.interval(), ms(100));
+
+        // 조금 늦게 찍어도 다음 시각은 격자 위에 있음
+        scheduler.advance(start + ms(30));
+        assert_eq!(scheduler.next_at(), start + ms(100));
+        scheduler.advance(start + ms(150));
+        assert_eq!(scheduler.next_at(), start + ms(200));
+        assert_eq!(scheduler.missed(), 0);
+    }
+
+    #[test]
+    fn scheduler_skips_missed_frames() {
+        let mut scheduler = FrameScheduler::new(10.0);
+        let start = scheduler.next_at();
+
+        // 100ms 차례의 프레임을 550ms에야 찍음: 200, 300, 400은 건너뛰고 500부터 다시
+        scheduler.advance(start);
+        scheduler.advance(start + ms(550));
+        assert_eq!(scheduler.missed(), 3);
+        assert_eq!(scheduler.next_at(), start + ms(500));
+        scheduler.advance(start + ms(560));
+        assert_eq!(scheduler.next_at(), start + ms(600));
+        assert_eq!(scheduler.missed(), 3);
+    }
+
+    #[test]
+    fn tracker_measures_fps_and_frame_age() {
+        let mut tracker = TimingTracker::default();
+        let origin = tracker.origin;
+        for i in 0..=10 {
+            tracker.on_frame(i * 100_000, origin + ms(i * 100));
+        }
+        let timing = tracker.timing(origin + ms(1000));
+        assert_fps(timing.capture_fps, 10.0);
+        assert_fps(timing.receive_fps, 10.0);
+        assert_eq!(timing.capture_gap, ms(100));
+        assert_eq!(timing.frame_age, Duration::ZERO);
+        assert_eq!(timing.since_last_frame, Duration::ZERO);
+
+        // 가장 빨리 온 프레임보다 50ms 늦게 도착
+        tracker.on_frame(1_100_000, origin + ms(1150));
+        assert_eq!(tracker.timing(origin + ms(1150)).frame_age, ms(50));
+
+        // 프레임이 끊기면 창 밖으로 밀려나서 FPS가 0이 됨
+        let timing = tracker.timing(origin + ms(4000));
+        assert_fps(timing.capture_fps, 0.0);
+        assert_eq!(timing.since_last_frame, ms(2850));
+    }
+
+    #[test]
+    fn tracker_ignores_late_frames_and_resets_on_clock_jump() {
+        let mut tracker = TimingTracker::default();
+        let origin = tracker.origin;
+        tracker.on_frame(2_000_000, origin);
+        tracker.on_frame(2_100_000, origin + ms(100));
+
+        // 조금 되돌아간 프레임은 늦게 끝난 재조립이므로 무시
+        tracker.on_frame(2_050_000, origin + ms(120));
+        assert_eq!(tracker.timing(origin + ms(120)).capture_gap, ms(100));
+
+        // 크게 되돌아가면 송신측이 다시 시작한 것으로 보고 처음부터
+        tracker.on_frame(0, origin + ms(200));
+        assert_eq!(tracker.samples.len(), 1);
+        assert_eq!(tracker.timing(origin + ms(200)).capture_gap, Duration::ZERO);
+    }
+}
@@ -0,0 +1,1090 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::auth::{self, PacketAuth, PairingKey, NONCE_SIZE, RESPONSE_SIZE};
+use crate::crypto::{KeyPair, PacketCipher, Protection, Role, SecurityConfig, SessionKeys, PUBLIC_KEY_SIZE};
+use crate::get_webcam::udp::codec::Codec;
+
+// HELLO/CONNECTED 문자열을 대신하는 바이너리 연결 메시지
+// magic(2) | version(1) | type(1) | session_id(8) | body
+pub const HANDSHAKE_MAGIC: [u8; 2] = *b"JH";
+pub const HANDSHAKE_VERSION: u8 = 5;
+const HANDSHAKE_HEADER_SIZE: usize = 2 + 1 + 1 + 8;
+
+const TYPE_HELLO: u8 = 1;
+const TYPE_ACCEPT: u8 = 2;
+const TYPE_REJECT: u8 = 3;
+const TYPE_CHALLENGE: u8 = 4;
+const TYPE_RESPONSE: u8 = 5;
+const TYPE_PROBE: u8 = 6;
+const TYPE_PROBE_ACK: u8 = 7;
+
+const HELLO_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+// MTU 탐색: 크기마다 응답을 기다리는 시간과 시도 횟수
+const PROBE_TIMEOUT: Duration = Duration::from_millis(100);
+const PROBE_ATTEMPTS: usize = 2;
+// 이 정도 차이까지 좁혀지면 탐색을 멈춤
+const PROBE_PRECISION: u16 = 32;
+// 챌린지를 보낸 뒤 응답을 기다리는 세션 수 상한
+const MAX_PENDING: usize = 16;
+// 송신측 이름표 최대 길이 (UTF-8 바이트)
+pub const MAX_SOURCE_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKind {
+    Webcam,
+    Mic,
+    Screen,
+    // based 예제처럼 임의 데이터를 주고받는 경우
+    Data,
+}
+
+impl StreamKind {
+    fn to_u8(self) -> u8 {
+        match self {
+            StreamKind::Webcam => 0,
+            StreamKind::Mic => 1,
+            StreamKind::Screen => 2,
+            StreamKind::Data => 3,
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(StreamKind::Webcam),
+            1 => Some(StreamKind::Mic),
+            2 => Some(StreamKind::Screen),
+            3 => Some(StreamKind::Data),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    VersionMismatch,
+    UnsupportedStreamKind,
+    NoCommonCodec,
+    Busy,
+    // 수신측은 암호화를 요구하는데 송신측이 공개키를 보내지 않음
+    EncryptionRequired,
+}
+
+impl RejectReason {
+    fn to_u8(self) -> u8 {
+        match self {
+            RejectReason::VersionMismatch => 0,
+            RejectReason::UnsupportedStreamKind => 1,
+            RejectReason::NoCommonCodec => 2,
+            RejectReason::Busy => 3,
+            RejectReason::EncryptionRequired => 4,
+        }
+    }
+
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(RejectReason::VersionMismatch),
+            1 => Some(RejectReason::UnsupportedStreamKind),
+            2 => Some(RejectReason::NoCommonCodec),
+            3 => Some(RejectReason::Busy),
+            4 => Some(RejectReason::EncryptionRequired),
+            _ => None,
+        }
+    }
+}
+
+// 송신측 → 수신측: 원하는 스트림 조건
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hello {
+    pub session_id: u64,
+    pub stream_kind: StreamKind,
+    // 선호 순서대로
+    pub codecs: Vec<Codec>,
+    pub width: u16,
+    pub height: u16,
+    // 송신측 목표 FPS (0이면 제한 없음)
+    pub fps: u16,
+    pub mtu: u16,
+    // PSK 챌린지/응답에 쓰는 송신측 nonce
+    pub nonce: [u8; NONCE_SIZE],
+    // 암호화를 원하면 송신측 X25519 공개키
+    pub public_key: Option<[u8; PUBLIC_KEY_SIZE]>,
+    // 수신측에서 스트림을 구분할 때 보여 줄 이름 (예: "desk-left")
+    // MAX_SOURCE_LEN 바이트를 넘으면 잘림
+    pub source: String,
+}
+
+impl Hello {
+    // 새 세션 id와 nonce를 가진 기본 Hello (코덱 없음, 해상도/FPS 0, MTU 1400)
+    pub fn new(stream_kind: StreamKind) -> Self {
+        Self {
+            session_id: new_session_id(),
+            stream_kind,
+            codecs: Vec::new(),
+            width: 0,
+            height: 0,
+            fps: 0,
+            mtu: 1400,
+            nonce: auth::random_nonce(),
+            public_key: None,
+            source: String::new(),
+        }
+    }
+}
+
+// 수신측 → 송신측: 실제로 사용할 조건
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accept {
+    pub session_id: u64,
+    pub stream_kind: StreamKind,
+    pub codec: Codec,
+    pub width: u16,
+    pub height: u16,
+    pub fps: u16,
+    pub mtu: u16,
+    // Some이면 이 세션은 암호화됨 (수신측 X25519 공개키)
+    pub public_key: Option<[u8; PUBLIC_KEY_SIZE]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandshakeMessage {
+    Hello(Hello),
+    Accept(Accept),
+    Reject { session_id: u64, reason: RejectReason },
+    // PSK가 설정된 수신측 → 송신측
+    Challenge { session_id: u64, nonce: [u8; NONCE_SIZE] },
+    // 송신측 → 수신측: HMAC(PSK, session_id | 송신측 nonce | 수신측 nonce)
+    Response { session_id: u64, response: [u8; RESPONSE_SIZE] },
+    // 송신측 → 수신측: MTU 탐색용, 인코딩하면 size 바이트가 되도록 0으로 채움
+    Probe { session_id: u64, size: u16 },
+    // 수신측 → 송신측: size 크기의 Probe를 받았음
+    ProbeAck { session_id: u64, size: u16 },
+}
+
+#[derive(Debug)]
+pub enum HandshakeError {
+    BadMagic,
+    Truncated,
+    VersionMismatch { local: u8, remote: u8 },
+    UnknownMessageType(u8),
+    UnknownStreamKind(u8),
+    UnknownCodec(u8),
+    UnknownRejectReason(u8),
+    Rejected(RejectReason),
+    // 챌린지 응답이 PSK와 맞지 않음
+    AuthFailed,
+    Timeout,
+    Io(std::io::Error),
+}
+
+impl fmt::Display for HandshakeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HandshakeError::BadMagic => write!(f, "janggab 연결 메시지가 아님 (magic 불일치)"),
+            HandshakeError::Truncated => write!(f, "연결 메시지가 잘림"),
+            HandshakeError::VersionMismatch { local, remote } => {
+                write!(f, "프로토콜 버전 불일치: 내 버전 {}, 상대 버전 {}", local, remote)
+            }
+            HandshakeError::UnknownMessageType(t) => write!(f, "알 수 없는 연결 메시지 타입 {}", t),
+            HandshakeError::UnknownStreamKind(k) => write!(f, "알 수 없는 스트림 종류 {}", k),
+            HandshakeError::UnknownCodec(c) => write!(f, "알 수 없는 코덱 {}", c),
+            HandshakeError::UnknownRejectReason(r) => write!(f, "알 수 없는 거절 사유 {}", r),
+            HandshakeError::Rejected(reason) => write!(f, "상대가 연결을 거절함: {:?}", reason),
+            HandshakeError::AuthFailed => write!(f, "페어링 키 인증 실패"),
+            HandshakeError::Timeout => write!(f, "연결 시간 초과 (페어링 키가 다르면 응답이 없음)"),
+            HandshakeError::Io(e) => write!(f, "연결 중 네트워크 오류: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for HandshakeError {}
+
+impl From<std::io::Error> for HandshakeError {
+    fn from(e: std::io::Error) -> Self {
+        HandshakeError::Io(e)
+    }
+}
+
+// PSK 챌린지와 키 유도에 들어가므로 OS 난수로 만듦
+pub fn new_session_id() -> u64 {
+    let mut bytes = [0u8; 8];
+    getrandom::getrandom(&mut bytes).expect("OS 난수 생성 실패");
+    u64::from_le_bytes(bytes)
+}
+
+pub fn is_handshake(buf: &[u8]) -> bool {
+    buf.len() >= 2 && buf[0..2] == HANDSHAKE_MAGIC
+}
+
+fn read_u16(buf: &[u8], at: usize) -> u16 {
+    u16::from_be_bytes(buf[at..at + 2].try_into().unwrap())
+}
+
+fn read_codec(value: u8) -> Result<Codec, HandshakeError> {
+    Codec::from_u8(value).ok_or(HandshakeError::UnknownCodec(value))
+}
+
+// 공개키 유무(1) | 공개키(32)
+fn write_public_key(out: &mut Vec<u8>, public_key: &Option<[u8; PUBLIC_KEY_SIZE]>) {
+    match public_key {
+        Some(key) => {
+            out.push(1);
+            out.extend_from_slice(key);
+        }
+        None => out.push(0),
+    }
+}
+
+// 길이(1) | UTF-8 이름
+fn write_source(out: &mut Vec<u8>, source: &str) {
+    let mut end = source.len().min(MAX_SOURCE_LEN);
+    while !source.is_char_boundary(end) {
+        end -= 1;
+    }
+    out.push(end as u8);
+    out.extend_from_slice(&source.as_bytes()[..end]);
+}
+
+fn read_source(buf: &[u8]) -> Result<(String, &[u8]), HandshakeError> {
+    let len = *buf.first().ok_or(HandshakeError::Truncated)? as usize;
+    let name = buf.get(1..1 + len).ok_or(HandshakeError::Truncated)?;
+    Ok((String::from_utf8_lossy(name).into_owned(), &buf[1 + len..]))
+}
+
+fn read_public_key(buf: &[u8]) -> Result<Option<[u8; PUBLIC_KEY_SIZE]>, HandshakeError> {
+    match buf.first() {
+        Some(0) => Ok(None),
+        Some(_) => buf.get(1..1 + PUBLIC_KEY_SIZE)
+            .map(|key| Some(key.try_into().unwrap()))
+            .ok_or(HandshakeError::Truncated),
+        None => Err(HandshakeError::Truncated),
+    }
+}
+
+impl HandshakeMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(32);
+        out.extend_from_slice(&HANDSHAKE_MAGIC);
+        out.push(HANDSHAKE_VERSION);
+        match self {
+            HandshakeMessage::Hello(hello) => {
+                out.push(TYPE_HELLO);
+                out.extend_from_slice(&hello.session_id.to_be_bytes());
+                out.push(hello.stream_kind.to_u8());
+                out.extend_from_slice(&hello.width.to_be_bytes());
+                out.extend_from_slice(&hello.height.to_be_bytes());
+                out.extend_from_slice(&hello.fps.to_be_bytes());
+                out.extend_from_slice(&hello.mtu.to_be_bytes());
+                out.push(hello.codecs.len() as u8);
+                out.extend(hello.codecs.iter().map(|c| c.to_u8()));
+                out.extend_from_slice(&hello.nonce);
+                write_source(&mut out, &hello.source);
+                write_public_key(&mut out, &hello.public_key);
+            }
+            HandshakeMessage::Accept(accept) => {
+                out.push(TYPE_ACCEPT);
+                out.extend_from_slice(&accept.session_id.to_be_bytes());
+                out.push(accept.stream_kind.to_u8());
+                out.extend_from_slice(&accept.width.to_be_bytes());
+                out.extend_from_slice(&accept.height.to_be_bytes());
+                out.extend_from_slice(&accept.fps.to_be_bytes());
+                out.extend_from_slice(&accept.mtu.to_be_bytes());
+                out.push(accept.codec.to_u8());
+                write_public_key(&mut out, &accept.public_key);
+            }
+            HandshakeMessage::Reject { session_id, reason } => {
+                out.push(TYPE_REJECT);
+                out.extend_from_slice(&session_id.to_be_bytes());
+                out.push(reason.to_u8());
+            }
+            HandshakeMessage::Challenge { session_id, nonce } => {
+                out.push(TYPE_CHALLENGE);
+                out.extend_from_slice(&session_id.to_be_bytes());
+                out.extend_from_slice(nonce);
+            }
+            HandshakeMessage::Response { session_id, response } => {
+                out.push(TYPE_RESPONSE);
+                out.extend_from_slice(&session_id.to_be_bytes());
+                out.extend_from_slice(response);
+            }
+            HandshakeMessage::Probe { session_id, size } => {
+                out.push(TYPE_PROBE);
+                out.extend_from_slice(&session_id.to_be_bytes());
+                out.extend_from_slice(&size.to_be_bytes());
+                out.resize(out.len().max(*size as usize), 0);
+            }
+            HandshakeMessage::ProbeAck { session_id, size } => {
+                out.push(TYPE_PROBE_ACK);
+                out.extend_from_slice(&session_id.to_be_bytes());
+                out.extend_from_slice(&size.to_be_bytes());
+            }
+        }
+        out
+    }
+
+    pub fn decode(buf: &[u8]) -> Result<Self, HandshakeError> {
+        if buf.len() < 3 {
+            return Err(HandshakeError::Truncated);
+        }
+        if buf[0..2] != HANDSHAKE_MAGIC {
+            return Err(HandshakeError::BadMagic);
+        }
+        // 버전이 다르면 나머지 구조도 다를 수 있으므로 더 읽지 않음
+        if buf[2] != HANDSHAKE_VERSION {
+            return Err(HandshakeError::VersionMismatch { local: HANDSHAKE_VERSION, remote: buf[2] });
+        }
+        if buf.len() < HANDSHAKE_HEADER_SIZE {
+            return Err(HandshakeError::Truncated);
+        }
+
+        let session_id = u64::from_be_bytes(buf[4..12].try_into().unwrap());
+        let body = &buf[HANDSHAKE_HEADER_SIZE..];
+        match buf[3] {
+            TYPE_HELLO | TYPE_ACCEPT => {
+                if body.len() < 10 {
+                    return Err(HandshakeError::Truncated);
+                }
+                let stream_kind = StreamKind::from_u8(body[0])
+                    .ok_or(HandshakeError::UnknownStreamKind(body[0]))?;
+                let width = read_u16(body, 1);
+                let height = read_u16(body, 3);
+                let fps = read_u16(body, 5);
+                let mtu = read_u16(body, 7);
+
+                if buf[3] == TYPE_ACCEPT {
+                    return Ok(HandshakeMessage::Accept(Accept {
+                        session_id,
+                        stream_kind,
+                        codec: read_codec(body[9])?,
+                        width,
+                        height,
+                        fps,
+                        mtu,
+                        public_key: read_public_key(&body[10..])?,
+                    }));
+                }
+
+                let count = body[9] as usize;
+                if body.len() < 10 + count + NONCE_SIZE {
+                    return Err(HandshakeError::Truncated);
+                }
+                let codecs = body[10..10 + count]
+                    .iter()
+                    .map(|&c| read_codec(c))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let nonce = body[10 + count..10 + count + NONCE_SIZE].try_into().unwrap();
+                let (source, rest) = read_source(&body[10 + count + NONCE_SIZE..])?;
+                let public_key = read_public_key(rest)?;
+                Ok(HandshakeMessage::Hello(Hello {
+                    session_id,
+                    stream_kind,
+                    codecs,
+                    width,
+                    height,
+                    fps,
+                    mtu,
+                    nonce,
+                    public_key,
+                    source,
+                }))
+            }
+            TYPE_REJECT => {
+                let Some(&reason) = body.first() else {
+                    return Err(HandshakeError::Truncated);
+                };
+                let reason = RejectReason::from_u8(reason)
+                    .ok_or(HandshakeError::UnknownRejectReason(reason))?;
+                Ok(HandshakeMessage::Reject { session_id, reason })
+            }
+            TYPE_CHALLENGE => {
+                let nonce = body.get(..NONCE_SIZE).ok_or(HandshakeError::Truncated)?;
+                Ok(HandshakeMessage::Challenge { session_id, nonce: nonce.try_into().unwrap() })
+            }
+            TYPE_RESPONSE => {
+                let response = body.get(..RESPONSE_SIZE).ok_or(HandshakeError::Truncated)?;
+                Ok(HandshakeMessage::Response { session_id, response: response.try_into().unwrap() })
+            }
+            TYPE_PROBE | TYPE_PROBE_ACK => {
+                if body.len() < 2 {
+                    return Err(HandshakeError::Truncated);
+                }
+                let size = read_u16(body, 0);
+                if buf[3] == TYPE_PROBE_ACK {
+                    return Ok(HandshakeMessage::ProbeAck { session_id, size });
+                }
+                // 중간에 잘린 Probe는 그 크기가 통과하지 못한 것
+                if buf.len() < size as usize {
+                    return Err(HandshakeError::Truncated);
+                }
+                Ok(HandshakeMessage::Probe { session_id, size })
+            }
+            other => Err(HandshakeError::UnknownMessageType(other)),
+        }
+    }
+}
+
+// 수신측이 어떤 Hello를 받아들일지
+#[derive(Debug, Clone)]
+pub struct AcceptPolicy {
+    pub stream_kind: StreamKind,
+    // 받아들일 수 있는 코덱 (송신측 선호 순서 중 처음 겹치는 것을 사용)
+    pub codecs: Vec<Codec>,
+    pub max_mtu: u16,
+}
+
+impl AcceptPolicy {
+    pub fn evaluate(&self, hello: &Hello) -> Result<Accept, RejectReason> {
+        if hello.stream_kind != self.stream_kind {
+            return Err(RejectReason::UnsupportedStreamKind);
+        }
+
+        // 코덱을 쓰지 않는 스트림은 제안이 비어 있어도 됨
+        let codec = if hello.codecs.is_empty() {
+            Codec::Raw
+        } else {
+            *hello.codecs.iter()
+                .find(|c| self.codecs.contains(c))
+                .ok_or(RejectReason::NoCommonCodec)?
+        };
+
+        Ok(Accept {
+            session_id: hello.session_id,
+            stream_kind: hello.stream_kind,
+            codec,
+            width: hello.width,
+            height: hello.height,
+            fps: hello.fps,
+            mtu: hello.mtu.min(self.max_mtu),
+            public_key: None,
+        })
+    }
+}
+
+// 연결이 끝난 뒤 양쪽이 갖는 세션 정보
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub peer: SocketAddr,
+    pub hello: Hello,
+    pub accept: Accept,
+    // PSK나 암호화를 쓰면 이후 모든 데이터그램에 적용
+    pub protection: Option<Protection>,
+}
+
+fn send_message(socket: &UdpSocket, addr: SocketAddr, message: &HandshakeMessage, auth: Option<&PacketAuth>) -> std::io::Result<()> {
+    let mut packet = message.encode();
+    if let Some(auth) = auth {
+        auth.seal(&mut packet);
+    }
+    socket.send_to(&packet, addr)?;
+    Ok(())
+}
+
+fn open_message<'a>(auth: Option<&PacketAuth>, packet: &'a [u8]) -> Option<&'a [u8]> {
+    match auth {
+        Some(auth) => auth.open(packet),
+        None => Some(packet),
+    }
+}
+
+// 키 교환 결과와 PSK로 세션 데이터그램 보호 방식을 정함
+fn session_protection(psk: Option<&PairingKey>, keys: Option<&SessionKeys>, role: Role, hello: &Hello, server_nonce: Option<&[u8; NONCE_SIZE]>) -> Option<Protection> {
+    if let Some(keys) = keys {
+        return Some(Protection::Aead(PacketCipher::new(keys, role)));
+    }
+    match (psk, server_nonce) {
+        (Some(psk), Some(server_nonce)) => Some(Protection::Mac(PacketAuth::for_session(psk, hello.session_id, &hello.nonce, server_nonce))),
+        _ => None,
+    }
+}
+
+fn derive_session_keys(psk: Option<&PairingKey>, shared_secret: &[u8; 32], hello: &Hello, client_public: &[u8; PUBLIC_KEY_SIZE], server_public: &[u8; PUBLIC_KEY_SIZE]) -> SessionKeys {
+    SessionKeys::derive(psk, shared_secret, &[&hello.session_id.to_be_bytes(), &hello.nonce, client_public, server_public])
+}
+
+// 송신측: Accept를 받을 때까지 Hello(또는 챌린지 응답)를 반복 전송
+pub fn connect(socket: &UdpSocket, server_addr: impl ToSocketAddrs, hello: &Hello, security: &SecurityConfig, timeout: Duration) -> Result<Session, HandshakeError> {
+    let server_addr = server_addr.to_socket_addrs()?
+        .next()
+        .ok_or_else(|| HandshakeError::Io(std::io::Error::new(ErrorKind::InvalidInput, "서버 주소 없음")))?;
+    let previous_timeout = socket.read_timeout()?;
+    socket.set_read_timeout(Some(HELLO_RETRY_INTERVAL))?;
+
+    let result = connect_inner(socket, server_addr, hello, security, timeout);
+    socket.set_read_timeout(previous_timeout)?;
+    result
+}
+
+fn connect_inner(socket: &UdpSocket, server_addr: SocketAddr, hello: &Hello, security: &SecurityConfig, timeout: Duration) -> Result<Session, HandshakeError> {
+    let psk = security.psk.as_ref();
+    let handshake_auth = psk.map(PacketAuth::new);
+    let key_pair = security.encrypt.then(KeyPair::generate);
+    let hello = Hello {
+        public_key: key_pair.as_ref().map(KeyPair::public_key),
+        ..hello.clone()
+    };
+
+    let mut message = HandshakeMessage::Hello(hello.clone());
+    let mut server_nonce = None;
+    let start = Instant::now();
+    let mut buf = [0u8; 2048];
+
+    while start.elapsed() < timeout {
+        send_message(socket, server_addr, &message, handshake_auth.as_ref())?;
+        let retry_at = Instant::now() + HELLO_RETRY_INTERVAL;
+
+        while Instant::now() < retry_at {
+            let (size, addr) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                // Windows는 서버가 아직 안 떠 있으면 ConnectionReset을 돌려줌
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::ConnectionReset) => break,
+                Err(e) => return Err(e.into()),
+            };
+            if addr != server_addr || !is_handshake(&buf[..size]) {
+                continue;
+            }
+            // PSK를 모르는 상대가 보낸 메시지는 조용히 무시
+            let Some(packet) = open_message(handshake_auth.as_ref(), &buf[..size]) else {
+                continue;
+            };
+
+            let reply = match HandshakeMessage::decode(packet) {
+                Ok(reply) => reply,
+                // 다른 버전의 서버는 계속 기다려도 받아 주지 않음
+                Err(e @ HandshakeError::VersionMismatch { .. }) => return Err(e),
+                // 깨진 패킷 하나 때문에 연결을 포기하지 않고 다음 응답을 기다림
+                Err(e) => {
+                    eprintln!("⚠️ {}의 handshake 패킷 해석 실패: {}. 무시", addr, e);
+                    continue;
+                }
+            };
+            match reply {
+                HandshakeMessage::Challenge { session_id, nonce } if session_id == hello.session_id => {
+                    let Some(psk) = psk else {
+                        continue;
+                    };
+                    let response = auth::challenge_response(psk, session_id, &hello.nonce, &nonce);
+                    message = HandshakeMessage::Response { session_id, response };
+                    server_nonce = Some(nonce);
+                    send_message(socket, server_addr, &message, handshake_auth.as_ref())?;
+                }
+                HandshakeMessage::Accept(accept) if accept.session_id == hello.session_id => {
+                    // PSK가 있는데 챌린지 없이 온 Accept, 암호화를 요청했는데 공개키가 없는 Accept는 받지 않음
+                    if psk.is_some() && server_nonce.is_none() {
+                        continue;
+                    }
+                    let keys = match (&key_pair, accept.public_key) {
+                        (Some(key_pair), Some(server_public)) => Some(derive_session_keys(
+                            psk,
+                            &key_pair.shared_secret(&server_public),
+                            &hello,
+                            &key_pair.public_key(),
+                            &server_public,
+                        )),
+                        (Some(_), None) => continue,
+                        (None, _) => None,
+                    };
+                    let protection = session_protection(psk, keys.as_ref(), Role::Initiator, &hello, server_nonce.as_ref());
+                    return Ok(Session { peer: server_addr, hello, accept, protection });
+                }
+                HandshakeMessage::Reject { session_id, reason } if session_id == hello.session_id => {
+                    return Err(HandshakeError::Rejected(reason));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Err(HandshakeError::Timeout)
+}
+
+// 송신측: 연결된 세션으로 min..=max 크기의 데이터그램을 보내 보고
+// 수신측까지 실제로 도착한 가장 큰 크기를 찾음 (하나도 도착하지 않으면 min)
+pub fn probe_mtu(socket: &UdpSocket, session: &Session, psk: Option<&PairingKey>, min: u16, max: u16) -> Result<u16, HandshakeError> {
+    let previous_timeout = socket.read_timeout()?;
+    socket.set_read_timeout(Some(PROBE_TIMEOUT))?;
+
+    let result = search_mtu(socket, session, psk.map(PacketAuth::new).as_ref(), min.min(max), max);
+    socket.set_read_timeout(previous_timeout)?;
+    result
+}
+
+fn search_mtu(socket: &UdpSocket, session: &Session, auth: Option<&PacketAuth>, mut low: u16, max: u16) -> Result<u16, HandshakeError> {
+    // 크기는 봉인까지 끝난 데이터그램 기준
+    let overhead = if auth.is_some() { auth::MAC_SIZE } else { 0 };
+    let probe = |size: u16| probe_once(socket, session, auth, (size as usize).saturating_sub(overhead) as u16);
+
+    // 루프백처럼 max가 그대로 통과하면 한 번에 끝남
+    if probe(max)? {
+        return Ok(max);
+    }
+    let mut high = max.saturating_sub(1).max(low);
+    while high - low > PROBE_PRECISION {
+        let mid = low + (high - low) / 2;
+        if probe(mid)? {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+fn probe_once(socket: &UdpSocket, session: &Session, auth: Option<&PacketAuth>, size: u16) -> Result<bool, HandshakeError> {
+    let session_id = session.accept.session_id;
+    let mut buf = [0u8; 2048];
+    for _ in 0..PROBE_ATTEMPTS {
+        // 너무 큰 데이터그램은 보내는 단계에서 실패하기도 함 (EMSGSIZE)
+        if send_message(socket, session.peer, &HandshakeMessage::Probe { session_id, size }, auth).is_err() {
+            return Ok(false);
+        }
+        let deadline = Instant::now() + PROBE_TIMEOUT;
+        while Instant::now() < deadline {
+            let (received, addr) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::ConnectionReset) => break,
+                Err(e) => return Err(e.into()),
+            };
+            if addr != session.peer || !is_handshake(&buf[..received]) {
+                continue;
+            }
+            let Some(packet) = open_message(auth, &buf[..received]) else {
+                continue;
+            };
+            // 이전 크기에 대한 늦은 응답이나 Accept 재전송은 무시
+            if let Ok(HandshakeMessage::ProbeAck { session_id: id, size: acked }) = HandshakeMessage::decode(packet)
+                && id == session_id && acked == size
+            {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+struct PendingSession {
+    addr: SocketAddr,
+    hello: Hello,
+    accept: Accept,
+    server_nonce: [u8; NONCE_SIZE],
+    keys: Option<SessionKeys>,
+    created: Instant,
+}
+
+// 수신측 handshake 상태
+// 최근 세션을 기억해 두었다가 같은 Hello/응답이 다시 오면 같은 Accept를 보냄
+pub struct Responder {
+    policy: AcceptPolicy,
+    security: SecurityConfig,
+    handshake_auth: Option<PacketAuth>,
+    sessions: HashMap<u64, PendingSession>,
+    busy: bool,
+}
+
+impl Responder {
+    pub fn new(policy: AcceptPolicy, security: SecurityConfig) -> Self {
+        Self {
+            handshake_auth: security.psk.as_ref().map(PacketAuth::new),
+            policy,
+            security,
+            sessions: HashMap::new(),
+            busy: false,
+        }
+    }
+
+    // true인 동안 새 세션의 Hello는 Busy로 거절 (이미 성립한 세션의 재전송은 계속 응답)
+    pub fn set_busy(&mut self, busy: bool) {
+        self.busy = busy;
+    }
+
+    // 정책에 맞고 인증을 통과한 Hello가 올 때까지 대기
+    pub fn wait(&mut self, socket: &UdpSocket) -> Result<Session, HandshakeError> {
+        let mut buf = [0u8; 2048];
+        loop {
+            let (size, addr) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::ConnectionReset) => continue,
+                Err(e) => return Err(e.into()),
+            };
+            if !is_handshake(&buf[..size]) {
+                continue;
+            }
+
+            match self.handle(socket, addr, &buf[..size]) {
+                Ok(Some(session)) => return Ok(session),
+                Ok(None) => {}
+                Err(e) => eprintln!("⚠️ {}의 연결 요청 거절: {}", addr, e),
+            }
+        }
+    }
+
+    // handshake 패킷 하나를 처리하고 연결이 성립하면 Session 반환
+    // 이미 성립한 세션의 Hello/응답이 다시 오면 Accept를 다시 보냄 (Accept 유실 대비)
+    pub fn handle(&mut self, socket: &UdpSocket, addr: SocketAddr, packet: &[u8]) -> Result<Option<Session>, HandshakeError> {
+        // PSK를 모르는 상대의 패킷은 응답 없이 버림
+        let Some(packet) = open_message(self.handshake_auth.as_ref(), packet) else {
+            return Ok(None);
+        };
+
+        let message = match HandshakeMessage::decode(packet) {
+            Ok(message) => message,
+            Err(e @ HandshakeError::VersionMismatch { .. }) => {
+                // 버전이 달라도 session_id 위치는 같으므로 그대로 돌려줘야 송신측이 자기 거절로 인식함
+                let session_id = packet.get(4..HANDSHAKE_HEADER_SIZE).map_or(0, |id| u64::from_be_bytes(id.try_into().unwrap()));
+                let reject = HandshakeMessage::Reject { session_id, reason: RejectReason::VersionMismatch };
+                send_message(socket, addr, &reject, self.handshake_auth.as_ref())?;
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
+
+        match message {
+            HandshakeMessage::Hello(hello) => self.on_hello(socket, addr, hello),
+            HandshakeMessage::Response { session_id, response } => self.on_response(socket, addr, session_id, &response),
+            HandshakeMessage::Probe { session_id, size } => self.on_probe(socket, addr, session_id, size),
+            _ => Ok(None),
+        }
+    }
+
+    fn reject(&self, socket: &UdpSocket, addr: SocketAddr, session_id: u64, reason: RejectReason) -> Result<Option<Session>, HandshakeError> {
+        let reject = HandshakeMessage::Reject { session_id, reason };
+        send_message(socket, addr, &reject, self.handshake_auth.as_ref())?;
+        Err(HandshakeError::Rejected(reason))
+    }
+
+    fn on_hello(&mut self, socket: &UdpSocket, addr: SocketAddr, hello: Hello) -> Result<Option<Session>, HandshakeError> {
+        let session_id = hello.session_id;
+        let known = self.sessions.get(&session_id).is_some_and(|s| s.addr == addr && s.hello == hello);
+        if !known {
+            if self.busy {
+                return self.reject(socket, addr, session_id, RejectReason::Busy);
+            }
+            let mut accept = match self.policy.evaluate(&hello) {
+                Ok(accept) => accept,
+                Err(reason) => return self.reject(socket, addr, session_id, reason),
+            };
+            if self.security.encrypt && hello.public_key.is_none() {
+                return self.reject(socket, addr, session_id, RejectReason::EncryptionRequired);
+            }
+
+            // 송신측이 공개키를 보내면 수신측 설정과 관계없이 암호화
+            let keys = hello.public_key.map(|client_public| {
+                let key_pair = KeyPair::generate();
+                accept.public_key = Some(key_pair.public_key());
+                derive_session_keys(
+                    self.security.psk.as_ref(),
+                    &key_pair.shared_secret(&client_public),
+                    &hello,
+                    &client_public,
+                    &key_pair.public_key(),
+                )
+            });
+
+            if self.sessions.len() >= MAX_PENDING {
+                let oldest = self.sessions.iter().min_by_key(|(_, s)| s.created).map(|(&id, _)| id);
+                if let Some(oldest) = oldest {
+                    self.sessions.remove(&oldest);
+                }
+            }
+            self.sessions.insert(session_id, PendingSession {
+                addr,
+                hello,
+                accept,
+                server_nonce: auth::random_nonce(),
+                keys,
+                created: Instant::now(),
+            });
+        }
+
+        let pending = &self.sessions[&session_id];
+        if self.security.psk.is_some() {
+            let challenge = HandshakeMessage::Challenge { session_id, nonce: pending.server_nonce };
+            send_message(socket, addr, &challenge, self.handshake_auth.as_ref())?;
+            return Ok(None);
+        }
+
+        send_message(socket, addr, &HandshakeMessage::Accept(pending.accept), None)?;
+        Ok(Some(self.session_for(pending)))
+    }
+
+    fn on_response(&mut self, socket: &UdpSocket, addr: SocketAddr, session_id: u64, response: &[u8]) -> Result<Option<Session>, HandshakeError> {
+        let (Some(psk), Some(pending)) = (&self.security.psk, self.sessions.get(&session_id)) else {
+            return Ok(None);
+        };
+        if pending.addr != addr {
+            return Ok(None);
+        }
+        if !auth::verify_response(psk, session_id, &pending.hello.nonce, &pending.server_nonce, response) {
+            return Err(HandshakeError::AuthFailed);
+        }
+
+        send_message(socket, addr, &HandshakeMessage::Accept(pending.accept), self.handshake_auth.as_ref())?;
+        Ok(Some(self.session_for(pending)))
+    }
+
+    // 연결된 송신측의 Probe에만 응답 (모르는 상대의 큰 패킷을 그대로 반사하지 않도록)
+    fn on_probe(&self, socket: &UdpSocket, addr: SocketAddr, session_id: u64, size: u16) -> Result<Option<Session>, HandshakeError> {
+        if self.sessions.get(&session_id).is_some_and(|pending| pending.addr == addr) {
+            send_message(socket, addr, &HandshakeMessage::ProbeAck { session_id, size }, self.handshake_auth.as_ref())?;
+        }
+        Ok(None)
+    }
+
+    fn session_for(&self, pending: &PendingSession) -> Session {
+        let server_nonce = self.security.psk.is_some().then_some(&pending.server_nonce);
+        Session {
+            peer: pending.addr,
+            hello: pending.hello.clone(),
+            accept: pending.accept,
+            protection: session_protection(self.security.psk.as_ref(), pending.keys.as_ref(), Role::Responder, &pending.hello, server_nonce),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto;
+
+    fn round_trip(message: HandshakeMessage) {
+        let encoded = message.encode();
+        assert!(is_handshake(&encoded));
+        assert_eq!(HandshakeMessage::decode(&encoded).unwrap(), message);
+    }
+
+    #[test]
+    fn messages_round_trip() {
+        let hello = Hello {
+            codecs: vec![Codec::Jpeg, Codec::Raw],
+            width: 320,
+            height: 240,
+            fps: 15,
+            public_key: Some([3; PUBLIC_KEY_SIZE]),
+            source: "desk-left".to_string(),
+            ..Hello::new(StreamKind::Webcam)
+        };
+        let session_id = hello.session_id;
+        round_trip(HandshakeMessage::Hello(hello.clone()));
+        round_trip(HandshakeMessage::Hello(Hello { public_key: None, source: String::new(), ..hello }));
+        round_trip(HandshakeMessage::Accept(Accept {
+            session_id,
+            stream_kind: StreamKind::Screen,
+            codec: Codec::Qoi,
+            width: 640,
+            height: 480,
+            fps: 30,
+            mtu: 1200,
+            public_key: None,
+        }));
+        round_trip(HandshakeMessage::Reject { session_id, reason: RejectReason::Busy });
+        round_trip(HandshakeMessage::Challenge { session_id, nonce: [5; NONCE_SIZE] });
+        round_trip(HandshakeMessage::Response { session_id, response: [6; RESPONSE_SIZE] });
+        round_trip(HandshakeMessage::Probe { session_id, size: 600 });
+        round_trip(HandshakeMessage::ProbeAck { session_id, size: 600 });
+        assert_eq!(HandshakeMessage::Probe { session_id, size: 600 }.encode().len(), 600);
+    }
+
+    #[test]
+    fn long_source_is_cut_at_char_boundary() {
+        let hello = Hello { source: "가".repeat(30), ..Hello::new(StreamKind::Webcam) };
+        let HandshakeMessage::Hello(decoded) = HandshakeMessage::decode(&HandshakeMessage::Hello(hello).encode()).unwrap() else {
+            panic!("Hello가 아님");
+        };
+        // 3바이트 글자 21개 = 63바이트
+        assert_eq!(decoded.source, "가".repeat(21));
+    }
+
+    fn policy() -> AcceptPolicy {
+        AcceptPolicy { stream_kind: StreamKind::Webcam, codecs: vec![Codec::Raw], max_mtu: 1200 }
+    }
+
+    fn sockets() -> (UdpSocket, UdpSocket) {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client.set_read_timeout(Some(Duration::from_millis(100))).unwrap();
+        (server, client)
+    }
+
+    #[test]
+    fn version_mismatch() {
+        let encoded = HandshakeMessage::Reject { session_id: 1, reason: RejectReason::Busy }.encode();
+        let mut other_version = encoded.clone();
+        other_version[2] = HANDSHAKE_VERSION + 1;
+        assert!(matches!(
+            HandshakeMessage::decode(&other_version),
+            Err(HandshakeError::VersionMismatch { local: HANDSHAKE_VERSION, remote }) if remote == HANDSHAKE_VERSION + 1
+        ));
+        // 버전이 다르면 헤더가 짧아도 VersionMismatch
+        assert!(matches!(HandshakeMessage::decode(&other_version[..3]), Err(HandshakeError::VersionMismatch { .. })));
+
+        let hello = Hello { codecs: vec![Codec::Raw], ..Hello::new(StreamKind::Webcam) };
+        let session_id = hello.session_id;
+
+        // 수신측이 Hello를 다른 버전으로 보면 session_id를 그대로 담아 거절
+        let (server, client) = sockets();
+        let server_addr = server.local_addr().unwrap();
+        let old_server = std::thread::spawn(move || {
+            let mut responder = Responder::new(policy(), SecurityConfig::default());
+            let mut buf = [0u8; 2048];
+            let (size, addr) = server.recv_from(&mut buf).unwrap();
+            buf[2] = HANDSHAKE_VERSION + 1;
+            responder.handle(&server, addr, &buf[..size])
+        });
+        let result = connect_inner(&client, server_addr, &hello, &SecurityConfig::default(), Duration::from_secs(2));
+        assert!(matches!(result, Err(HandshakeError::Rejected(RejectReason::VersionMismatch))));
+        assert!(matches!(old_server.join().unwrap(), Err(HandshakeError::VersionMismatch { .. })));
+
+        // 수신측의 응답 자체가 다른 버전이면 송신측도 VersionMismatch로 끝냄
+        let (server, client) = sockets();
+        let server_addr = server.local_addr().unwrap();
+        let new_server = std::thread::spawn(move || {
+            let mut buf = [0u8; 2048];
+            let (_, addr) = server.recv_from(&mut buf).unwrap();
+            let mut reject = HandshakeMessage::Reject { session_id, reason: RejectReason::VersionMismatch }.encode();
+            reject[2] = HANDSHAKE_VERSION + 1;
+            server.send_to(&reject, addr).unwrap();
+        });
+        let result = connect_inner(&client, server_addr, &hello, &SecurityConfig::default(), Duration::from_secs(2));
+        assert!(matches!(
+            result,
+            Err(HandshakeError::VersionMismatch { local: HANDSHAKE_VERSION, remote }) if remote == HANDSHAKE_VERSION + 1
+        ));
+        new_server.join().unwrap();
+    }
+
+    #[test]
+    fn connect_skips_undecodable_packets() {
+        let (server, client) = sockets();
+        let server_addr = server.local_addr().unwrap();
+        let hello = Hello { codecs: vec![Codec::Raw], ..Hello::new(StreamKind::Webcam) };
+        let responder = std::thread::spawn(move || {
+            let mut responder = Responder::new(policy(), SecurityConfig::default());
+            let mut buf = [0u8; 2048];
+            let (size, addr) = server.recv_from(&mut buf).unwrap();
+            // 헤더만 있는 잘린 Reject를 먼저 보낸 뒤 정상 Accept
+            let reject = HandshakeMessage::Reject { session_id: 1, reason: RejectReason::Busy }.encode();
+            server.send_to(&reject[..HANDSHAKE_HEADER_SIZE], addr).unwrap();
+            responder.handle(&server, addr, &buf[..size]).unwrap().is_some()
+        });
+        let session = connect_inner(&client, server_addr, &hello, &SecurityConfig::default(), Duration::from_secs(2)).unwrap();
+        assert_eq!(session.accept.session_id, hello.session_id);
+        assert!(responder.join().unwrap());
+    }
+
+    fn secured(secret: &str) -> SecurityConfig {
+        SecurityConfig { psk: Some(PairingKey::new(secret)), encrypt: false }
+    }
+
+    // 수신측 스레드가 Session을 돌려주거나 패킷이 끊길 때까지 handle을 반복
+    fn spawn_responder(server: UdpSocket, security: SecurityConfig) -> std::thread::JoinHandle<Option<Session>> {
+        std::thread::spawn(move || {
+            server.set_read_timeout(Some(Duration::from_millis(500))).unwrap();
+            let mut responder = Responder::new(policy(), security);
+            let mut buf = [0u8; 2048];
+            while let Ok((size, addr)) = server.recv_from(&mut buf) {
+                if let Ok(Some(session)) = responder.handle(&server, addr, &buf[..size]) {
+                    return Some(session);
+                }
+            }
+            None
+        })
+    }
+
+    #[test]
+    fn psk_handshake_shares_session_mac() {
+        let (server, client) = sockets();
+        let server_addr = server.local_addr().unwrap();
+        let responder = spawn_responder(server, secured("pairing"));
+
+        let hello = Hello { codecs: vec![Codec::Raw], ..Hello::new(StreamKind::Webcam) };
+        let mut session = connect_inner(&client, server_addr, &hello, &secured("pairing"), Duration::from_secs(2)).unwrap();
+        let mut accepted = responder.join().unwrap().expect("같은 키면 연결됨");
+        assert!(matches!(session.protection, Some(Protection::Mac(_))));
+
+        let mut packet = b"chunk".to_vec();
+        crypto::seal(session.protection.as_mut(), &mut packet);
+        assert_eq!(crypto::open(accepted.protection.as_mut(), &packet).as_deref(), Some(&b"chunk"[..]));
+    }
+
+    #[test]
+    fn psk_mismatch_is_ignored() {
+        let (server, client) = sockets();
+        let server_addr = server.local_addr().unwrap();
+        let responder = spawn_responder(server, secured("pairing"));
+
+        // 다른 키로 봉인한 Hello는 수신측이 응답 없이 버리므로 송신측은 시간 초과
+        let hello = Hello { codecs: vec![Codec::Raw], ..Hello::new(StreamKind::Webcam) };
+        let result = connect_inner(&client, server_addr, &hello, &secured("other"), Duration::from_millis(300));
+        assert!(matches!(result, Err(HandshakeError::Timeout)));
+        assert!(responder.join().unwrap().is_none());
+    }
+
+    #[test]
+    fn wrong_challenge_response_is_auth_failed() {
+        let (server, client) = sockets();
+        let server_addr = server.local_addr().unwrap();
+        let psk = PairingKey::new("pairing");
+        let auth = PacketAuth::new(&psk);
+        let mut responder = Responder::new(policy(), secured("pairing"));
+        let mut buf = [0u8; 2048];
+
+        let hello = Hello { codecs: vec![Codec::Raw], ..Hello::new(StreamKind::Webcam) };
+        let session_id = hello.session_id;
+        send_message(&client, server_addr, &HandshakeMessage::Hello(hello.clone()), Some(&auth)).unwrap();
+        let (size, addr) = server.recv_from(&mut buf).unwrap();
+        assert!(responder.handle(&server, addr, &buf[..size]).unwrap().is_none());
+
+        let size = client.recv(&mut buf).unwrap();
+        let Ok(HandshakeMessage::Challenge { nonce, .. }) = HandshakeMessage::decode(open_message(Some(&auth), &buf[..size]).unwrap()) else {
+            panic!("Challenge가 아님");
+        };
+
+        // handshake MAC은 맞지만 다른 키로 만든 응답
+        let response = auth::challenge_response(&PairingKey::new("other"), session_id, &hello.nonce, &nonce);
+        send_message(&client, server_addr, &HandshakeMessage::Response { session_id, response }, Some(&auth)).unwrap();
+        let (size, addr) = server.recv_from(&mut buf).unwrap();
+        assert!(matches!(responder.handle(&server, addr, &buf[..size]), Err(HandshakeError::AuthFailed)));
+
+        // 맞는 응답은 그대로 받아 줌
+        let response = auth::challenge_response(&psk, session_id, &hello.nonce, &nonce);
+        send_message(&client, server_addr, &HandshakeMessage::Response { session_id, response }, Some(&auth)).unwrap();
+        let (size, addr) = server.recv_from(&mut buf).unwrap();
+        assert!(responder.handle(&server, addr, &buf[..size]).unwrap().is_some());
+    }
+
+    #[test]
+    fn decode_errors() {
+        let encoded = HandshakeMessage::Reject { session_id: 1, reason: RejectReason::Busy }.encode();
+
+        assert!(matches!(HandshakeMessage::decode(b"XX\x05"), Err(HandshakeError::BadMagic)));
+        assert!(matches!(HandshakeMessage::decode(&encoded[..HANDSHAKE_HEADER_SIZE]), Err(HandshakeError::Truncated)));
+
+        let mut unknown_type = encoded.clone();
+        unknown_type[3] = 99;
+        assert!(matches!(HandshakeMessage::decode(&unknown_type), Err(HandshakeError::UnknownMessageType(99))));
+
+        let mut unknown_reason = encoded;
+        unknown_reason[HANDSHAKE_HEADER_SIZE] = 99;
+        assert!(matches!(HandshakeMessage::decode(&unknown_reason), Err(HandshakeError::UnknownRejectReason(99))));
+
+        // 중간에 잘린 Probe
+        let probe = HandshakeMessage::Probe { session_id: 1, size: 600 }.encode();
+        assert!(matches!(HandshakeMessage::decode(&probe[..500]), Err(HandshakeError::Truncated)));
+    }
+
+    #[test]
+    fn policy_picks_first_common_codec() {
+        let policy = AcceptPolicy { stream_kind: StreamKind::Webcam, codecs: vec![Codec::Raw, Codec::Png], max_mtu: 1200 };
+        let hello = Hello { codecs: vec![Codec::Jpeg, Codec::Png, Codec::Raw], ..Hello::new(StreamKind::Webcam) };
+        let accept = policy.evaluate(&hello).unwrap();
+        assert_eq!(accept.codec, Codec::Png);
+        assert_eq!(accept.mtu, 1200);
+
+        let jpeg_only = Hello { codecs: vec![Codec::Jpeg], ..hello.clone() };
+        assert_eq!(policy.evaluate(&jpeg_only), Err(RejectReason::NoCommonCodec));
+        let screen = Hello { stream_kind: StreamKind::Screen, ..hello };
+        assert_eq!(policy.evaluate(&screen), Err(RejectReason::UnsupportedStreamKind));
+    }
+}
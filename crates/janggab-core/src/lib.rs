@@ -1,6 +1,11 @@
+pub mod auth;
 pub mod based;
+pub mod connection;
+pub mod crypto;
+pub mod endpoint;
 pub mod get_mic;
 pub mod get_screen;
 pub mod get_webcam;
+pub mod handshake;
 pub mod sand_speaker;
 pub mod tools;
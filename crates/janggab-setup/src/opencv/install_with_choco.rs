@@ -16,8 +16,7 @@ pub fn choco_cmd() -> io::Result<()> {
         },
         Err(e) => {
             eprintln!("오류 발생: {}", e);
-            Err(io::Error::new(
-                io::ErrorKind::Other,
+            Err(io::Error::other(
                 format!("명령어 실행 실패: {}", e)
             ))
         }
@@ -39,8 +38,7 @@ pub fn install_equipments() -> io::Result<()> {
         },
         Err(e) => {
             eprintln!("오류 발생: {}", e);
-            Err(io::Error::new(
-                io::ErrorKind::Other,
+            Err(io::Error::other(
                 format!("명령어 실행 실패: {}", e)
             ))
         }
@@ -49,7 +47,7 @@ pub fn install_equipments() -> io::Result<()> {
 
 fn run_elevated_command(cmd: &str) -> io::Result<()> {
     let output = Command::new("powershell")
-        .args(&[
+        .args([
             "-NoProfile",
             "-ExecutionPolicy",
             "Bypass",
@@ -62,8 +60,7 @@ fn run_elevated_command(cmd: &str) -> io::Result<()> {
         Ok(())
     } else {
         let error_message = String::from_utf8_lossy(&output.stderr);
-        Err(io::Error::new(
-            io::ErrorKind::Other,
+        Err(io::Error::other(
             format!("명령어 실행 실패: {}", error_message)
         ))
     }
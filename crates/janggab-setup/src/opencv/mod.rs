@@ -20,8 +20,7 @@ pub fn opencv_setup_main() -> std::io::Result<()> {
                                 },
                                 Err(e) => {
                                     eprintln!("문제 발생: {}", e);
-                                    Err(std::io::Error::new(
-                                        std::io::ErrorKind::Other,
+                                    Err(std::io::Error::other(
                                         format!("명령어 실행 실패: {}", e)
                                     ))
                                 },
@@ -29,8 +28,7 @@ pub fn opencv_setup_main() -> std::io::Result<()> {
                         },
                         Err(e) => {
                             eprintln!("문제 발생: {}", e);
-                            Err(std::io::Error::new(
-                                std::io::ErrorKind::Other,
+                            Err(std::io::Error::other(
                                 format!("명령어 실행 실패: {}", e)
                             ))
                         },
@@ -38,8 +36,7 @@ pub fn opencv_setup_main() -> std::io::Result<()> {
                 },
                 Err(e) => {
                     eprintln!("문제 발생: {}", e);
-                    Err(std::io::Error::new(
-                        std::io::ErrorKind::Other,
+                    Err(std::io::Error::other(
                         format!("명령어 실행 실패: {}", e)
                     ))
                 },
@@ -47,8 +44,7 @@ pub fn opencv_setup_main() -> std::io::Result<()> {
         },
         Err(e) => {
             eprintln!("문제 발생: {}", e);
-            Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
+            Err(std::io::Error::other(
                 format!("명령어 실행 실패: {}", e)
             ))
         },
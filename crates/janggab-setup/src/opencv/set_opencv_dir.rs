@@ -41,8 +41,7 @@ pub fn dirs_cmd() -> io::Result<()> {
         }
         Err(e) => {
             eprintln!("오류 발생: {}", e);
-            Err(io::Error::new(
-                io::ErrorKind::Other,
+            Err(io::Error::other(
                 format!("명령어 실행 실패: {}", e)
             ))
         },
@@ -71,15 +70,14 @@ fn get_opencv_num_and_version() -> u32 {
         let file_name = file_name.to_string_lossy();
 
         // 정규식에 매칭되는 파일 찾기
-        if let Some(captures) = re.captures(&file_name) {
-            if let Some(version_str) = captures.get(1) {
-                let version = version_str.as_str().parse::<u32>()
-                    .expect("버전 숫자가 u32 형식으로 변환되지 않습니다");
-                
-                println!("발견된 OpenCV 라이브러리: {}", file_name);
-                println!("추출된 버전 숫자: {}", version);
-                return version;
-            }
+        if let Some(captures) = re.captures(&file_name)
+            && let Some(version_str) = captures.get(1) {
+            let version = version_str.as_str().parse::<u32>()
+                .expect("버전 숫자가 u32 형식으로 변환되지 않습니다");
+            
+            println!("발견된 OpenCV 라이브러리: {}", file_name);
+            println!("추출된 버전 숫자: {}", version);
+            return version;
         }
     }
 
@@ -88,7 +86,7 @@ fn get_opencv_num_and_version() -> u32 {
 
 pub fn run_elevated_command(cmd: &str) -> io::Result<()> {
     let output = Command::new("powershell")
-        .args(&[
+        .args([
             "-NoProfile",
             "-ExecutionPolicy",
             "Bypass",
@@ -101,8 +99,7 @@ pub fn run_elevated_command(cmd: &str) -> io::Result<()> {
         Ok(())
     } else {
         let error_message = String::from_utf8_lossy(&output.stderr);
-        Err(io::Error::new(
-            io::ErrorKind::Other,
+        Err(io::Error::other(
             format!("명령어 실행 실패: {}", error_message)
         ))
     }
@@ -21,8 +21,7 @@ pub fn bin_cmd() -> io::Result<()> {
         },
         Err(e) => {
             eprintln!("환경 변수 설정 실패: {}", e);
-            Err(io::Error::new(
-                io::ErrorKind::Other,
+            Err(io::Error::other(
                 format!("명령어 실행 실패: {}", e)
             ))
         },
@@ -31,7 +30,7 @@ pub fn bin_cmd() -> io::Result<()> {
 
 fn run_elevated_command(cmd: &str) -> io::Result<()> {
     let output = Command::new("powershell")
-        .args(&[
+        .args([
             "-NoProfile",
             "-ExecutionPolicy",
             "Bypass",
@@ -44,8 +43,7 @@ fn run_elevated_command(cmd: &str) -> io::Result<()> {
         Ok(())
     } else {
         let error_message = String::from_utf8_lossy(&output.stderr);
-        Err(io::Error::new(
-            io::ErrorKind::Other,
+        Err(io::Error::other(
             format!("명령어 실행 실패: {}", error_message)
         ))
     }
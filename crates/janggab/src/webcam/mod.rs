@@ -1,16 +1,81 @@
+pub use janggab_core::get_webcam::device::{CaptureBackend, DeviceInfo};
+use janggab_core::get_webcam::device::{CaptureConfig, DeviceSelector};
+pub use janggab_core::get_webcam::sink::{FrameSink, SharedSink};
+#[cfg(feature = "display")]
+pub use janggab_core::get_webcam::sink::HighguiSink;
+pub use janggab_core::get_webcam::udp::codec::Codec;
+pub use janggab_core::get_webcam::udp::convert::{OutputFormat, OutputFrame};
+pub use janggab_core::get_webcam::udp::delta::DeltaConfig;
+pub use janggab_core::get_webcam::udp::frame::Frame;
+pub use janggab_core::get_webcam::udp::header::PixelFormat;
+pub use janggab_core::get_webcam::udp::jitter::JitterPolicy;
+pub use janggab_core::get_webcam::udp::pacing::PacingConfig;
+pub use janggab_core::get_webcam::udp::reassembly::{NackConfig, ReassemblyConfig};
+pub use janggab_core::get_webcam::udp::receiver::{DropStats, ReceiverConfig, StreamEvent, StreamInfo, WebcamReceiver};
+pub use janggab_core::get_webcam::udp::sender::SenderConfig;
+pub use janggab_core::get_webcam::udp::timing::FrameTiming;
+pub use janggab_core::auth::PairingKey;
+pub use janggab_core::connection::{ConnectionState, LivenessConfig};
+pub use janggab_core::crypto::SecurityConfig;
+pub use janggab_core::endpoint::{EndpointConfig, IpFamily};
+pub use janggab_core::handshake::{Accept, HandshakeError, StreamKind};
+
 pub struct Windows;
 
 pub struct Wsl;
 
 impl Windows {
+    // 기존 사용자 코드와 호환되도록 이름은 new 그대로 (송신 루프를 실행)
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(ip: &str, width: i32, height: i32) {
         janggab_core::get_webcam::udp::client::client_main(ip, width, height).unwrap();
     }
+
+    // 압축 코덱 등을 지정할 때 사용
+    pub fn with_config(ip: &str, width: i32, height: i32, config: SenderConfig) {
+        janggab_core::get_webcam::udp::client::client_main_with(ip, width, height, config).unwrap();
+    }
+
+    // devices()의 번호로 카메라를 고르고 캡처 백엔드를 지정할 때 사용
+    // Windows 백엔드는 장치 이름을 알려주지 않으므로 이름 대신 번호로 고름
+    pub fn with_capture(ip: &str, width: i32, height: i32, config: SenderConfig, index: i32, backend: CaptureBackend) {
+        let capture = CaptureConfig { device: DeviceSelector::Index(index), backend };
+        janggab_core::get_webcam::udp::client::client_main_with_capture(ip, width, height, config, &capture).unwrap();
+    }
+
+    // 연결된 카메라 목록 (번호, 백엔드, 이름, 지원 해상도)
+    pub fn devices(backend: CaptureBackend) -> Vec<DeviceInfo> {
+        janggab_core::get_webcam::device::list_devices(backend).unwrap()
+    }
 }
 
 impl Wsl {
+    // 기존 사용자 코드와 호환되도록 이름은 new 그대로 (프레임 하나를 받아서 반환)
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(width: usize, height: usize) -> Vec<u8> {
-        let jgb_full_data = janggab_core::get_webcam::udp::server::server_main(width, height).unwrap();
-        jgb_full_data
+        janggab_core::get_webcam::udp::server::server_main(width, height).unwrap()
+    }
+
+    // 송신측의 BGR 그대로가 아니라 format(RGB, RGBA, GRAY, I420 등)으로 변환해서 받음
+    pub fn new_as(width: usize, height: usize, format: OutputFormat) -> OutputFrame {
+        janggab_core::get_webcam::udp::server::server_main_as(width, height, ReceiverConfig::default(), format).unwrap()
+    }
+
+    // 받은 프레임을 sink에도 보냄 (창에 띄우려면 `display` feature의 HighguiSink)
+    pub fn with_sink(width: usize, height: usize, sink: &mut dyn FrameSink) -> Vec<u8> {
+        janggab_core::get_webcam::udp::server::server_main_with_sink(width, height, ReceiverConfig::default(), sink).unwrap()
+    }
+
+    // 연결을 한 번만 맺고 프레임을 계속 받을 때 사용
+    pub fn stream(width: usize, height: usize) -> WebcamReceiver {
+        Self::stream_with(width, height, ReceiverConfig::default())
+    }
+
+    // NACK 재전송 요청 등을 켤 때 사용
+    pub fn stream_with(width: usize, height: usize, config: ReceiverConfig) -> WebcamReceiver {
+        let mut receiver = WebcamReceiver::bind_with(config).unwrap();
+        receiver.expect_size(width, height);
+        println!("🚀 UDP 서버가 {}에서 시작되었습니다", receiver.local_addr());
+        receiver
     }
 }
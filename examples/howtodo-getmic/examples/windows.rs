@@ -0,0 +1,2 @@
+// 마이크 기능은 아직 구현 전
+fn main() {}
@@ -0,0 +1,13 @@
+// this code must working on windows
+
+// print every camera the sender can open, then stream from the second one
+
+use janggab::webcam::{CaptureBackend, SenderConfig, Windows};
+
+fn main() {
+    for device in Windows::devices(CaptureBackend::Any) {
+        println!("{} [{}] {:?} {:?}", device.index, device.backend, device.name, device.resolutions);
+    }
+
+    Windows::with_capture("your ip", 320, 240, SenderConfig::default(), 1, CaptureBackend::Any);
+}
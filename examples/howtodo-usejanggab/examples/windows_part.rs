@@ -13,5 +13,5 @@ const TARGET_WIDTH: i32 = 320;
 const TARGET_HEIGHT: i32 = 240;
 
 fn main() {
-    let _ = janggab::webcam::Windows::new("your ip", TARGET_WIDTH, TARGET_HEIGHT);
+    janggab::webcam::Windows::new("your ip", TARGET_WIDTH, TARGET_HEIGHT);
 }
@@ -7,6 +7,6 @@ const TARGET_WIDTH: usize = 320;
 const TARGET_HEIGHT: usize = 240;
 
 fn main() {
-    let a = janggab::webcam::Wsl::new(320, 240);
+    let a = janggab::webcam::Wsl::new(TARGET_WIDTH, TARGET_HEIGHT);
     println!("{:?}", a);
 }
@@ -0,0 +1,25 @@
+// this code must working on wsl
+
+// handshake only once, then keep receiving frames
+// several windows machines (or cameras) can send to the same port at once
+
+fn main() {
+    let mut receiver = janggab::webcam::Wsl::stream(320, 240);
+    loop {
+        match receiver.next_frame() {
+            Ok(frame) => {
+                println!("[{} {}] frame {}: {}x{} {:?}", frame.stream_id, frame.source, frame.frame_id, frame.width, frame.height, frame.pixel_format);
+                // low capture_fps = camera stall, growing frame_age = network stall
+                if frame.frame_id.is_multiple_of(30)
+                    && let Some(info) = receiver.stream(frame.stream_id)
+                {
+                    println!("    {:?}", info.timing);
+                }
+            }
+            Err(e) => {
+                eprintln!("{:?}", e);
+                break;
+            }
+        }
+    }
+}